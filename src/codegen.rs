@@ -5,6 +5,10 @@
 //! - Shared object messages referenced by event classes
 //! - Per-class and shared-object enum definitions
 //! - An enum-value-map.json reference file
+//! - A field-metadata.json file with per-field requirement, group, profile,
+//!   and sibling information
+//! - A manifest.json listing the protos written, the requested classes, the
+//!   resolved objects, and the run's statistics
 //!
 //! The generated output is deterministic: identical input always produces
 //! byte-identical output. Fields are sorted alphabetically and numbered
@@ -12,615 +16,4901 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
 
 use crate::error::{Error, Result};
-use crate::schema::{OcsfAttribute, OcsfClass, OcsfObject, OcsfSchema};
+use crate::field_numbers::{
+    FIELD_NUMBERS_LOCK, FieldAllocator, FieldNumbers, MAX_FIELD_NUMBER, RESERVED_FIELD_NUMBERS,
+    field_numbers_lock_json,
+};
+use crate::schema::{
+    OcsfAttribute, OcsfClass, OcsfDeprecated, OcsfEnumValue, OcsfObject, OcsfSchema, SchemaVisitor,
+    available_classes, lookup_object, resolve_object_graph_with,
+};
 use crate::type_map::{
-    ocsf_to_proto_type, sanitize_object_name, to_enum_variant_name, to_pascal_case,
-    to_screaming_snake,
+    DefaultNaming, NamingStrategy, caption_to_field_name, known_scalar_type, sanitize_field_name,
+    sanitize_object_name, variant_name_or_value, version_to_slug,
 };
 
 /// Statistics collected during generation for reporting.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct GenerationStats {
     pub classes_generated: usize,
     pub objects_generated: usize,
     pub enums_generated: usize,
+    /// Fields emitted across every class and object message.
+    pub fields_generated: usize,
     pub deprecated_fields_skipped: usize,
+    /// Deprecated fields whose number and name were reserved
+    /// ([`DeprecatedHandling::Reserve`]).
+    pub deprecated_fields_reserved: usize,
+    /// Deprecated fields emitted with `[deprecated = true]`
+    /// ([`DeprecatedHandling::Emit`]).
+    pub deprecated_fields_emitted: usize,
+    /// Attributes left out for falling below
+    /// [`GenerationOptions::min_requirement`], deprecated or not.
+    pub requirement_filtered: usize,
+    /// Attributes that meet the requirement threshold but were left out for
+    /// a profile outside [`GenerationOptions::include_profiles`].
+    pub profile_filtered: usize,
+    /// Attributes left out by [`GenerationOptions::exclude_attributes`].
+    pub attributes_excluded: usize,
+    /// Event class and object messages marked `option deprecated = true;`.
+    pub deprecated_messages: usize,
     pub string_enum_fields_skipped: usize,
     pub unknown_types_defaulted: usize,
+    pub field_name_collisions_resolved: usize,
+    /// Enums given a synthetic zero value because OCSF defines no 0.
+    pub synthetic_zero_values: usize,
+    /// Referenced objects that get no message, having no fields to emit or
+    /// being the untyped base `object`; fields referencing them are `string`.
+    pub empty_objects_skipped: usize,
+    /// Attributes whose referenced object is not generated, as
+    /// `owner.attribute -> object_type`. Each was emitted as `string`.
+    pub dangling_references: Vec<String>,
+    /// Warnings from the run (missing objects, unknown types defaulted to
+    /// `string`, field name collisions resolved), in the order they were
+    /// met. Each is also logged with [`log::warn!`].
+    pub warnings: Vec<String>,
 }
 
-/// Generate proto files for the requested event classes.
-///
-/// Resolves the transitive object dependency graph, generates proto files
-/// for events, objects, and enums, and writes them to `output_dir`.
-///
-/// Returns generation statistics for reporting.
-pub fn generate(
-    schema: &OcsfSchema,
-    class_names: &[String],
-    output_dir: &Path,
-) -> Result<GenerationStats> {
-    let version_slug = version_to_slug(&schema.version);
-    let mut stats = GenerationStats::default();
+/// What a generation run wrote, as serialized to `ocsf/<slug>/manifest.json`.
+#[derive(Debug, Serialize)]
+pub struct GenerationManifest {
+    /// OCSF version of the schema (e.g., `1.7.0`).
+    pub ocsf_version: String,
 
-    // Validate all requested classes exist.
-    for name in class_names {
-        if !schema.classes.contains_key(name.as_str()) {
-            let available: Vec<&str> = schema.classes.keys().map(|s| s.as_str()).collect();
-            return Err(Error::ClassNotFound {
-                name: name.clone(),
-                available: if available.len() > 10 {
-                    format!(
-                        "{} ... and {} more",
-                        available[..10].join(", "),
-                        available.len() - 10
-                    )
-                } else {
-                    available.join(", ")
-                },
-            });
-        }
-    }
+    /// Requested class names, sorted.
+    pub classes: Vec<String>,
 
-    // Resolve which objects are needed (transitive closure via BFS).
-    let needed_objects = resolve_object_graph(schema, class_names);
+    /// Objects the classes reference that got a message, by sanitized name,
+    /// sorted.
+    pub objects: Vec<String>,
 
-    // Group classes by category for file organization.
-    let mut classes_by_category: BTreeMap<String, Vec<&OcsfClass>> = BTreeMap::new();
-    for name in class_names {
-        let cls = &schema.classes[name.as_str()];
-        classes_by_category
-            .entry(cls.category.clone())
-            .or_default()
-            .push(cls);
+    /// Every `.proto` written, relative to the output directory, sorted.
+    pub protos: Vec<String>,
+
+    /// Statistics for the run.
+    pub stats: GenerationStats,
+}
+
+impl GenerationStats {
+    /// Add `other`'s counts to these, appending its dangling references and
+    /// warnings.
+    fn merge(&mut self, other: GenerationStats) {
+        let GenerationStats {
+            classes_generated,
+            objects_generated,
+            enums_generated,
+            fields_generated,
+            deprecated_fields_skipped,
+            deprecated_fields_reserved,
+            deprecated_fields_emitted,
+            requirement_filtered,
+            profile_filtered,
+            attributes_excluded,
+            deprecated_messages,
+            string_enum_fields_skipped,
+            unknown_types_defaulted,
+            field_name_collisions_resolved,
+            synthetic_zero_values,
+            empty_objects_skipped,
+            dangling_references,
+            warnings,
+        } = other;
+        self.classes_generated += classes_generated;
+        self.objects_generated += objects_generated;
+        self.enums_generated += enums_generated;
+        self.fields_generated += fields_generated;
+        self.deprecated_fields_skipped += deprecated_fields_skipped;
+        self.deprecated_fields_reserved += deprecated_fields_reserved;
+        self.deprecated_fields_emitted += deprecated_fields_emitted;
+        self.requirement_filtered += requirement_filtered;
+        self.profile_filtered += profile_filtered;
+        self.attributes_excluded += attributes_excluded;
+        self.deprecated_messages += deprecated_messages;
+        self.string_enum_fields_skipped += string_enum_fields_skipped;
+        self.unknown_types_defaulted += unknown_types_defaulted;
+        self.field_name_collisions_resolved += field_name_collisions_resolved;
+        self.synthetic_zero_values += synthetic_zero_values;
+        self.empty_objects_skipped += empty_objects_skipped;
+        self.dangling_references.extend(dangling_references);
+        self.warnings.extend(warnings);
     }
 
-    // Generate event proto files per category.
-    for (category, classes) in &classes_by_category {
-        let events_proto = generate_events_proto(
-            &version_slug,
-            category,
-            classes,
-            &schema.objects,
-            &mut stats,
-        );
-        let enums_proto = generate_class_enums_proto(&version_slug, category, classes, &mut stats);
-
-        let category_dir = output_dir
-            .join("ocsf")
-            .join(&version_slug)
-            .join("events")
-            .join(category);
-        write_file(
-            &category_dir.join(format!("{category}.proto")),
-            &events_proto,
-        )?;
-        write_file(
-            &category_dir.join("enums").join("enums.proto"),
-            &enums_proto,
-        )?;
+    /// Log `message` at warn level and record it in
+    /// [`GenerationStats::warnings`].
+    fn warn(&mut self, message: String) {
+        log::warn!("{message}");
+        self.warnings.push(message);
     }
-    stats.classes_generated = class_names.len();
+}
 
-    // Generate shared objects proto.
-    let objects_proto = generate_objects_proto(&version_slug, schema, &needed_objects, &mut stats);
-    let object_enums_proto =
-        generate_object_enums_proto(&version_slug, schema, &needed_objects, &mut stats);
+/// Options controlling proto generation.
+///
+/// `GenerationOptions::default()` reproduces the output of [`generate`].
+#[derive(Debug, Clone)]
+pub struct GenerationOptions {
+    /// Naming conventions for generated messages, enums, and enum variants.
+    pub naming: Arc<dyn NamingStrategy>,
 
-    let objects_dir = output_dir.join("ocsf").join(&version_slug).join("objects");
-    write_file(&objects_dir.join("objects.proto"), &objects_proto)?;
-    write_file(
-        &objects_dir.join("enums").join("enums.proto"),
-        &object_enums_proto,
-    )?;
-    stats.objects_generated = needed_objects.len();
+    /// Glob patterns (`*`, `?`) selecting which enums appear in
+    /// `enum-value-map.json`, matched against the enum name
+    /// (e.g., `"AUTHENTICATION_*"`). Empty includes every enum.
+    pub enum_map_globs: Vec<String>,
 
-    // Generate enum-value-map.json reference.
-    let enum_map = generate_enum_value_map(schema, class_names, &needed_objects)?;
-    write_file(
-        &output_dir
-            .join("ocsf")
-            .join(&version_slug)
-            .join("enum-value-map.json"),
-        &enum_map,
-    )?;
+    /// Include shared-object enums in `enum-value-map.json`.
+    pub enum_map_include_objects: bool,
 
-    Ok(stats)
-}
+    /// Also write `enum-value-map-by-number.json`, mapping each enum name to
+    /// its values and their captions (`{"AUTHENTICATION_ACTIVITY_ID":
+    /// {"1": "Logon"}}`) for decoding. It covers the same enums as
+    /// `enum-value-map.json`.
+    pub emit_enum_map_by_number: bool,
 
-// ── Object graph resolution ────────────────────────────────────────────
+    /// Write `presence-caveats.json`, listing fields whose proto3 default
+    /// is indistinguishable from a meaningful OCSF value.
+    pub emit_presence_caveats: bool,
 
-/// Compute the transitive closure of all objects referenced by the requested
-/// event classes via BFS.
-///
-/// Starting from objects directly referenced by event class attributes,
-/// follows `object_type` references recursively until no new objects are
-/// found. Returns sanitized object names (extension prefixes stripped).
-fn resolve_object_graph(schema: &OcsfSchema, class_names: &[String]) -> BTreeSet<String> {
-    let mut needed: BTreeSet<String> = BTreeSet::new();
-    let mut queue: Vec<String> = Vec::new();
+    /// Write `buf.yaml` and `buf.gen.yaml` at the output directory root,
+    /// making it a Buf module whose `buf.gen.yaml` generates this version's
+    /// protos. Both are overwritten on every run.
+    pub emit_buf_config: bool,
 
-    // Seed with objects directly referenced by requested classes.
-    for name in class_names {
-        if let Some(cls) = schema.classes.get(name.as_str()) {
-            for attr in cls.attributes.values() {
-                if let Some(obj_type) = &attr.object_type {
-                    let key = sanitize_object_name(obj_type);
-                    if needed.insert(key.clone()) {
-                        queue.push(obj_type.clone());
-                    }
-                }
-            }
-        }
-    }
+    /// Render every file but write none and create no directories:
+    /// [`generate_with_options`] and [`generate_with_manifest`] log each
+    /// path they would write, with its size in bytes, at `info` instead.
+    /// [`generate_to_map`] gives the contents themselves.
+    pub dry_run: bool,
 
-    // BFS: follow object → object references.
-    while let Some(obj_ref) = queue.pop() {
-        if let Some(obj) = lookup_object(schema, &obj_ref) {
-            for attr in obj.attributes.values() {
-                if let Some(obj_type) = &attr.object_type {
-                    let key = sanitize_object_name(obj_type);
-                    if needed.insert(key.clone()) {
-                        queue.push(obj_type.clone());
-                    }
-                }
-            }
-        }
-    }
+    /// Emit `option deprecated = true;` in every generated file, for
+    /// versions that have been superseded.
+    pub mark_file_deprecated: bool,
 
-    needed
-}
+    /// Fail generation with [`Error::Codegen`] if any event class message
+    /// would have no fields (e.g., every attribute is deprecated). Objects
+    /// with no fields never get a message; see
+    /// [`GenerationStats::empty_objects_skipped`].
+    pub reject_empty_messages: bool,
 
-/// Look up an object by name, handling extension-prefixed names.
-///
-/// OCSF extension objects use path-prefixed names (e.g., `"win/win_service"`).
-/// This function tries the original name first, then the sanitized name,
-/// then searches all objects by sanitized name comparison.
-fn lookup_object<'a>(schema: &'a OcsfSchema, name: &str) -> Option<&'a OcsfObject> {
-    schema.objects.get(name).or_else(|| {
-        let sanitized = sanitize_object_name(name);
-        schema.objects.get(&sanitized).or_else(|| {
-            schema
-                .objects
-                .values()
-                .find(|o| sanitize_object_name(&o.name) == sanitized)
-        })
-    })
-}
+    /// Fail generation with [`Error::Codegen`], naming the class or object
+    /// and attribute, instead of emitting `string` for an attribute of
+    /// unknown type or one that references a missing object.
+    pub strict: bool,
 
-// ── Event class proto generation ───────────────────────────────────────
+    /// After rendering, check that `enum-value-map.json` lists exactly the
+    /// variants of the enums it covers that the generated protos define
+    /// (synthetic zero values aside), failing with [`Error::Codegen`] naming
+    /// the first enum that disagrees. A self-test of the generator; skipped
+    /// under [`EnumMode::Int32WithComment`], which defines no enums.
+    pub verify_enum_map: bool,
 
-fn generate_events_proto(
-    version_slug: &str,
-    category: &str,
-    classes: &[&OcsfClass],
-    objects: &BTreeMap<String, OcsfObject>,
-    stats: &mut GenerationStats,
-) -> String {
-    let mut out = String::new();
+    /// Where proto field names come from.
+    pub field_name_source: FieldNameSource,
 
-    writeln!(out, "syntax = \"proto3\";").unwrap();
-    writeln!(out).unwrap();
-    writeln!(out, "package ocsf.{version_slug}.events.{category};").unwrap();
-    writeln!(out).unwrap();
-    writeln!(
-        out,
-        "import \"ocsf/{version_slug}/events/{category}/enums/enums.proto\";"
-    )
-    .unwrap();
-    writeln!(out).unwrap();
-    writeln!(out, "import \"ocsf/{version_slug}/objects/objects.proto\";").unwrap();
+    /// Order of the fields in each message.
+    pub field_order: FieldOrder,
 
-    for cls in classes {
-        let class_upper = to_screaming_snake(&cls.name);
+    /// Place extension classes and objects (`win/win_service`) in an
+    /// extension-scoped package and directory, e.g. `ocsf.v1_7_0.ext.win.objects`
+    /// under `ocsf/v1_7_0/ext/win/objects/`, instead of alongside core types.
+    ///
+    /// Core and extension object files import each other as needed, so
+    /// objects that reference each other across the two would form an
+    /// import cycle; see [`check_import_graph`].
+    pub extension_packages: bool,
 
-        writeln!(out).unwrap();
-        writeln!(out, "// Event: {category}").unwrap();
-        writeln!(out, "// Class UID: {}", cls.uid).unwrap();
-        writeln!(out, "message {} {{", to_pascal_case(&cls.name)).unwrap();
+    /// Field numbers to keep, keyed by `Message.field_name` (see
+    /// [`crate::field_numbers`]). Other fields are numbered around them.
+    pub pinned_field_numbers: FieldNumbers,
 
-        let mut field_num = 1u32;
-        for (attr_name, attr) in &cls.attributes {
-            if attr.deprecated.is_some() {
-                stats.deprecated_fields_skipped += 1;
-                continue;
-            }
+    /// The contents of an earlier run's `field-numbers.lock`, or an empty
+    /// map to start one. When set, fields keep their locked numbers, new
+    /// fields are numbered after the highest locked number of their message,
+    /// removed fields are emitted as `reserved`, and the updated lock is
+    /// written to [`FIELD_NUMBERS_LOCK`] at the root of the output directory.
+    /// Pins in [`GenerationOptions::pinned_field_numbers`] take precedence.
+    pub field_numbers_lock: Option<FieldNumbers>,
 
-            let (repeated, proto_type) = resolve_event_field_type(
-                attr,
-                attr_name,
-                &class_upper,
-                version_slug,
-                category,
-                objects,
-                stats,
-            );
-            let repeated_kw = if repeated { "repeated " } else { "" };
+    /// Caption for the synthetic zero value added to enums that OCSF gives
+    /// no 0 value, keyed by enum name (e.g., `AUTHENTICATION_ACTIVITY_ID`) or
+    /// `*` for every enum. The zero is named from the caption and commented
+    /// with it; without an entry, or if the name is taken, it stays
+    /// `<ENUM>_UNSPECIFIED`.
+    pub synthetic_zero_captions: BTreeMap<String, String>,
 
-            writeln!(
-                out,
-                "\t{repeated_kw}{proto_type} {attr_name} = {field_num}; // Caption: {};",
-                attr.caption
-            )
-            .unwrap();
-            field_num += 1;
-        }
+    /// Emit protoc-gen-validate rules: `message.required` on required
+    /// singular object fields and a 0–65535 range on `port_t` fields. Files
+    /// that use a rule import `validate/validate.proto`.
+    pub emit_pgv_rules: bool,
 
-        writeln!(out, "}}").unwrap();
-    }
+    /// Emit an explicit `[packed = ...]` on repeated numeric scalar and enum
+    /// fields. `None` leaves packing to the proto3 default (packed).
+    pub packed_repeated: Option<bool>,
 
-    out
-}
+    /// Write `ocsf/ocsf_options.proto`, which defines the `(ocsf.profiles)`
+    /// message option, and set it on every event class message with active
+    /// profiles.
+    pub emit_profile_options: bool,
 
-// ── Class enum generation ──────────────────────────────────────────────
+    /// Write `ocsf/ocsf_options.proto` and set the `(ocsf.class_uid)` and
+    /// `(ocsf.category_uid)` message options on every event class message,
+    /// so the OCSF identifiers can be read from the descriptor at runtime
+    /// without adding fields to every event.
+    pub emit_uid_options: bool,
 
-fn generate_class_enums_proto(
-    version_slug: &str,
-    category: &str,
-    classes: &[&OcsfClass],
-    stats: &mut GenerationStats,
-) -> String {
-    let mut out = String::new();
+    /// Write `category.proto` with an `OcsfCategory` enum of the categories
+    /// of every generated class, valued by `category_uid`.
+    pub emit_category_enum: bool,
 
-    writeln!(out, "syntax = \"proto3\";").unwrap();
-    writeln!(out).unwrap();
-    writeln!(out, "package ocsf.{version_slug}.events.{category}.enums;").unwrap();
+    /// Write `ocsf_event.proto` with an `OcsfEvent` envelope message holding
+    /// a `oneof event` over every generated class, numbered by class UID.
+    pub emit_event_envelope: bool,
 
-    for cls in classes {
-        let class_upper = to_screaming_snake(&cls.name);
+    /// How enums are split into files.
+    pub enum_layout: EnumLayout,
 
-        for (attr_name, attr) in &cls.attributes {
-            if attr.deprecated.is_some() {
-                continue;
-            }
-            let Some(enum_vals) = &attr.enum_values else {
-                continue;
-            };
-            if !is_integer_enum(enum_vals) {
-                continue;
-            }
+    /// Precede each field whose type fell back to `string` (an unknown OCSF
+    /// type or a missing object) with a
+    /// `// TODO(ocsf-proto-gen): ...` comment.
+    pub emit_todo_markers: bool,
 
-            let attr_upper = to_screaming_snake(attr_name);
-            let enum_name = format!("{class_upper}_{attr_upper}");
+    /// Proto type of `timestamp_t` fields.
+    pub timestamp_mapping: TimestampMapping,
 
-            write_enum_definition(&mut out, &enum_name, enum_vals);
-            stats.enums_generated += 1;
-        }
-    }
+    /// Proto type of `json_t` fields and of references to objects with no
+    /// fields (e.g., `unmapped`).
+    pub json_mapping: JsonMapping,
 
-    out
-}
+    /// What happens to deprecated attributes.
+    pub deprecated_handling: DeprecatedHandling,
 
-// ── Object proto generation ────────────────────────────────────────────
+    /// Write each class's, object's, and attribute's OCSF description as a
+    /// `//` comment block above its message or field, wrapped at 100
+    /// columns. Empty descriptions get no comment.
+    pub emit_descriptions: bool,
 
-fn generate_objects_proto(
-    version_slug: &str,
-    schema: &OcsfSchema,
-    needed_objects: &BTreeSet<String>,
-    stats: &mut GenerationStats,
-) -> String {
-    let mut out = String::new();
+    /// Write each enum value's OCSF description, if it has one, as a `//`
+    /// comment above its variant.
+    pub emit_enum_descriptions: bool,
 
-    writeln!(out, "syntax = \"proto3\";").unwrap();
-    writeln!(out).unwrap();
-    writeln!(out, "package ocsf.{version_slug}.objects;").unwrap();
-    writeln!(out).unwrap();
-    writeln!(
-        out,
-        "import \"ocsf/{version_slug}/objects/enums/enums.proto\";"
-    )
-    .unwrap();
+    /// Write `// Observable type: <n>` above the message of each object
+    /// with an OCSF observable type number ([`OcsfObject::observable`]).
+    pub emit_observable_comments: bool,
 
-    for obj_name in needed_objects {
-        let obj = lookup_object(schema, obj_name);
-        let Some(obj) = obj else {
-            eprintln!("warning: object '{obj_name}' referenced but not found in schema");
-            continue;
-        };
-        let obj_upper = to_screaming_snake(obj_name);
+    /// After each integer enum's variants, emit `reserved <lo> to <hi>;`
+    /// (or `reserved <n>;`) for every unused value between the lowest and
+    /// highest defined ones, so numbers OCSF may assign later cannot be
+    /// taken by hand-added variants.
+    pub reserve_enum_gaps: bool,
 
-        writeln!(out).unwrap();
-        writeln!(out, "message {} {{", to_pascal_case(obj_name)).unwrap();
+    /// Leave out attributes whose OCSF `requirement` is below this level.
+    /// Under [`DeprecatedHandling::Reserve`] their numbers and names are
+    /// reserved; otherwise later fields are renumbered. `None` keeps every
+    /// attribute.
+    pub min_requirement: Option<Requirement>,
 
-        let mut field_num = 1u32;
-        for (attr_name, attr) in &obj.attributes {
-            if attr.deprecated.is_some() {
-                stats.deprecated_fields_skipped += 1;
-                continue;
-            }
+    /// Emit only base attributes (no `profile`) and those from these
+    /// profiles. Objects referenced only through left-out attributes are not
+    /// generated. Numbering follows the same reserve/skip policy as
+    /// [`GenerationOptions::min_requirement`]. `None` keeps every attribute.
+    pub include_profiles: Option<BTreeSet<String>>,
 
-            let (repeated, proto_type) = resolve_object_field_type(
-                attr,
-                attr_name,
-                &obj_upper,
-                version_slug,
-                &schema.objects,
-                stats,
-            );
-            let repeated_kw = if repeated { "repeated " } else { "" };
+    /// Attributes to leave out, by owner: a class name, or a sanitized
+    /// object name (extension prefix stripped), mapped to attribute names.
+    /// Objects referenced only through excluded attributes are not
+    /// generated. Numbering follows the same reserve/skip policy as
+    /// [`GenerationOptions::min_requirement`].
+    pub exclude_attributes: BTreeMap<String, BTreeSet<String>>,
 
-            writeln!(
-                out,
-                "\t{repeated_kw}{proto_type} {attr_name} = {field_num}; // Caption: {};",
-                attr.caption
-            )
-            .unwrap();
-            field_num += 1;
-        }
+    /// Link sibling attributes (an enum `*_id` and its caption `*_name`,
+    /// joined by OCSF `sibling`) with a `Sibling: <field>;` note in each
+    /// field's trailing comment. The pair stays two independent fields
+    /// rather than a `oneof`, since OCSF events carry both values.
+    pub group_siblings: bool,
 
-        writeln!(out, "}}").unwrap();
-    }
+    /// How generated messages and enums are split into `.proto` files.
+    pub layout: OutputLayout,
 
-    out
-}
+    /// Leading proto package segments, in place of `ocsf` in every package,
+    /// import path, and qualified type reference: `"com.acme.ocsf"` gives
+    /// `package com.acme.ocsf.v1_7_0.objects;` under
+    /// `com/acme/ocsf/v1_7_0/objects/`. Each dot-separated segment must be a
+    /// proto identifier, or generation fails with [`Error::Codegen`].
+    pub package_prefix: String,
 
-// ── Object enum generation ─────────────────────────────────────────────
+    /// How the OCSF version becomes the `v1_7_0` segment of every package,
+    /// import path, and output directory.
+    pub slug_style: SlugStyle,
 
-fn generate_object_enums_proto(
-    version_slug: &str,
-    schema: &OcsfSchema,
-    needed_objects: &BTreeSet<String>,
-    stats: &mut GenerationStats,
-) -> String {
-    let mut out = String::new();
+    /// Proto types to use in place of the built-in mapping, keyed by OCSF
+    /// type name (e.g., `"uuid_t"` → `"acme.Uuid"`). Overrides take
+    /// precedence over [`GenerationOptions::timestamp_mapping`] and
+    /// [`GenerationOptions::json_mapping`], and cover unknown types too.
+    /// They never apply to `object_t`, whose fields always reference the
+    /// generated object message, nor to integer enum fields, which keep
+    /// their generated enum.
+    pub type_overrides: BTreeMap<String, String>,
 
-    writeln!(out, "syntax = \"proto3\";").unwrap();
-    writeln!(out).unwrap();
-    writeln!(out, "package ocsf.{version_slug}.objects.enums;").unwrap();
+    /// Imports (e.g., `"acme/types.proto"`) added to every file with a
+    /// field typed by [`GenerationOptions::type_overrides`] or
+    /// [`GenerationOptions::json_field_overrides`].
+    pub extra_imports: Vec<String>,
 
-    for obj_name in needed_objects {
-        let obj = lookup_object(schema, obj_name);
-        let Some(obj) = obj else {
-            continue;
-        };
-        let obj_upper = to_screaming_snake(obj_name);
+    /// Proto types for individual `json_t` attributes, keyed by
+    /// `"Message.attribute"` with the generated message name and the OCSF
+    /// attribute name (e.g., `"Metadata.labels"` → `"map<string, string>"`,
+    /// or a custom message listed in
+    /// [`GenerationOptions::extra_imports`]). They take precedence over
+    /// [`GenerationOptions::type_overrides`] and
+    /// [`GenerationOptions::json_mapping`]. A `map<...>` type cannot be
+    /// `repeated`, so giving one to an array attribute is an error.
+    pub json_field_overrides: BTreeMap<String, String>,
 
-        for (attr_name, attr) in &obj.attributes {
-            if attr.deprecated.is_some() {
-                continue;
-            }
-            let Some(enum_vals) = &attr.enum_values else {
-                continue;
-            };
-            if !is_integer_enum(enum_vals) {
-                continue;
-            }
+    /// Emit attributes named in [`GenerationOptions::map_attribute_names`]
+    /// as proto maps when they are `json_t`, or `object_t` references
+    /// emitted as `string` (the base object or a field-less one):
+    /// `map<string, string>`, or `map<string, google.protobuf.Value>` under
+    /// [`JsonMapping::WellKnownStruct`]. Explicit
+    /// [`GenerationOptions::json_field_overrides`] and
+    /// [`GenerationOptions::type_overrides`] entries win. Proto maps
+    /// cannot be `repeated`, so an array attribute with such a name is an
+    /// error.
+    pub infer_maps: bool,
 
-            let attr_upper = to_screaming_snake(attr_name);
-            let enum_name = format!("{obj_upper}_{attr_upper}");
+    /// OCSF attribute names treated as dictionaries under
+    /// [`GenerationOptions::infer_maps`]. Defaults to `data` and `labels`.
+    pub map_attribute_names: BTreeSet<String>,
 
-            write_enum_definition(&mut out, &enum_name, enum_vals);
-            stats.enums_generated += 1;
-        }
-    }
+    /// Whether integer-keyed enum attributes get generated enum types.
+    pub enum_mode: EnumMode,
+}
 
-    out
+/// How integer-keyed OCSF enum attributes are emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnumMode {
+    /// As fields of a generated proto `enum` type.
+    #[default]
+    Generate,
+
+    /// As plain `int32` fields whose trailing comment lists the values
+    /// (`Values: 0=Unknown, 1=Logon;`). No enum types or enum files are
+    /// generated and nothing imports them; `enum-value-map.json` still
+    /// describes every enum.
+    Int32WithComment,
 }
 
-// ── Enum value map (JSON reference) ────────────────────────────────────
+/// How an OCSF version is turned into the version segment of packages and
+/// paths.
+#[derive(Clone, Default)]
+pub enum SlugStyle {
+    /// [`version_to_slug`]: `"1.8.0-dev"` → `v1_8_0_dev`.
+    #[default]
+    Default,
 
-fn generate_enum_value_map(
-    schema: &OcsfSchema,
-    class_names: &[String],
-    needed_objects: &BTreeSet<String>,
-) -> Result<String> {
-    let mut map: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    /// [`version_to_slug`] without the `v`: `"1.8.0-dev"` → `1_8_0_dev`.
+    /// A package segment starting with a digit is not a proto identifier,
+    /// so `protoc` rejects these packages; use it where only the directory
+    /// layout matters.
+    NoPrefix,
 
-    for name in class_names {
-        if let Some(cls) = schema.classes.get(name.as_str()) {
-            let class_upper = to_screaming_snake(&cls.name);
-            collect_enum_entries(&class_upper, &cls.attributes, &mut map);
+    /// A caller-supplied mapping from the OCSF version to the slug, e.g.
+    /// `|v| format!("v{v}")` for `v1.8.0-dev` directories. The slug must be
+    /// non-empty and contain no `/`, or generation fails with
+    /// [`Error::Codegen`].
+    Custom(Arc<dyn Fn(&str) -> String + Send + Sync>),
+}
+
+impl SlugStyle {
+    /// The slug for OCSF `version`.
+    pub fn slug(&self, version: &str) -> String {
+        match self {
+            Self::Default => version_to_slug(version),
+            Self::NoPrefix => version.replace(['.', '-'], "_"),
+            Self::Custom(slug) => slug(version),
         }
     }
+}
 
-    for obj_name in needed_objects {
-        if let Some(obj) = lookup_object(schema, obj_name) {
-            let obj_upper = to_screaming_snake(obj_name);
-            collect_enum_entries(&obj_upper, &obj.attributes, &mut map);
+impl std::fmt::Debug for SlugStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => f.write_str("Default"),
+            Self::NoPrefix => f.write_str("NoPrefix"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
         }
     }
-
-    serde_json::to_string_pretty(&map)
-        .map_err(|e| Error::Codegen(format!("serializing enum map: {e}")))
 }
 
-fn collect_enum_entries(
-    prefix: &str,
-    attributes: &BTreeMap<String, OcsfAttribute>,
-    map: &mut BTreeMap<String, serde_json::Value>,
-) {
-    for (attr_name, attr) in attributes {
-        let Some(enum_vals) = &attr.enum_values else {
-            continue;
-        };
-        if !is_integer_enum(enum_vals) {
-            continue;
-        }
-        let attr_upper = to_screaming_snake(attr_name);
-        let enum_name = format!("{prefix}_{attr_upper}");
+/// How generated messages and enums are split into `.proto` files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputLayout {
+    /// Per-category event files and per-package object files, each with
+    /// their enum files, linked by imports.
+    #[default]
+    Split,
 
-        for (key_str, val) in enum_vals {
-            if let Ok(key) = key_str.parse::<i32>() {
-                let variant_name = to_enum_variant_name(&val.caption);
-                let full_name = format!("{enum_name}_{variant_name}");
-                map.insert(
-                    full_name,
-                    serde_json::json!({"name": val.caption, "value": key}),
-                );
-            }
-        }
-    }
+    /// One `ocsf/<slug>/ocsf.proto` in `package ocsf.<slug>;` with every
+    /// message and enum, types referenced by bare name and no imports.
+    /// [`GenerationOptions::enum_layout`] and
+    /// [`GenerationOptions::extension_packages`] do not apply; the category
+    /// enum and event envelope, when requested, are written into the same
+    /// file. Fails with [`Error::Codegen`] if two messages or two enums would
+    /// share a name.
+    ///
+    /// Without imports the file cannot use well-known types, protoc-gen-validate
+    /// rules, or the `ocsf_options.proto` options, and its override types
+    /// must be proto scalars or maps of them. Generation fails with
+    /// [`Error::Codegen`] if [`GenerationOptions::timestamp_mapping`] or
+    /// [`GenerationOptions::json_mapping`] selects a well-known type, if
+    /// [`GenerationOptions::emit_pgv_rules`],
+    /// [`GenerationOptions::emit_profile_options`],
+    /// [`GenerationOptions::emit_uid_options`], or
+    /// [`GenerationOptions::extra_imports`] is set, or if
+    /// [`GenerationOptions::type_overrides`] or
+    /// [`GenerationOptions::json_field_overrides`] name any other type.
+    SingleFile,
 }
 
-// ── Field type resolution ──────────────────────────────────────────────
+/// OCSF attribute requirement level, ordered
+/// `Optional < Recommended < Required`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Requirement {
+    /// `optional`, and any missing or unrecognized requirement.
+    Optional,
 
-/// Resolve the proto type for an event class attribute.
-///
-/// For integer-keyed enum attributes, returns a qualified reference to the
-/// generated enum type (e.g., `ocsf.v1_7_0.events.iam.enums.AUTHENTICATION_ACTIVITY_ID`).
-fn resolve_event_field_type(
-    attr: &OcsfAttribute,
-    attr_name: &str,
-    class_upper: &str,
-    version_slug: &str,
-    category: &str,
-    objects: &BTreeMap<String, OcsfObject>,
-    stats: &mut GenerationStats,
-) -> (bool, String) {
-    let repeated = attr.is_array;
+    /// `recommended`.
+    Recommended,
 
-    // Object references → qualified message type.
-    if attr.type_name == "object_t" {
-        return resolve_object_ref(attr, version_slug, objects, repeated, stats);
-    }
+    /// `required`.
+    Required,
+}
 
-    // Integer-keyed enum → qualified enum type reference.
-    if let Some(enum_vals) = &attr.enum_values {
-        if is_integer_enum(enum_vals) {
-            let attr_upper = to_screaming_snake(attr_name);
-            let enum_type =
-                format!("ocsf.{version_slug}.events.{category}.enums.{class_upper}_{attr_upper}");
-            return (repeated, enum_type);
+impl Requirement {
+    /// The requirement level of `attr`.
+    pub fn of(attr: &OcsfAttribute) -> Self {
+        match attr.requirement.as_deref() {
+            Some("required") => Self::Required,
+            Some("recommended") => Self::Recommended,
+            _ => Self::Optional,
         }
-        stats.string_enum_fields_skipped += 1;
     }
-
-    // Primitive type.
-    let proto_type = ocsf_to_proto_type(&attr.type_name).unwrap_or_else(|| {
-        stats.unknown_types_defaulted += 1;
-        "string"
-    });
-    (repeated, proto_type.to_string())
 }
 
-/// Resolve the proto type for an object attribute.
-///
-/// Same as event field resolution but enum references go to the objects
-/// enum package instead of a per-event-class package.
-fn resolve_object_field_type(
-    attr: &OcsfAttribute,
-    attr_name: &str,
-    obj_upper: &str,
-    version_slug: &str,
-    objects: &BTreeMap<String, OcsfObject>,
-    stats: &mut GenerationStats,
-) -> (bool, String) {
-    let repeated = attr.is_array;
+/// How deprecated attributes are emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeprecatedHandling {
+    /// Leave them out; later fields take the numbers they would have had.
+    #[default]
+    Skip,
 
-    if attr.type_name == "object_t" {
-        return resolve_object_ref(attr, version_slug, objects, repeated, stats);
-    }
+    /// Emit `reserved N;` and `reserved "name";` in their place, so the
+    /// surviving fields keep their numbers when an attribute is deprecated
+    /// between releases.
+    Reserve,
 
-    if let Some(enum_vals) = &attr.enum_values {
-        if is_integer_enum(enum_vals) {
-            let attr_upper = to_screaming_snake(attr_name);
-            let enum_type = format!("ocsf.{version_slug}.objects.enums.{obj_upper}_{attr_upper}");
-            return (repeated, enum_type);
-        }
-        stats.string_enum_fields_skipped += 1;
-    }
+    /// Emit them with their type and `[deprecated = true]`, with the OCSF
+    /// deprecation note in the trailing comment.
+    Emit,
+}
 
-    let proto_type = ocsf_to_proto_type(&attr.type_name).unwrap_or_else(|| {
-        stats.unknown_types_defaulted += 1;
-        "string"
-    });
-    (repeated, proto_type.to_string())
+/// How `timestamp_t` fields are represented.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampMapping {
+    /// `int64` epoch milliseconds, as OCSF defines the type.
+    #[default]
+    Int64Millis,
+
+    /// `google.protobuf.Timestamp`. Files with at least one such field
+    /// import `google/protobuf/timestamp.proto`.
+    WellKnownTimestamp,
 }
 
-/// Resolve an `object_t` attribute to a qualified proto message reference.
-///
-/// If the referenced object has no non-deprecated attributes (e.g., the OCSF
-/// base `object` type used by the `unmapped` field), emits `string` instead —
-/// an empty proto message cannot hold data, so `string` (for JSON) is correct.
-fn resolve_object_ref(
-    attr: &OcsfAttribute,
-    version_slug: &str,
-    objects: &BTreeMap<String, OcsfObject>,
-    repeated: bool,
-    stats: &mut GenerationStats,
-) -> (bool, String) {
-    let obj_type = attr.object_type.as_deref().unwrap_or("unknown");
-    let sanitized = sanitize_object_name(obj_type);
+/// How generated enums are split into files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnumLayout {
+    /// One `enums/enums.proto` per category and per objects directory.
+    #[default]
+    Shared,
 
-    let obj = objects
-        .get(obj_type)
-        .or_else(|| objects.get(&sanitized))
-        .or_else(|| {
-            objects
-                .values()
-                .find(|o| sanitize_object_name(&o.name) == sanitized)
-        });
+    /// One file per enum, `enums/<enum_name>.proto` (lowercased), in the same
+    /// package. Each message file imports only the enum files it uses.
+    PerEnum,
+}
 
-    let Some(obj) = obj else {
-        eprintln!("warning: object type '{obj_type}' not found, defaulting to string");
-        stats.unknown_types_defaulted += 1;
-        return (repeated, "string".to_string());
-    };
+/// How `json_t` fields and references to field-less objects are represented.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonMapping {
+    /// `string` holding serialized JSON, which keeps prost-generated types
+    /// serde-compatible.
+    #[default]
+    StringJson,
 
-    // Empty objects (no non-deprecated attributes) produce empty proto messages
-    // that cannot hold data. Emit `string` instead so the field can carry JSON.
-    // This handles the OCSF `unmapped` field (type: object_t, object_type: object).
-    let has_fields = obj.attributes.values().any(|a| a.deprecated.is_none());
-    if !has_fields {
-        return (repeated, "string".to_string());
-    }
+    /// `google.protobuf.Struct`. Files with at least one such field import
+    /// `google/protobuf/struct.proto`.
+    WellKnownStruct,
+}
 
-    let pascal = to_pascal_case(&sanitized);
-    let qualified = format!("ocsf.{version_slug}.objects.{pascal}");
-    (repeated, qualified)
+/// Order of the fields in generated messages, which also sets the order
+/// field numbers are assigned in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldOrder {
+    /// By attribute name.
+    #[default]
+    Alphabetical,
+
+    /// As the attributes appear in the schema export
+    /// ([`OcsfAttribute::position`]), by name among equal positions.
+    SchemaOrder,
 }
 
-// ── Shared helpers ─────────────────────────────────────────────────────
+/// Source of generated proto field names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldNameSource {
+    /// The OCSF attribute key (e.g., `activity_id`), sanitized if needed.
+    #[default]
+    AttributeKey,
+
+    /// The snake_cased attribute caption (`"Activity ID"` → `activity_id`).
+    /// Every field carries `[json_name = "<attribute key>"]` so the JSON
+    /// mapping still matches OCSF. Attributes with an empty caption fall
+    /// back to their key.
+    Caption,
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        Self {
+            naming: Arc::new(DefaultNaming),
+            enum_map_globs: Vec::new(),
+            enum_map_include_objects: true,
+            emit_enum_map_by_number: false,
+            emit_presence_caveats: false,
+            emit_buf_config: false,
+            dry_run: false,
+            mark_file_deprecated: false,
+            reject_empty_messages: false,
+            strict: false,
+            verify_enum_map: false,
+            field_name_source: FieldNameSource::AttributeKey,
+            field_order: FieldOrder::Alphabetical,
+            extension_packages: false,
+            pinned_field_numbers: FieldNumbers::new(),
+            field_numbers_lock: None,
+            synthetic_zero_captions: BTreeMap::new(),
+            emit_pgv_rules: false,
+            packed_repeated: None,
+            emit_profile_options: false,
+            emit_uid_options: false,
+            emit_category_enum: false,
+            emit_event_envelope: false,
+            enum_layout: EnumLayout::Shared,
+            emit_todo_markers: false,
+            timestamp_mapping: TimestampMapping::Int64Millis,
+            json_mapping: JsonMapping::StringJson,
+            deprecated_handling: DeprecatedHandling::Skip,
+            emit_descriptions: false,
+            emit_enum_descriptions: false,
+            emit_observable_comments: false,
+            reserve_enum_gaps: false,
+            min_requirement: None,
+            include_profiles: None,
+            exclude_attributes: BTreeMap::new(),
+            group_siblings: false,
+            layout: OutputLayout::Split,
+            package_prefix: "ocsf".to_string(),
+            slug_style: SlugStyle::Default,
+            type_overrides: BTreeMap::new(),
+            extra_imports: Vec::new(),
+            json_field_overrides: BTreeMap::new(),
+            infer_maps: false,
+            map_attribute_names: ["data", "labels"].map(String::from).into(),
+            enum_mode: EnumMode::Generate,
+        }
+    }
+}
+
+impl GenerationOptions {
+    /// Start building options from the defaults.
+    ///
+    /// ```
+    /// use ocsf_proto_gen::codegen::{EnumLayout, GenerationOptions};
+    ///
+    /// let opts = GenerationOptions::builder()
+    ///     .emit_category_enum(true)
+    ///     .enum_layout(EnumLayout::PerEnum)
+    ///     .build();
+    /// assert!(opts.emit_category_enum);
+    /// ```
+    pub fn builder() -> GenerationOptionsBuilder {
+        GenerationOptionsBuilder::default()
+    }
+}
+
+/// Builder for [`GenerationOptions`], starting from its defaults.
+///
+/// Each setter replaces the field of the same name.
+#[derive(Debug, Clone, Default)]
+pub struct GenerationOptionsBuilder {
+    opts: GenerationOptions,
+}
+
+impl GenerationOptionsBuilder {
+    /// Set [`GenerationOptions::naming`].
+    pub fn naming(mut self, naming: Arc<dyn NamingStrategy>) -> Self {
+        self.opts.naming = naming;
+        self
+    }
+
+    /// Set [`GenerationOptions::enum_map_globs`].
+    pub fn enum_map_globs(mut self, enum_map_globs: Vec<String>) -> Self {
+        self.opts.enum_map_globs = enum_map_globs;
+        self
+    }
+
+    /// Set [`GenerationOptions::enum_map_include_objects`].
+    pub fn enum_map_include_objects(mut self, enum_map_include_objects: bool) -> Self {
+        self.opts.enum_map_include_objects = enum_map_include_objects;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_enum_map_by_number`].
+    pub fn emit_enum_map_by_number(mut self, emit_enum_map_by_number: bool) -> Self {
+        self.opts.emit_enum_map_by_number = emit_enum_map_by_number;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_buf_config`].
+    pub fn emit_buf_config(mut self, emit_buf_config: bool) -> Self {
+        self.opts.emit_buf_config = emit_buf_config;
+        self
+    }
+
+    /// Set [`GenerationOptions::dry_run`].
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.opts.dry_run = dry_run;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_presence_caveats`].
+    pub fn emit_presence_caveats(mut self, emit_presence_caveats: bool) -> Self {
+        self.opts.emit_presence_caveats = emit_presence_caveats;
+        self
+    }
+
+    /// Set [`GenerationOptions::mark_file_deprecated`].
+    pub fn mark_file_deprecated(mut self, mark_file_deprecated: bool) -> Self {
+        self.opts.mark_file_deprecated = mark_file_deprecated;
+        self
+    }
+
+    /// Set [`GenerationOptions::reject_empty_messages`].
+    pub fn reject_empty_messages(mut self, reject_empty_messages: bool) -> Self {
+        self.opts.reject_empty_messages = reject_empty_messages;
+        self
+    }
+
+    /// Set [`GenerationOptions::strict`].
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.opts.strict = strict;
+        self
+    }
+
+    /// Set [`GenerationOptions::verify_enum_map`].
+    pub fn verify_enum_map(mut self, verify_enum_map: bool) -> Self {
+        self.opts.verify_enum_map = verify_enum_map;
+        self
+    }
+
+    /// Set [`GenerationOptions::field_order`].
+    pub fn field_order(mut self, field_order: FieldOrder) -> Self {
+        self.opts.field_order = field_order;
+        self
+    }
+
+    /// Set [`GenerationOptions::field_name_source`].
+    pub fn field_name_source(mut self, field_name_source: FieldNameSource) -> Self {
+        self.opts.field_name_source = field_name_source;
+        self
+    }
+
+    /// Set [`GenerationOptions::extension_packages`].
+    pub fn extension_packages(mut self, extension_packages: bool) -> Self {
+        self.opts.extension_packages = extension_packages;
+        self
+    }
+
+    /// Set [`GenerationOptions::pinned_field_numbers`].
+    pub fn pinned_field_numbers(mut self, pinned_field_numbers: FieldNumbers) -> Self {
+        self.opts.pinned_field_numbers = pinned_field_numbers;
+        self
+    }
+
+    /// Set [`GenerationOptions::field_numbers_lock`].
+    pub fn field_numbers_lock(mut self, field_numbers_lock: Option<FieldNumbers>) -> Self {
+        self.opts.field_numbers_lock = field_numbers_lock;
+        self
+    }
+
+    /// Set [`GenerationOptions::synthetic_zero_captions`].
+    pub fn synthetic_zero_captions(
+        mut self,
+        synthetic_zero_captions: BTreeMap<String, String>,
+    ) -> Self {
+        self.opts.synthetic_zero_captions = synthetic_zero_captions;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_pgv_rules`].
+    pub fn emit_pgv_rules(mut self, emit_pgv_rules: bool) -> Self {
+        self.opts.emit_pgv_rules = emit_pgv_rules;
+        self
+    }
+
+    /// Set [`GenerationOptions::packed_repeated`].
+    pub fn packed_repeated(mut self, packed_repeated: Option<bool>) -> Self {
+        self.opts.packed_repeated = packed_repeated;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_profile_options`].
+    pub fn emit_profile_options(mut self, emit_profile_options: bool) -> Self {
+        self.opts.emit_profile_options = emit_profile_options;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_uid_options`].
+    pub fn emit_uid_options(mut self, emit_uid_options: bool) -> Self {
+        self.opts.emit_uid_options = emit_uid_options;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_category_enum`].
+    pub fn emit_category_enum(mut self, emit_category_enum: bool) -> Self {
+        self.opts.emit_category_enum = emit_category_enum;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_event_envelope`].
+    pub fn emit_event_envelope(mut self, emit_event_envelope: bool) -> Self {
+        self.opts.emit_event_envelope = emit_event_envelope;
+        self
+    }
+
+    /// Set [`GenerationOptions::enum_layout`].
+    pub fn enum_layout(mut self, enum_layout: EnumLayout) -> Self {
+        self.opts.enum_layout = enum_layout;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_todo_markers`].
+    pub fn emit_todo_markers(mut self, emit_todo_markers: bool) -> Self {
+        self.opts.emit_todo_markers = emit_todo_markers;
+        self
+    }
+
+    /// Set [`GenerationOptions::timestamp_mapping`].
+    pub fn timestamp_mapping(mut self, timestamp_mapping: TimestampMapping) -> Self {
+        self.opts.timestamp_mapping = timestamp_mapping;
+        self
+    }
+
+    /// Set [`GenerationOptions::json_mapping`].
+    pub fn json_mapping(mut self, json_mapping: JsonMapping) -> Self {
+        self.opts.json_mapping = json_mapping;
+        self
+    }
+
+    /// Set [`GenerationOptions::deprecated_handling`].
+    pub fn deprecated_handling(mut self, deprecated_handling: DeprecatedHandling) -> Self {
+        self.opts.deprecated_handling = deprecated_handling;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_descriptions`].
+    pub fn emit_descriptions(mut self, emit_descriptions: bool) -> Self {
+        self.opts.emit_descriptions = emit_descriptions;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_enum_descriptions`].
+    pub fn emit_enum_descriptions(mut self, emit_enum_descriptions: bool) -> Self {
+        self.opts.emit_enum_descriptions = emit_enum_descriptions;
+        self
+    }
+
+    /// Set [`GenerationOptions::emit_observable_comments`].
+    pub fn emit_observable_comments(mut self, emit_observable_comments: bool) -> Self {
+        self.opts.emit_observable_comments = emit_observable_comments;
+        self
+    }
+
+    /// Set [`GenerationOptions::reserve_enum_gaps`].
+    pub fn reserve_enum_gaps(mut self, reserve_enum_gaps: bool) -> Self {
+        self.opts.reserve_enum_gaps = reserve_enum_gaps;
+        self
+    }
+
+    /// Set [`GenerationOptions::min_requirement`].
+    pub fn min_requirement(mut self, min_requirement: Option<Requirement>) -> Self {
+        self.opts.min_requirement = min_requirement;
+        self
+    }
+
+    /// Set [`GenerationOptions::include_profiles`].
+    pub fn include_profiles(mut self, include_profiles: Option<BTreeSet<String>>) -> Self {
+        self.opts.include_profiles = include_profiles;
+        self
+    }
+
+    /// Set [`GenerationOptions::exclude_attributes`].
+    pub fn exclude_attributes(
+        mut self,
+        exclude_attributes: BTreeMap<String, BTreeSet<String>>,
+    ) -> Self {
+        self.opts.exclude_attributes = exclude_attributes;
+        self
+    }
+
+    /// Set [`GenerationOptions::group_siblings`].
+    pub fn group_siblings(mut self, group_siblings: bool) -> Self {
+        self.opts.group_siblings = group_siblings;
+        self
+    }
+
+    /// Set [`GenerationOptions::layout`].
+    pub fn layout(mut self, layout: OutputLayout) -> Self {
+        self.opts.layout = layout;
+        self
+    }
+
+    /// Set [`GenerationOptions::package_prefix`].
+    pub fn package_prefix(mut self, package_prefix: impl Into<String>) -> Self {
+        self.opts.package_prefix = package_prefix.into();
+        self
+    }
+
+    /// Set [`GenerationOptions::slug_style`].
+    pub fn slug_style(mut self, slug_style: SlugStyle) -> Self {
+        self.opts.slug_style = slug_style;
+        self
+    }
+
+    /// Set [`GenerationOptions::type_overrides`].
+    pub fn type_overrides(mut self, type_overrides: BTreeMap<String, String>) -> Self {
+        self.opts.type_overrides = type_overrides;
+        self
+    }
+
+    /// Set [`GenerationOptions::extra_imports`].
+    pub fn extra_imports(mut self, extra_imports: Vec<String>) -> Self {
+        self.opts.extra_imports = extra_imports;
+        self
+    }
+
+    /// Set [`GenerationOptions::json_field_overrides`].
+    pub fn json_field_overrides(mut self, json_field_overrides: BTreeMap<String, String>) -> Self {
+        self.opts.json_field_overrides = json_field_overrides;
+        self
+    }
+
+    /// Set [`GenerationOptions::infer_maps`].
+    pub fn infer_maps(mut self, infer_maps: bool) -> Self {
+        self.opts.infer_maps = infer_maps;
+        self
+    }
+
+    /// Set [`GenerationOptions::map_attribute_names`].
+    pub fn map_attribute_names(mut self, map_attribute_names: BTreeSet<String>) -> Self {
+        self.opts.map_attribute_names = map_attribute_names;
+        self
+    }
+
+    /// Set [`GenerationOptions::enum_mode`].
+    pub fn enum_mode(mut self, enum_mode: EnumMode) -> Self {
+        self.opts.enum_mode = enum_mode;
+        self
+    }
+
+    /// Finish building.
+    pub fn build(self) -> GenerationOptions {
+        self.opts
+    }
+}
+
+/// Generate proto files for the requested event classes.
+///
+/// Resolves the transitive object dependency graph, generates proto files
+/// for events, objects, and enums, and writes them to `output_dir`.
+///
+/// Returns generation statistics for reporting.
+pub fn generate(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    output_dir: &Path,
+) -> Result<GenerationStats> {
+    generate_with_options(
+        schema,
+        class_names,
+        output_dir,
+        &GenerationOptions::default(),
+    )
+}
+
+/// Generate proto files for the requested event classes using `opts`.
+///
+/// Identical to [`generate`] except that output is shaped by the supplied
+/// [`GenerationOptions`].
+pub fn generate_with_options(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    output_dir: &Path,
+    opts: &GenerationOptions,
+) -> Result<GenerationStats> {
+    generate_with_manifest(schema, class_names, output_dir, opts).map(|manifest| manifest.stats)
+}
+
+/// Generate like [`generate_with_options`], returning the contents of the
+/// `manifest.json` it writes instead of only the stats.
+pub fn generate_with_manifest(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    output_dir: &Path,
+    opts: &GenerationOptions,
+) -> Result<GenerationManifest> {
+    let (files, manifest) = render_files(schema, class_names, opts)?;
+    if opts.dry_run {
+        for (path, content) in &files {
+            log::info!(
+                "Would write {} ({} bytes)",
+                output_dir.join(path).display(),
+                content.len()
+            );
+        }
+        return Ok(manifest);
+    }
+    let files: Vec<(&String, &String)> = files.iter().collect();
+    map_ordered(&files, |(path, content)| {
+        write_file(&output_dir.join(path), content)
+    })
+    .into_iter()
+    .collect::<Result<()>>()?;
+    Ok(manifest)
+}
+
+/// Render the files [`generate_with_options`] would write, keyed by path
+/// relative to the output directory, without touching the filesystem.
+///
+/// The keys match [`planned_outputs`] and the contents are byte-identical
+/// to what generation writes, so callers can post-process or route output
+/// anywhere.
+pub fn generate_to_map(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<BTreeMap<PathBuf, String>> {
+    let (files, _) = render_files(schema, class_names, opts)?;
+    Ok(files
+        .into_iter()
+        .map(|(path, content)| (PathBuf::from(path), content))
+        .collect())
+}
+
+/// Generate like [`generate_with_options`], but write the output tree as a
+/// single zip archive at `archive` (see [`zip_archive`]) instead of into a
+/// directory. Under [`GenerationOptions::dry_run`] the archive's path and
+/// size are logged instead.
+#[cfg(feature = "archive")]
+pub fn generate_archive(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    archive: &Path,
+    opts: &GenerationOptions,
+) -> Result<GenerationStats> {
+    let (files, manifest) = render_files(schema, class_names, opts)?;
+    let files: BTreeMap<PathBuf, String> = files
+        .into_iter()
+        .map(|(path, content)| (PathBuf::from(path), content))
+        .collect();
+    let bytes = zip_archive(&files)?;
+    if opts.dry_run {
+        log::info!("Would write {} ({} bytes)", archive.display(), bytes.len());
+    } else {
+        if let Some(parent) = archive.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::Write {
+                path: parent.to_path_buf(),
+                source: e,
+            })?;
+        }
+        std::fs::write(archive, bytes).map_err(|e| Error::Write {
+            path: archive.to_path_buf(),
+            source: e,
+        })?;
+    }
+    Ok(manifest.stats)
+}
+
+/// Pack `files` (e.g., from [`generate_to_map`]) into a deflate-compressed
+/// zip archive with the same relative paths.
+///
+/// Entries are added in path order with the zip epoch (1980-01-01) as their
+/// timestamp and mode `0644`, so the same files always give the same bytes.
+#[cfg(feature = "archive")]
+pub fn zip_archive(files: &BTreeMap<PathBuf, String>) -> Result<Vec<u8>> {
+    use std::io::Write;
+
+    let archive_error = |e: &dyn std::fmt::Display| Error::Codegen(format!("writing archive: {e}"));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(zip::DateTime::default())
+        .unix_permissions(0o644);
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (path, content) in files {
+        // Zip entry names always use `/`.
+        let name: Vec<_> = path
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect();
+        writer
+            .start_file(name.join("/"), options)
+            .map_err(|e| archive_error(&e))?;
+        writer
+            .write_all(content.as_bytes())
+            .map_err(|e| archive_error(&e))?;
+    }
+    let cursor = writer.finish().map_err(|e| archive_error(&e))?;
+    Ok(cursor.into_inner())
+}
+
+/// Run generation in memory without writing any files, returning the
+/// stats [`generate_with_options`] would report.
+pub fn check(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<GenerationStats> {
+    let (_, manifest) = render_files(schema, class_names, opts)?;
+    Ok(manifest.stats)
+}
+
+/// Count the classes, objects, fields and enums [`generate_with_options`]
+/// would emit, with the rest of its stats, without building any proto text
+/// or writing files.
+///
+/// The plan is built, and every message's fields named, decided and
+/// numbered, by the same code generation uses, so the counts equal the ones
+/// [`check`] reports (under [`OutputLayout::SingleFile`] the warnings may
+/// come in another order) and it fails wherever generation would.
+/// [`GenerationOptions::verify_enum_map`] checks the rendered protos, so
+/// with it set they are rendered in memory after all.
+pub fn estimate(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<GenerationStats> {
+    let ctx = Context {
+        inline_types: opts.layout == OutputLayout::SingleFile,
+        ..Context::new(schema, opts)
+    };
+    if opts.verify_enum_map && ctx.generates_enums() {
+        return check(schema, class_names, opts);
+    }
+    let plan = Plan::new(&ctx, class_names)?;
+    let mut stats = GenerationStats::default();
+
+    // In the order generation meets them: each category, then each
+    // extension's objects, messages before enums.
+    for classes in plan.classes_by_category.values() {
+        for cls in classes {
+            let message_name = ctx.opts.naming.message_name(&cls.name);
+            stats.deprecated_messages += usize::from(cls.deprecated.is_some());
+            let fields = message_fields(
+                &ctx,
+                "class",
+                &cls.name,
+                &message_name,
+                &cls.attributes,
+                "",
+                &mut stats,
+            )?;
+            check_not_empty(&ctx, &message_name, &cls.name, fields.numbers.is_empty())?;
+        }
+        for (owner, attributes) in class_owners(classes) {
+            for (_, enum_vals) in owner_enums(&ctx, owner, attributes) {
+                count_enum(enum_vals, &mut stats);
+            }
+        }
+    }
+    for objects in plan.objects_by_extension.values() {
+        for obj_name in objects {
+            let Some(obj) = lookup_object(schema, obj_name) else {
+                stats.warn(format!(
+                    "object '{obj_name}' referenced but not found in schema"
+                ));
+                continue;
+            };
+            stats.deprecated_messages += usize::from(obj.deprecated.is_some());
+            let message_name = ctx.opts.naming.message_name(obj_name);
+            message_fields(
+                &ctx,
+                "object",
+                obj_name,
+                &message_name,
+                &obj.attributes,
+                "",
+                &mut stats,
+            )?;
+        }
+        for (owner, attributes) in object_owners(&ctx, objects) {
+            for (_, enum_vals) in owner_enums(&ctx, owner, attributes) {
+                count_enum(enum_vals, &mut stats);
+            }
+        }
+    }
+
+    stats.classes_generated = class_names.len();
+    stats.objects_generated = plan.needed_objects.len();
+    stats.empty_objects_skipped = plan.empty_objects_skipped;
+    Ok(stats)
+}
+
+/// The objects [`generate_with_options`] would emit a message for, by
+/// sanitized name (extension prefix stripped, e.g. `win_service` for
+/// `win/win_service`), without building any content.
+///
+/// Unlike [`crate::schema::resolve_object_graph`], this applies `opts`: it
+/// follows only attributes that are emitted, and leaves out objects with no
+/// fields. The set equals [`GenerationManifest::objects`] for the same
+/// inputs, and it fails where generation would (e.g., an unknown class).
+pub fn resolved_objects(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<BTreeSet<String>> {
+    let ctx = Context::new(schema, opts);
+    Ok(Plan::new(&ctx, class_names)?.needed_objects)
+}
+
+/// The directory, relative to the output directory, that holds the files
+/// generated for `schema`'s version: `ocsf/v1_7_0` by default, following
+/// [`GenerationOptions::package_prefix`] and [`GenerationOptions::slug_style`].
+///
+/// Everything [`generate_with_options`] writes is below it except the
+/// shared `ocsf_options.proto`, the field number lock, and the Buf files.
+pub fn version_root(schema: &OcsfSchema, opts: &GenerationOptions) -> PathBuf {
+    PathBuf::from(Context::new(schema, opts).import_root(None))
+}
+
+/// List the paths, relative to the output directory, that
+/// [`generate_with_options`] would write, without building any content.
+///
+/// Build systems can use this to declare outputs before running generation.
+pub fn planned_outputs(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<Vec<PathBuf>> {
+    let ctx = Context::new(schema, opts);
+    let plan = Plan::new(&ctx, class_names)?;
+    let root = ctx.import_root(None);
+    let single_file = opts.layout == OutputLayout::SingleFile;
+
+    let mut paths = BTreeSet::new();
+    if single_file {
+        paths.insert(format!("{root}/ocsf.proto"));
+    }
+    for ((ext, category), classes) in plan.classes_by_category.iter().filter(|_| !single_file) {
+        let category_dir = ctx.category_dir(ext.as_deref(), category);
+        paths.insert(format!("{category_dir}/{category}.proto"));
+        let enums =
+            class_owners(classes).flat_map(|(owner, attrs)| owner_enums(&ctx, owner, attrs));
+        paths.extend(ctx.enum_files(
+            &format!("{category_dir}/enums"),
+            enums.map(|(name, _)| name),
+        ));
+    }
+    for (ext, objects) in plan.objects_by_extension.iter().filter(|_| !single_file) {
+        let objects_dir = ctx.objects_dir(ext.as_deref());
+        paths.insert(format!("{objects_dir}/objects.proto"));
+        let enums =
+            object_owners(&ctx, objects).flat_map(|(owner, attrs)| owner_enums(&ctx, owner, attrs));
+        paths.extend(ctx.enum_files(&format!("{objects_dir}/enums"), enums.map(|(name, _)| name)));
+    }
+    let optional = [
+        (ctx.writes_options_proto(), ctx.options_proto()),
+        (
+            opts.emit_category_enum && !single_file,
+            format!("{root}/category.proto"),
+        ),
+        (
+            opts.emit_event_envelope && !single_file,
+            format!("{root}/ocsf_event.proto"),
+        ),
+        (
+            opts.emit_presence_caveats,
+            format!("{root}/presence-caveats.json"),
+        ),
+        (true, format!("{root}/enum-value-map.json")),
+        (
+            opts.emit_enum_map_by_number,
+            format!("{root}/enum-value-map-by-number.json"),
+        ),
+        (true, format!("{root}/field-metadata.json")),
+        (true, format!("{root}/object-name-map.json")),
+        (true, format!("{root}/manifest.json")),
+        (
+            opts.field_numbers_lock.is_some(),
+            FIELD_NUMBERS_LOCK.to_string(),
+        ),
+        (opts.emit_buf_config, BUF_YAML.to_string()),
+        (opts.emit_buf_config, BUF_GEN_YAML.to_string()),
+    ];
+    paths.extend(
+        optional
+            .into_iter()
+            .filter_map(|(on, path)| on.then_some(path)),
+    );
+    Ok(paths.into_iter().map(PathBuf::from).collect())
+}
+
+/// Render every generated file in memory, keyed by its path relative to
+/// the output directory (e.g., `ocsf/v1_7_0/objects/objects.proto`), along
+/// with the manifest describing them.
+fn render_files(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<(BTreeMap<String, String>, GenerationManifest)> {
+    let ctx = Context {
+        inline_types: opts.layout == OutputLayout::SingleFile,
+        ..Context::new(schema, opts)
+    };
+    let plan = Plan::new(&ctx, class_names)?;
+    let needed_objects = &plan.needed_objects;
+    let mut files = BTreeMap::new();
+    let mut stats = GenerationStats::default();
+    let mut lock = opts.field_numbers_lock.clone().unwrap_or_default();
+    let root = ctx.import_root(None);
+
+    if opts.layout == OutputLayout::SingleFile {
+        let proto = generate_single_file(&ctx, &plan, &mut stats, &mut lock)?;
+        files.insert(format!("{root}/ocsf.proto"), proto);
+    } else {
+        render_split_files(&ctx, &plan, &mut files, &mut stats, &mut lock)?;
+    }
+    // Locked numbers of classes and objects not generated now stay locked.
+    if opts.field_numbers_lock.is_some() {
+        files.insert(
+            FIELD_NUMBERS_LOCK.to_string(),
+            field_numbers_lock_json(&lock)?,
+        );
+    }
+    stats.classes_generated = class_names.len();
+    stats.objects_generated = needed_objects.len();
+    stats.empty_objects_skipped = plan.empty_objects_skipped;
+
+    // Generate enum-value-map.json reference.
+    let enum_map = generate_enum_value_map(&ctx, class_names, needed_objects)?;
+    if opts.verify_enum_map && ctx.generates_enums() {
+        verify_enum_map(&ctx, class_names, needed_objects, &files, &enum_map)?;
+    }
+    files.insert(format!("{root}/enum-value-map.json"), enum_map);
+    if opts.emit_enum_map_by_number {
+        let by_number = generate_enum_value_map_by_number(&ctx, class_names, needed_objects)?;
+        files.insert(format!("{root}/enum-value-map-by-number.json"), by_number);
+    }
+
+    // Generate field-metadata.json reference.
+    let field_metadata = generate_field_metadata(&ctx, class_names, needed_objects)?;
+    files.insert(format!("{root}/field-metadata.json"), field_metadata);
+
+    // Generate object-name-map.json reference.
+    let object_names = generate_object_name_map(&ctx, needed_objects)?;
+    files.insert(format!("{root}/object-name-map.json"), object_names);
+
+    // Generate presence-caveats.json reference.
+    if opts.emit_presence_caveats {
+        let caveats = generate_presence_caveats(&ctx, class_names, needed_objects)?;
+        files.insert(format!("{root}/presence-caveats.json"), caveats);
+    }
+
+    if opts.emit_buf_config {
+        files.insert(BUF_YAML.to_string(), generate_buf_yaml());
+        files.insert(BUF_GEN_YAML.to_string(), generate_buf_gen_yaml(&ctx));
+    }
+
+    // Generate manifest.json, listing the protos written above.
+    let classes: BTreeSet<&String> = class_names.iter().collect();
+    let manifest = GenerationManifest {
+        ocsf_version: schema.version.clone(),
+        classes: classes.into_iter().cloned().collect(),
+        objects: needed_objects.iter().cloned().collect(),
+        protos: files
+            .keys()
+            .filter(|path| path.ends_with(".proto"))
+            .cloned()
+            .collect(),
+        stats,
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| Error::Codegen(format!("serializing manifest: {e}")))?;
+    files.insert(format!("{root}/manifest.json"), manifest_json);
+
+    Ok((files, manifest))
+}
+
+/// Render the [`OutputLayout::SingleFile`] proto: enums, then object
+/// messages, then event messages by category, then the category enum and
+/// event envelope if requested.
+fn generate_single_file(
+    ctx: &Context,
+    plan: &Plan,
+    stats: &mut GenerationStats,
+    lock: &mut FieldNumbers,
+) -> Result<String> {
+    let classes: Vec<&OcsfClass> = plan
+        .classes_by_category
+        .values()
+        .flatten()
+        .copied()
+        .collect();
+
+    let mut out = String::new();
+    write_file_header(&mut out, ctx, &ctx.package_root(None));
+    write_class_enums(&mut out, ctx, &classes, stats);
+    write_object_enums(&mut out, ctx, &plan.needed_objects, stats);
+    write_object_messages(&mut out, ctx, &plan.needed_objects, "", stats, lock)?;
+    for ((_, category), classes) in &plan.classes_by_category {
+        write_event_messages(&mut out, ctx, category, classes, "", stats, lock)?;
+    }
+    if ctx.opts.emit_category_enum {
+        write_category_enum(&mut out, ctx, &plan.classes_by_category);
+    }
+    if ctx.opts.emit_event_envelope {
+        write_envelope_message(&mut out, ctx, &plan.classes_by_category)?;
+    }
+    Ok(out)
+}
+
+/// Fail with [`Error::Codegen`] if `opts` needs an import that `output`
+/// (`"single-file"` or `"standalone"`), which has none, cannot have: a
+/// well-known type, a protoc-gen-validate rule, a custom option, or an
+/// override type other than a proto scalar or a map of them.
+fn check_import_free_options(opts: &GenerationOptions, output: &str) -> Result<()> {
+    let needs_import = [
+        (
+            opts.timestamp_mapping == TimestampMapping::WellKnownTimestamp,
+            "the well-known timestamp mapping",
+        ),
+        (
+            opts.json_mapping == JsonMapping::WellKnownStruct,
+            "the well-known struct mapping",
+        ),
+        (opts.emit_pgv_rules, "protoc-gen-validate rules"),
+        (opts.emit_profile_options, "the profiles option"),
+        (opts.emit_uid_options, "the class and category UID options"),
+        (!opts.extra_imports.is_empty(), "extra imports"),
+    ];
+    if let Some((_, what)) = needs_import.iter().find(|(set, _)| *set) {
+        return Err(Error::Codegen(format!(
+            "{output} output has no imports, so it cannot use {what}"
+        )));
+    }
+    let overrides = opts.type_overrides.iter().chain(&opts.json_field_overrides);
+    for (key, proto_type) in overrides {
+        if !is_import_free_type(proto_type) {
+            return Err(Error::Codegen(format!(
+                "{output} output has no imports, so the override of '{key}' must be a \
+                 proto scalar or a map of them, not '{proto_type}'"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `proto_type` is a proto scalar, or a `map<...>` of scalars, that
+/// needs no import.
+fn is_import_free_type(proto_type: &str) -> bool {
+    let scalar = |name: &str| PROTO_SCALARS.contains(&name.trim());
+    match proto_type
+        .strip_prefix("map<")
+        .and_then(|rest| rest.strip_suffix('>'))
+    {
+        Some(entry) => entry
+            .split_once(',')
+            .is_some_and(|(key, value)| scalar(key) && scalar(value)),
+        None => scalar(proto_type),
+    }
+}
+
+/// Fail with [`Error::Codegen`] if two messages, or two enums, of the
+/// [`OutputLayout::SingleFile`] proto would share a name: in the split
+/// layout they live in different packages.
+fn check_single_file_names(ctx: &Context, plan: &Plan) -> Result<()> {
+    let classes: Vec<&OcsfClass> = plan
+        .classes_by_category
+        .values()
+        .flatten()
+        .copied()
+        .collect();
+    let owners = class_owners(&classes)
+        .map(|(name, attributes)| ("class", name, attributes))
+        .chain(
+            object_owners(ctx, &plan.needed_objects)
+                .map(|(name, attributes)| ("object", name, attributes)),
+        );
+
+    let mut messages: BTreeMap<String, (&str, &str)> = BTreeMap::new();
+    let mut enums: BTreeSet<String> = BTreeSet::new();
+    for (kind, name, attributes) in owners {
+        let message = ctx.opts.naming.message_name(name);
+        if let Some((other_kind, other)) = messages.insert(message.clone(), (kind, name)) {
+            return Err(Error::Codegen(format!(
+                "{other_kind} '{other}' and {kind} '{name}' would generate the same message \
+                 {message} in one file"
+            )));
+        }
+        for (enum_name, _) in owner_enums(ctx, name, attributes) {
+            if !enums.insert(enum_name.clone()) {
+                return Err(Error::Codegen(format!(
+                    "enum {enum_name} would be generated twice in one file"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Render the split layout's proto files: per-category event files, then
+/// shared objects files (core, plus one set per extension when extension
+/// packages are enabled), then the optional top-level files. Assigned field
+/// numbers are recorded in `lock`.
+fn render_split_files(
+    ctx: &Context,
+    plan: &Plan,
+    files: &mut BTreeMap<String, String>,
+    stats: &mut GenerationStats,
+    lock: &mut FieldNumbers,
+) -> Result<()> {
+    // Each job has its own stats and numbers, merged in job order.
+    let jobs: Vec<RenderJob> = plan
+        .classes_by_category
+        .iter()
+        .map(|((ext, category), classes)| RenderJob::Category {
+            ext: ext.as_deref(),
+            category,
+            classes,
+        })
+        .chain(
+            plan.objects_by_extension
+                .iter()
+                .map(|(ext, objects)| RenderJob::Objects {
+                    ext: ext.as_deref(),
+                    objects,
+                }),
+        )
+        .collect();
+    for rendered in map_ordered(&jobs, |job| job.render(ctx)) {
+        let (job_files, job_stats, job_lock) = rendered?;
+        files.extend(job_files);
+        stats.merge(job_stats);
+        lock.extend(job_lock);
+    }
+
+    if ctx.writes_options_proto() {
+        files.insert(ctx.options_proto(), generate_options_proto(ctx));
+    }
+    if ctx.opts.emit_category_enum {
+        let categories = generate_category_proto(ctx, &plan.classes_by_category);
+        files.insert(
+            format!("{}/category.proto", ctx.import_root(None)),
+            categories,
+        );
+    }
+    if ctx.opts.emit_event_envelope {
+        let envelope = generate_envelope_proto(ctx, &plan.classes_by_category)?;
+        files.insert(
+            format!("{}/ocsf_event.proto", ctx.import_root(None)),
+            envelope,
+        );
+    }
+    Ok(())
+}
+
+/// A unit of independent rendering work in [`render_files`].
+enum RenderJob<'p, 'a> {
+    /// One category's events proto and its enum files.
+    Category {
+        ext: Option<&'p str>,
+        category: &'p str,
+        classes: &'p [&'a OcsfClass],
+    },
+
+    /// One objects proto and its enum files.
+    Objects {
+        ext: Option<&'p str>,
+        objects: &'p BTreeSet<String>,
+    },
+}
+
+impl RenderJob<'_, '_> {
+    /// Render the job's files, keyed by path, with the stats they produced
+    /// and the field numbers they assigned.
+    fn render(
+        &self,
+        ctx: &Context,
+    ) -> Result<(BTreeMap<String, String>, GenerationStats, FieldNumbers)> {
+        let mut stats = GenerationStats::default();
+        let mut lock = FieldNumbers::new();
+        let mut files = BTreeMap::new();
+        match *self {
+            RenderJob::Category {
+                ext,
+                category,
+                classes,
+            } => {
+                let events_proto =
+                    generate_events_proto(ctx, ext, category, classes, &mut stats, &mut lock)?;
+                let enums_package = format!("{}.enums", ctx.category_package(ext, category));
+                let category_dir = ctx.category_dir(ext, category);
+                files.insert(format!("{category_dir}/{category}.proto"), events_proto);
+                files.extend(generate_enum_files(
+                    ctx,
+                    &format!("{category_dir}/enums"),
+                    &enums_package,
+                    class_owners(classes),
+                    &mut stats,
+                ));
+            }
+            RenderJob::Objects { ext, objects } => {
+                let objects_proto =
+                    generate_objects_proto(ctx, ext, objects, &mut stats, &mut lock)?;
+                let enums_package = format!("{}.objects.enums", ctx.package_root(ext));
+                let objects_dir = ctx.objects_dir(ext);
+                files.insert(format!("{objects_dir}/objects.proto"), objects_proto);
+                files.extend(generate_enum_files(
+                    ctx,
+                    &format!("{objects_dir}/enums"),
+                    &enums_package,
+                    object_owners(ctx, objects),
+                    &mut stats,
+                ));
+            }
+        }
+        Ok((files, stats, lock))
+    }
+}
+
+/// Apply `f` to each item, returning results in item order. With the
+/// `parallel` feature the items are processed on the rayon thread pool.
+fn map_ordered<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// The classes and objects a generation run covers, grouped into files.
+struct Plan<'a> {
+    /// Objects transitively referenced by the requested classes, less those
+    /// with no fields.
+    needed_objects: BTreeSet<String>,
+
+    /// Referenced objects left out of `needed_objects` for having no fields.
+    empty_objects_skipped: usize,
+
+    /// Classes grouped by (extension, category), each group in name order.
+    classes_by_category: BTreeMap<(Option<String>, String), Vec<&'a OcsfClass>>,
+
+    /// Needed objects grouped by extension; the core group is always present.
+    objects_by_extension: BTreeMap<Option<String>, BTreeSet<String>>,
+}
+
+impl<'a> Plan<'a> {
+    /// Validate `class_names`, and the options against them, and resolve
+    /// the files they generate.
+    fn new(ctx: &Context<'a>, class_names: &[String]) -> Result<Self> {
+        let schema = ctx.schema;
+        check_package_prefix(&ctx.opts.package_prefix)?;
+        check_version_slug(&ctx.version_slug)?;
+        let single_file = ctx.opts.layout == OutputLayout::SingleFile;
+        if single_file {
+            check_import_free_options(ctx.opts, "single-file")?;
+        }
+
+        // Validate all requested classes exist.
+        for name in class_names {
+            if !schema.classes.contains_key(name.as_str()) {
+                return Err(Error::ClassNotFound {
+                    name: name.clone(),
+                    available: available_classes(schema),
+                });
+            }
+        }
+
+        check_class_collisions(ctx, class_names)?;
+
+        // Resolve which objects are needed (transitive closure via BFS).
+        let (needed_objects, empty_objects_skipped) = ctx.object_graph(class_names);
+        check_object_collisions(ctx, class_names, &needed_objects)?;
+
+        // Group classes by (extension, category) for file organization.
+        let mut classes_by_category: BTreeMap<(Option<String>, String), Vec<&OcsfClass>> =
+            BTreeMap::new();
+        for name in class_names {
+            let cls = &schema.classes[name.as_str()];
+            classes_by_category
+                .entry((ctx.class_extension(cls), cls.category.clone()))
+                .or_default()
+                .push(cls);
+        }
+        // Emit classes in name order so output does not depend on input order.
+        for classes in classes_by_category.values_mut() {
+            classes.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        let mut objects_by_extension: BTreeMap<Option<String>, BTreeSet<String>> =
+            BTreeMap::from([(None, BTreeSet::new())]);
+        for obj_name in &needed_objects {
+            objects_by_extension
+                .entry(ctx.object_extension(obj_name))
+                .or_default()
+                .insert(obj_name.clone());
+        }
+
+        let plan = Self {
+            needed_objects,
+            empty_objects_skipped,
+            classes_by_category,
+            objects_by_extension,
+        };
+        if single_file {
+            check_single_file_names(ctx, &plan)?;
+        }
+        if ctx.opts.emit_event_envelope {
+            envelope_fields(ctx, &plan.classes_by_category)?;
+        }
+        Ok(plan)
+    }
+}
+
+/// Fail with [`Error::Codegen`] unless `prefix` is a dotted sequence of
+/// proto identifiers.
+fn check_package_prefix(prefix: &str) -> Result<()> {
+    let is_ident = |segment: &str| {
+        segment
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if prefix.split('.').all(is_ident) {
+        Ok(())
+    } else {
+        Err(Error::Codegen(format!(
+            "invalid package prefix '{prefix}': expected dot-separated identifiers"
+        )))
+    }
+}
+
+/// Fail with [`Error::Codegen`] if a [`SlugStyle::Custom`] slug is empty or
+/// would add a directory level.
+fn check_version_slug(slug: &str) -> Result<()> {
+    if slug.is_empty() || slug.contains('/') {
+        return Err(Error::Codegen(format!(
+            "invalid version slug '{slug}': must be non-empty with no '/'"
+        )));
+    }
+    Ok(())
+}
+
+/// Fail with [`Error::Codegen`] if two requested classes would share a
+/// message name, e.g. an extension class and the core class it mirrors
+/// (`win/registry_key_activity` and `registry_key_activity`). They live in
+/// different packages, but the JSON sidecars key fields by message name.
+fn check_class_collisions(ctx: &Context, class_names: &[String]) -> Result<()> {
+    let mut by_message: BTreeMap<String, &str> = BTreeMap::new();
+    for name in class_names {
+        let message = ctx.opts.naming.message_name(name);
+        if let Some(other) = by_message.insert(message.clone(), name) {
+            return Err(Error::Codegen(format!(
+                "classes '{other}' and '{name}' would generate the same message {message}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Fail with [`Error::Codegen`] if two distinct objects referenced by the
+/// requested classes would share a message: their names match once the
+/// extension prefix is stripped (`win/service` and `service`), or the naming
+/// strategy gives them the same message name in one package.
+fn check_object_collisions(
+    ctx: &Context,
+    class_names: &[String],
+    needed_objects: &BTreeSet<String>,
+) -> Result<()> {
+    let schema = ctx.schema;
+    let attributes = class_names
+        .iter()
+        .filter_map(|name| schema.classes.get(name.as_str()))
+        .flat_map(|cls| ctx.kept(&cls.name, &cls.attributes))
+        .chain(
+            object_owners(ctx, needed_objects).flat_map(|(owner, attrs)| ctx.kept(owner, attrs)),
+        );
+    let referenced: BTreeSet<&str> = attributes
+        .map(|(_, attr)| attr)
+        .filter(|attr| ctx.selects(attr))
+        .filter_map(|attr| attr.object_type.as_deref())
+        .filter_map(|obj_type| lookup_object(schema, obj_type))
+        .map(|obj| obj.name.as_str())
+        .collect();
+
+    let mut by_key: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+    let mut by_message: BTreeMap<(Option<String>, String), BTreeSet<&str>> = BTreeMap::new();
+    for &name in &referenced {
+        let sanitized = sanitize_object_name(name);
+        let message = ctx.opts.naming.message_name(&sanitized);
+        by_message
+            .entry((ctx.object_extension(name), message))
+            .or_default()
+            .insert(name);
+        by_key.entry(sanitized).or_default().insert(name);
+    }
+    let collision = by_key
+        .into_iter()
+        .map(|(key, names)| (ctx.opts.naming.message_name(&key), names))
+        .chain(
+            by_message
+                .into_iter()
+                .map(|((_, message), names)| (message, names)),
+        )
+        .find(|(_, names)| names.len() > 1);
+    match collision {
+        Some((message, names)) => {
+            let names: Vec<String> = names.iter().map(|name| format!("'{name}'")).collect();
+            Err(Error::Codegen(format!(
+                "objects {} would generate the same message {message}",
+                names.join(" and ")
+            )))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Generate protos for the requested classes and compile them in memory to
+/// a serialized `google.protobuf.FileDescriptorSet`.
+///
+/// Nothing is written to disk and `protoc` is not needed: the generated
+/// text is parsed by [`protox`] with the output directory layout as the
+/// include root. Imports are included, so the set is self-contained.
+///
+/// Requires the `descriptors` feature.
+#[cfg(feature = "descriptors")]
+pub fn generate_descriptor_set(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<Vec<u8>> {
+    let (files, _) = render_files(schema, class_names, opts)?;
+    let protos: BTreeMap<String, String> = files
+        .into_iter()
+        .filter(|(path, _)| path.ends_with(".proto"))
+        .collect();
+    let names: Vec<String> = protos.keys().cloned().collect();
+
+    let mut compiler = protox::Compiler::with_file_resolver(InMemoryProtos(protos));
+    compiler.include_imports(true);
+    compiler
+        .open_files(&names)
+        .map_err(|e| Error::Codegen(format!("compiling generated protos: {e}")))?;
+    Ok(compiler.encode_file_descriptor_set())
+}
+
+/// Resolves imports against generated proto sources held in memory, falling
+/// back to the bundled `google/protobuf/*.proto` files.
+#[cfg(feature = "descriptors")]
+struct InMemoryProtos(BTreeMap<String, String>);
+
+#[cfg(feature = "descriptors")]
+impl protox::file::FileResolver for InMemoryProtos {
+    fn open_file(&self, name: &str) -> std::result::Result<protox::file::File, protox::Error> {
+        match self.0.get(name) {
+            Some(source) => protox::file::File::from_source(name, source),
+            None => protox::file::GoogleFileResolver::new().open_file(name),
+        }
+    }
+}
+
+/// Shared inputs for a single generation run.
+struct Context<'a> {
+    schema: &'a OcsfSchema,
+    opts: &'a GenerationOptions,
+    version_slug: String,
+    /// Every type lives in one package: reference types by bare name.
+    inline_types: bool,
+}
+
+impl<'a> Context<'a> {
+    /// Context for generating the regular multi-file layout.
+    fn new(schema: &'a OcsfSchema, opts: &'a GenerationOptions) -> Self {
+        Self {
+            schema,
+            opts,
+            version_slug: opts.slug_style.slug(&schema.version),
+            inline_types: false,
+        }
+    }
+
+    /// Whether `attr` meets [`GenerationOptions::min_requirement`].
+    fn meets_requirement(&self, attr: &OcsfAttribute) -> bool {
+        self.opts
+            .min_requirement
+            .is_none_or(|min| Requirement::of(attr) >= min)
+    }
+
+    /// Whether `attr` is base or from one of
+    /// [`GenerationOptions::include_profiles`].
+    fn in_profiles(&self, attr: &OcsfAttribute) -> bool {
+        self.opts.include_profiles.as_ref().is_none_or(|profiles| {
+            attr.profile
+                .as_ref()
+                .is_none_or(|profile| profiles.contains(profile))
+        })
+    }
+
+    /// Whether attribute `attr_name` of class or object `owner` is listed in
+    /// [`GenerationOptions::exclude_attributes`].
+    fn excludes(&self, owner: &str, attr_name: &str) -> bool {
+        self.opts
+            .exclude_attributes
+            .get(owner)
+            .is_some_and(|names| names.contains(attr_name))
+    }
+
+    /// The attributes of `owner` not excluded by
+    /// [`GenerationOptions::exclude_attributes`].
+    fn kept<'b>(
+        &self,
+        owner: &str,
+        attributes: &'b BTreeMap<String, OcsfAttribute>,
+    ) -> Vec<(&'b String, &'b OcsfAttribute)> {
+        attributes
+            .iter()
+            .filter(|(attr_name, _)| !self.excludes(owner, attr_name))
+            .collect()
+    }
+
+    /// Whether `attr` passes the requirement and profile filters.
+    fn selects(&self, attr: &OcsfAttribute) -> bool {
+        self.meets_requirement(attr) && self.in_profiles(attr)
+    }
+
+    /// Whether `attr` becomes a field: it passes the filters and is not
+    /// deprecated, or deprecated attributes are emitted
+    /// ([`DeprecatedHandling::Emit`]).
+    fn emits_field(&self, attr: &OcsfAttribute) -> bool {
+        self.selects(attr)
+            && (attr.deprecated.is_none()
+                || self.opts.deprecated_handling == DeprecatedHandling::Emit)
+    }
+
+    /// `attributes` in the order their fields are emitted, per
+    /// [`GenerationOptions::field_order`].
+    fn ordered<'b>(
+        &self,
+        attributes: &'b BTreeMap<String, OcsfAttribute>,
+    ) -> Vec<(&'b String, &'b OcsfAttribute)> {
+        let mut ordered: Vec<_> = attributes.iter().collect();
+        if self.opts.field_order == FieldOrder::SchemaOrder {
+            // Stable, so equal positions stay in name order.
+            ordered.sort_by_key(|(_, attr)| attr.position);
+        }
+        ordered
+    }
+
+    /// Objects transitively referenced by `class_names`, through attributes
+    /// that are not excluded and pass the requirement and profile filters,
+    /// and how many were left out for getting no message (see
+    /// [`Context::gets_message`]): those are referenced as `string`. The
+    /// base `object`'s own references are not followed.
+    fn object_graph(&self, class_names: &[String]) -> (BTreeSet<String>, usize) {
+        let mut objects =
+            resolve_object_graph_with(self.schema, class_names, |owner, attr_name, attr| {
+                owner != BASE_OBJECT && !self.excludes(owner, attr_name) && self.selects(attr)
+            });
+        let referenced = objects.len();
+        objects.retain(|name| {
+            name != BASE_OBJECT
+                && lookup_object(self.schema, name).is_none_or(|obj| self.gets_message(obj))
+        });
+        let skipped = referenced - objects.len();
+        (objects, skipped)
+    }
+
+    /// Whether the message for `obj` has any field.
+    fn has_fields(&self, obj: &OcsfObject) -> bool {
+        self.kept(&sanitize_object_name(&obj.name), &obj.attributes)
+            .into_iter()
+            .any(|(_, attr)| self.emits_field(attr))
+    }
+
+    /// Whether `obj` is generated as a message that fields reference: it is
+    /// not the base [`BASE_OBJECT`] and [`Context::has_fields`].
+    fn gets_message(&self, obj: &OcsfObject) -> bool {
+        obj.name != BASE_OBJECT && self.has_fields(obj)
+    }
+
+    /// Whether a reference to `obj_type` is emitted as `string` for want of
+    /// a message: it names the base [`BASE_OBJECT`], whether or not the
+    /// schema defines it, or an object that fails [`Context::gets_message`].
+    fn references_no_message(&self, obj_type: &str) -> bool {
+        obj_type == BASE_OBJECT
+            || lookup_object(self.schema, obj_type).is_some_and(|obj| !self.gets_message(obj))
+    }
+
+    /// Proto package prefix for core (`None`) or extension types:
+    /// `ocsf.v1_7_0` or `ocsf.v1_7_0.ext.win`.
+    fn package_root(&self, ext: Option<&str>) -> String {
+        let prefix = &self.opts.package_prefix;
+        match ext {
+            None => format!("{prefix}.{}", self.version_slug),
+            Some(ext) => format!("{prefix}.{}.ext.{ext}", self.version_slug),
+        }
+    }
+
+    /// Import path prefix matching [`Context::package_root`], which is also
+    /// the directory the files live in under the output directory:
+    /// `ocsf/v1_7_0` or `ocsf/v1_7_0/ext/win`.
+    fn import_root(&self, ext: Option<&str>) -> String {
+        let prefix = self.prefix_dir();
+        match ext {
+            None => format!("{prefix}/{}", self.version_slug),
+            Some(ext) => format!("{prefix}/{}/ext/{ext}", self.version_slug),
+        }
+    }
+
+    /// Directory of [`GenerationOptions::package_prefix`]: `ocsf` or
+    /// `com/acme/ocsf`.
+    fn prefix_dir(&self) -> String {
+        self.opts.package_prefix.replace('.', "/")
+    }
+
+    /// Whether `ocsf_options.proto` is written, for
+    /// [`GenerationOptions::emit_profile_options`] or
+    /// [`GenerationOptions::emit_uid_options`].
+    fn writes_options_proto(&self) -> bool {
+        (self.opts.emit_profile_options || self.opts.emit_uid_options)
+            && self.opts.layout == OutputLayout::Split
+    }
+
+    /// Path of the custom options file, shared by every OCSF version:
+    /// `ocsf/ocsf_options.proto`.
+    fn options_proto(&self) -> String {
+        format!("{}/ocsf_options.proto", self.prefix_dir())
+    }
+
+    /// Directory of a category's event files: `ocsf/v1_7_0/events/iam`.
+    ///
+    /// Extension classes get their own directory, under the extension
+    /// package with [`GenerationOptions::extension_packages`]
+    /// (`ocsf/v1_7_0/ext/win/events/iam`) and otherwise below the core
+    /// events (`ocsf/v1_7_0/events/win/iam`).
+    fn category_dir(&self, ext: Option<&str>, category: &str) -> String {
+        match ext.filter(|_| !self.opts.extension_packages) {
+            Some(ext) => format!("{}/events/{ext}/{category}", self.import_root(None)),
+            None => format!("{}/events/{category}", self.import_root(ext)),
+        }
+    }
+
+    /// Package of a category's event messages, matching
+    /// [`Context::category_dir`]: `ocsf.v1_7_0.events.iam` or
+    /// `ocsf.v1_7_0.events.win.iam`.
+    fn category_package(&self, ext: Option<&str>, category: &str) -> String {
+        match ext.filter(|_| !self.opts.extension_packages) {
+            Some(ext) => format!("{}.events.{ext}.{category}", self.package_root(None)),
+            None => format!("{}.events.{category}", self.package_root(ext)),
+        }
+    }
+
+    /// Field number allocator for `message`, honoring
+    /// [`GenerationOptions::pinned_field_numbers`] and
+    /// [`GenerationOptions::field_numbers_lock`].
+    fn field_allocator(&self, message: &str) -> FieldAllocator {
+        let pinned = &self.opts.pinned_field_numbers;
+        match &self.opts.field_numbers_lock {
+            Some(lock) => FieldAllocator::with_lock(pinned, lock, message),
+            None => FieldAllocator::new(pinned, message),
+        }
+    }
+
+    /// Directory of the object files: `ocsf/v1_7_0/objects`.
+    fn objects_dir(&self, ext: Option<&str>) -> String {
+        format!("{}/objects", self.import_root(ext))
+    }
+
+    /// Whether integer-keyed enums get generated enum types, per
+    /// [`GenerationOptions::enum_mode`].
+    fn generates_enums(&self) -> bool {
+        self.opts.enum_mode == EnumMode::Generate
+    }
+
+    /// Files under `enums_dir` holding the enums named `enum_names`: the
+    /// shared `enums.proto` (always written, unless enums are not
+    /// generated), or one file per enum.
+    fn enum_files(
+        &self,
+        enums_dir: &str,
+        enum_names: impl IntoIterator<Item = String>,
+    ) -> Vec<String> {
+        if !self.generates_enums() {
+            return Vec::new();
+        }
+        match self.opts.enum_layout {
+            EnumLayout::Shared => vec![format!("{enums_dir}/enums.proto")],
+            EnumLayout::PerEnum => enum_names
+                .into_iter()
+                .map(|name| format!("{enums_dir}/{}.proto", name.to_lowercase()))
+                .collect(),
+        }
+    }
+
+    /// Extension an event class belongs to (`win/registry_key_activity` →
+    /// `win`), which keeps its category apart from the core one of the same
+    /// name; see [`Context::category_dir`].
+    fn class_extension(&self, cls: &OcsfClass) -> Option<String> {
+        extension_prefix(&cls.name)
+    }
+
+    /// Extension an object belongs to, when extension packages are on.
+    ///
+    /// Derived from the object's schema key (`win/win_service` → `win`),
+    /// falling back to its `name`.
+    fn object_extension(&self, obj_name: &str) -> Option<String> {
+        if !self.opts.extension_packages {
+            return None;
+        }
+        lookup_object_key(self.schema, obj_name)
+            .and_then(extension_prefix)
+            .or_else(|| {
+                lookup_object(self.schema, obj_name).and_then(|o| extension_prefix(&o.name))
+            })
+    }
+
+    /// Import statements for the object files of every extension referenced
+    /// by `attributes`, other than `own`. Sorted for deterministic output.
+    fn extension_object_imports<'b>(
+        &self,
+        own: Option<&str>,
+        attributes: impl IntoIterator<Item = &'b OcsfAttribute>,
+    ) -> BTreeSet<String> {
+        attributes
+            .into_iter()
+            .filter(|attr| self.emits_field(attr) && attr.type_name == "object_t")
+            .filter_map(|attr| attr.object_type.as_deref())
+            .filter(|obj_type| {
+                lookup_object(self.schema, obj_type).is_some_and(|obj| self.gets_message(obj))
+            })
+            .map(|obj_type| self.object_extension(obj_type))
+            .filter(|ext| ext.as_deref() != own)
+            .map(|ext| {
+                format!(
+                    "import \"{}/objects/objects.proto\";",
+                    self.import_root(ext.as_deref())
+                )
+            })
+            .collect()
+    }
+}
+
+/// Extension prefix of an OCSF name: `"win/win_service"` → `Some("win")`.
+fn extension_prefix(name: &str) -> Option<String> {
+    name.split_once('/').map(|(ext, _)| ext.to_string())
+}
+
+// ── Event class proto generation ───────────────────────────────────────
+
+fn generate_events_proto(
+    ctx: &Context,
+    ext: Option<&str>,
+    category: &str,
+    classes: &[&OcsfClass],
+    stats: &mut GenerationStats,
+    lock: &mut FieldNumbers,
+) -> Result<String> {
+    let package = ctx.category_package(ext, category);
+    let core_root = ctx.import_root(None);
+    let mut out = String::new();
+
+    write_file_header(&mut out, ctx, &package);
+    writeln!(out).unwrap();
+    let enums = class_owners(classes).flat_map(|(owner, attrs)| owner_enums(ctx, owner, attrs));
+    let enum_files = ctx.enum_files(
+        &format!("{}/enums", ctx.category_dir(ext, category)),
+        enums.map(|(name, _)| name),
+    );
+    for path in &enum_files {
+        writeln!(out, "import \"{path}\";").unwrap();
+    }
+    if !enum_files.is_empty() {
+        writeln!(out).unwrap();
+    }
+    writeln!(out, "import \"{core_root}/objects/objects.proto\";").unwrap();
+    let fields = classes.iter().flat_map(|cls| {
+        let owner = cls.name.as_str();
+        ctx.kept(owner, &cls.attributes)
+            .into_iter()
+            .map(move |(attr_name, attr)| (owner, attr_name.as_str(), attr))
+    });
+    let attributes = fields.clone().map(|(_, _, attr)| attr);
+    for import in ctx.extension_object_imports(None, attributes.clone()) {
+        writeln!(out, "{import}").unwrap();
+    }
+    write_well_known_imports(&mut out, ctx, fields.clone());
+    write_override_imports(&mut out, ctx, fields);
+    write_pgv_import(&mut out, ctx, attributes);
+    if ctx.opts.emit_uid_options
+        || ctx.opts.emit_profile_options && classes.iter().any(|cls| !cls.profiles.is_empty())
+    {
+        writeln!(out, "import \"{}\";", ctx.options_proto()).unwrap();
+    }
+
+    write_event_messages(
+        &mut out,
+        ctx,
+        category,
+        classes,
+        &format!("{package}.enums"),
+        stats,
+        lock,
+    )?;
+    Ok(out)
+}
+
+/// Write the event class messages of `classes`, referencing their enums in
+/// `enum_package` and recording their field numbers in `lock`.
+fn write_event_messages(
+    out: &mut String,
+    ctx: &Context,
+    category: &str,
+    classes: &[&OcsfClass],
+    enum_package: &str,
+    stats: &mut GenerationStats,
+    lock: &mut FieldNumbers,
+) -> Result<()> {
+    for cls in classes {
+        writeln!(out).unwrap();
+        writeln!(out, "// Event: {category}").unwrap();
+        writeln!(out, "// Class UID: {}", cls.uid).unwrap();
+        write_message_description(out, ctx, &cls.description);
+        let message_name = ctx.opts.naming.message_name(&cls.name);
+        write_message_open(out, &message_name, cls.deprecated.as_ref(), stats);
+        if ctx.opts.emit_profile_options && !cls.profiles.is_empty() {
+            writeln!(
+                out,
+                "\toption ({}.profiles) = \"{}\";",
+                ctx.opts.package_prefix,
+                cls.profiles.join(",")
+            )
+            .unwrap();
+        }
+        if ctx.opts.emit_uid_options {
+            let prefix = &ctx.opts.package_prefix;
+            writeln!(out, "\toption ({prefix}.class_uid) = {};", cls.uid).unwrap();
+            writeln!(
+                out,
+                "\toption ({prefix}.category_uid) = {};",
+                cls.category_uid
+            )
+            .unwrap();
+        }
+
+        let fields = message_fields(
+            ctx,
+            "class",
+            &cls.name,
+            &message_name,
+            &cls.attributes,
+            enum_package,
+            stats,
+        )?;
+        write_fields(out, ctx, &fields);
+        write_freed_numbers(out, &fields.numbers);
+        check_not_empty(ctx, &message_name, &cls.name, fields.numbers.is_empty())?;
+        fields.numbers.record(&message_name, lock);
+
+        writeln!(out, "}}").unwrap();
+    }
+    Ok(())
+}
+
+// ── Custom options ─────────────────────────────────────────────────────
+
+/// Generate `ocsf/ocsf_options.proto`, defining the OCSF custom options in
+/// the [`GenerationOptions::package_prefix`] package.
+///
+/// Extension numbers come from the 50000–99999 range protobuf sets aside
+/// for in-house options.
+fn generate_options_proto(ctx: &Context) -> String {
+    let mut out = String::new();
+    // Shared by every OCSF version, so the provenance names none.
+    writeln!(
+        out,
+        "// Generated by ocsf-proto-gen {}. DO NOT EDIT.",
+        env!("CARGO_PKG_VERSION")
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "syntax = \"proto3\";").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "package {};", ctx.opts.package_prefix).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "import \"google/protobuf/descriptor.proto\";").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "extend google.protobuf.MessageOptions {{").unwrap();
+    writeln!(
+        out,
+        "\t// Comma-separated active OCSF profiles of an event class."
+    )
+    .unwrap();
+    writeln!(out, "\tstring profiles = 50000;").unwrap();
+    writeln!(out, "\t// OCSF class UID of an event class (e.g., 3002).").unwrap();
+    writeln!(out, "\tint32 class_uid = 50001;").unwrap();
+    writeln!(out, "\t// OCSF category UID of an event class (e.g., 3).").unwrap();
+    writeln!(out, "\tint32 category_uid = 50002;").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+// ── Event envelope generation ──────────────────────────────────────────
+
+/// Generate `ocsf_event.proto`: an `OcsfEvent` message whose `oneof event`
+/// holds any generated class, so every event fits one message.
+///
+/// Each class's oneof field is numbered by its class UID, which keeps
+/// numbers stable as classes are added or removed.
+fn generate_envelope_proto(
+    ctx: &Context,
+    classes_by_category: &BTreeMap<(Option<String>, String), Vec<&OcsfClass>>,
+) -> Result<String> {
+    let mut out = String::new();
+    write_file_header(&mut out, ctx, &ctx.package_root(None));
+    writeln!(out).unwrap();
+    for (ext, category) in classes_by_category.keys() {
+        writeln!(
+            out,
+            "import \"{}/{category}.proto\";",
+            ctx.category_dir(ext.as_deref(), category)
+        )
+        .unwrap();
+    }
+    write_envelope_message(&mut out, ctx, classes_by_category)?;
+    Ok(out)
+}
+
+/// Write the `OcsfEvent` message, referencing classes by bare name when
+/// every type shares one package.
+fn write_envelope_message(
+    out: &mut String,
+    ctx: &Context,
+    classes_by_category: &BTreeMap<(Option<String>, String), Vec<&OcsfClass>>,
+) -> Result<()> {
+    let fields = envelope_fields(ctx, classes_by_category)?;
+    writeln!(out).unwrap();
+    writeln!(out, "// Any generated OCSF event class.").unwrap();
+    writeln!(out, "message OcsfEvent {{").unwrap();
+    writeln!(out, "\toneof event {{").unwrap();
+    for (uid, (proto_type, field_name)) in &fields {
+        writeln!(out, "\t\t{proto_type} {field_name} = {uid};").unwrap();
+    }
+    writeln!(out, "\t}}").unwrap();
+    writeln!(out, "}}").unwrap();
+    Ok(())
+}
+
+/// The `OcsfEvent` oneof fields, as `(type, name)` keyed by class UID,
+/// failing with [`Error::Codegen`] if a UID is not a valid field number or
+/// is shared by two classes.
+fn envelope_fields(
+    ctx: &Context,
+    classes_by_category: &BTreeMap<(Option<String>, String), Vec<&OcsfClass>>,
+) -> Result<BTreeMap<u32, (String, String)>> {
+    let mut fields = BTreeMap::new();
+    for ((ext, category), classes) in classes_by_category {
+        let package = ctx.category_package(ext.as_deref(), category);
+        for cls in classes {
+            if cls.uid == 0
+                || cls.uid > MAX_FIELD_NUMBER
+                || RESERVED_FIELD_NUMBERS.contains(&cls.uid)
+            {
+                return Err(Error::Codegen(format!(
+                    "class '{}' has UID {}, which is not a valid OcsfEvent field number",
+                    cls.name, cls.uid
+                )));
+            }
+            let message_name = ctx.opts.naming.message_name(&cls.name);
+            let proto_type = if ctx.inline_types {
+                message_name
+            } else {
+                format!("{package}.{message_name}")
+            };
+            let field = (proto_type, sanitize_field_name(&cls.name));
+            if let Some((_, other)) = fields.insert(cls.uid, field) {
+                return Err(Error::Codegen(format!(
+                    "classes '{other}' and '{}' share UID {}",
+                    cls.name, cls.uid
+                )));
+            }
+        }
+    }
+    Ok(fields)
+}
+
+/// Generate `category.proto`: an `OcsfCategory` enum with one variant per
+/// category of the generated classes, valued by its `category_uid`.
+fn generate_category_proto(
+    ctx: &Context,
+    classes_by_category: &BTreeMap<(Option<String>, String), Vec<&OcsfClass>>,
+) -> String {
+    let mut out = String::new();
+    write_file_header(&mut out, ctx, &ctx.package_root(None));
+    write_category_enum(&mut out, ctx, classes_by_category);
+    out
+}
+
+/// Write the `OcsfCategory` enum of the categories of `classes_by_category`.
+fn write_category_enum(
+    out: &mut String,
+    ctx: &Context,
+    classes_by_category: &BTreeMap<(Option<String>, String), Vec<&OcsfClass>>,
+) {
+    let mut categories: BTreeMap<u32, &OcsfClass> = BTreeMap::new();
+    for cls in classes_by_category.values().flatten() {
+        categories.entry(cls.category_uid).or_insert(cls);
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "enum OcsfCategory {{").unwrap();
+    if !categories.contains_key(&0) {
+        writeln!(out, "\tOCSF_CATEGORY_UNSPECIFIED = 0;").unwrap();
+    }
+    for (uid, cls) in &categories {
+        let variant = ctx.opts.naming.enum_variant_name(&cls.category);
+        write!(out, "\tOCSF_CATEGORY_{variant} = {uid};").unwrap();
+        if !cls.category_name.is_empty() {
+            write!(out, " // {}", cls.category_name).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+}
+
+// ── Class enum generation ──────────────────────────────────────────────
+
+/// Write the integer-keyed enums of `classes`.
+fn write_class_enums(
+    out: &mut String,
+    ctx: &Context,
+    classes: &[&OcsfClass],
+    stats: &mut GenerationStats,
+) {
+    for (owner, attributes) in class_owners(classes) {
+        for (enum_name, enum_vals) in owner_enums(ctx, owner, attributes) {
+            write_enum_definition(out, &enum_name, enum_vals, ctx.opts, stats);
+        }
+    }
+}
+
+/// Generate the enum files of `owners` under `enums_dir` per
+/// [`GenerationOptions::enum_layout`], keyed by path.
+fn generate_enum_files<'b>(
+    ctx: &Context,
+    enums_dir: &str,
+    package: &str,
+    owners: impl Iterator<Item = (&'b str, &'b BTreeMap<String, OcsfAttribute>)>,
+    stats: &mut GenerationStats,
+) -> BTreeMap<String, String> {
+    if !ctx.generates_enums() {
+        return BTreeMap::new();
+    }
+    let enums: Vec<_> = owners
+        .flat_map(|(owner, attributes)| owner_enums(ctx, owner, attributes))
+        .collect();
+    let paths = ctx.enum_files(enums_dir, enums.iter().map(|(name, _)| name.clone()));
+    let mut files = BTreeMap::new();
+    match ctx.opts.enum_layout {
+        EnumLayout::Shared => {
+            let mut out = String::new();
+            write_file_header(&mut out, ctx, package);
+            for (enum_name, enum_vals) in &enums {
+                write_enum_definition(&mut out, enum_name, enum_vals, ctx.opts, stats);
+            }
+            files.insert(paths[0].clone(), out);
+        }
+        EnumLayout::PerEnum => {
+            for (path, (enum_name, enum_vals)) in paths.into_iter().zip(&enums) {
+                let mut out = String::new();
+                write_file_header(&mut out, ctx, package);
+                write_enum_definition(&mut out, enum_name, enum_vals, ctx.opts, stats);
+                files.insert(path, out);
+            }
+        }
+    }
+    files
+}
+
+/// `(owner name, attributes)` for each of `classes`.
+fn class_owners<'b>(
+    classes: &[&'b OcsfClass],
+) -> impl Iterator<Item = (&'b str, &'b BTreeMap<String, OcsfAttribute>)> {
+    classes
+        .iter()
+        .map(|cls| (cls.name.as_str(), &cls.attributes))
+}
+
+/// `(owner name, attributes)` for each of `needed_objects` in the schema.
+fn object_owners<'b>(
+    ctx: &Context<'b>,
+    needed_objects: &'b BTreeSet<String>,
+) -> impl Iterator<Item = (&'b str, &'b BTreeMap<String, OcsfAttribute>)> {
+    let schema = ctx.schema;
+    needed_objects.iter().filter_map(move |obj_name| {
+        lookup_object(schema, obj_name).map(|obj| (obj_name.as_str(), &obj.attributes))
+    })
+}
+
+/// The generated enums of one class or object: `(enum name, values)` for
+/// each non-deprecated, integer-keyed enum attribute, or none under
+/// [`EnumMode::Int32WithComment`].
+fn owner_enums<'b>(
+    ctx: &Context,
+    owner: &str,
+    attributes: &'b BTreeMap<String, OcsfAttribute>,
+) -> Vec<(String, &'b BTreeMap<String, OcsfEnumValue>)> {
+    ctx.kept(owner, attributes)
+        .into_iter()
+        .filter(|_| ctx.generates_enums())
+        .filter(|(_, attr)| ctx.emits_field(attr))
+        .filter_map(|(attr_name, attr)| {
+            let enum_vals = attr.enum_values.as_ref()?;
+            is_integer_enum(enum_vals)
+                .then(|| (ctx.opts.naming.enum_name(owner, attr_name), enum_vals))
+        })
+        .collect()
+}
+
+// ── Object proto generation ────────────────────────────────────────────
+
+fn generate_objects_proto(
+    ctx: &Context,
+    ext: Option<&str>,
+    needed_objects: &BTreeSet<String>,
+    stats: &mut GenerationStats,
+    lock: &mut FieldNumbers,
+) -> Result<String> {
+    let package = format!("{}.objects", ctx.package_root(ext));
+    let import_root = ctx.import_root(ext);
+    let mut out = String::new();
+
+    write_file_header(&mut out, ctx, &package);
+    writeln!(out).unwrap();
+    let enums = object_owners(ctx, needed_objects)
+        .flat_map(|(owner, attrs)| owner_enums(ctx, owner, attrs));
+    let enum_files = ctx.enum_files(
+        &format!("{import_root}/objects/enums"),
+        enums.map(|(name, _)| name),
+    );
+    for path in &enum_files {
+        writeln!(out, "import \"{path}\";").unwrap();
+    }
+    let fields = needed_objects
+        .iter()
+        .filter_map(|name| lookup_object(ctx.schema, name).map(|obj| (name.as_str(), obj)))
+        .flat_map(|(owner, obj)| {
+            ctx.kept(owner, &obj.attributes)
+                .into_iter()
+                .map(move |(attr_name, attr)| (owner, attr_name.as_str(), attr))
+        });
+    let attributes = fields.clone().map(|(_, _, attr)| attr);
+    for import in ctx.extension_object_imports(ext, attributes.clone()) {
+        writeln!(out, "{import}").unwrap();
+    }
+    write_well_known_imports(&mut out, ctx, fields.clone());
+    write_override_imports(&mut out, ctx, fields);
+    write_pgv_import(&mut out, ctx, attributes);
+
+    write_object_messages(
+        &mut out,
+        ctx,
+        needed_objects,
+        &format!("{package}.enums"),
+        stats,
+        lock,
+    )?;
+    Ok(out)
+}
+
+/// Write the messages of `needed_objects`, referencing their enums in
+/// `enum_package` and recording their field numbers in `lock`.
+fn write_object_messages(
+    out: &mut String,
+    ctx: &Context,
+    needed_objects: &BTreeSet<String>,
+    enum_package: &str,
+    stats: &mut GenerationStats,
+    lock: &mut FieldNumbers,
+) -> Result<()> {
+    for obj_name in needed_objects {
+        let obj = lookup_object(ctx.schema, obj_name);
+        let Some(obj) = obj else {
+            stats.warn(format!(
+                "object '{obj_name}' referenced but not found in schema"
+            ));
+            continue;
+        };
+        writeln!(out).unwrap();
+        write_message_description(out, ctx, &obj.description);
+        if let Some(observable) = obj.observable.filter(|_| ctx.opts.emit_observable_comments) {
+            writeln!(out, "// Observable type: {observable}").unwrap();
+        }
+        let message_name = ctx.opts.naming.message_name(obj_name);
+        write_message_open(out, &message_name, obj.deprecated.as_ref(), stats);
+
+        let fields = message_fields(
+            ctx,
+            "object",
+            obj_name,
+            &message_name,
+            &obj.attributes,
+            enum_package,
+            stats,
+        )?;
+        write_fields(out, ctx, &fields);
+        write_freed_numbers(out, &fields.numbers);
+        fields.numbers.record(&message_name, lock);
+
+        writeln!(out, "}}").unwrap();
+    }
+    Ok(())
+}
+
+// ── Object enum generation ─────────────────────────────────────────────
+
+/// Write the integer-keyed enums of `needed_objects`.
+fn write_object_enums(
+    out: &mut String,
+    ctx: &Context,
+    needed_objects: &BTreeSet<String>,
+    stats: &mut GenerationStats,
+) {
+    for (owner, attributes) in object_owners(ctx, needed_objects) {
+        for (enum_name, enum_vals) in owner_enums(ctx, owner, attributes) {
+            write_enum_definition(out, &enum_name, enum_vals, ctx.opts, stats);
+        }
+    }
+}
+
+// ── Standalone single-class generation ─────────────────────────────────
+
+/// Generate one self-contained proto file for a single event class.
+///
+/// The file holds the class message, every transitively referenced object
+/// message, and all of their enums in the package
+/// `ocsf.<version>.standalone.<class>`, with types referenced by bare name
+/// and no `import` statements. Useful for sharing one class on its own,
+/// e.g. in a bug report or a service that only handles that class.
+///
+/// [`GenerationOptions::extension_packages`] does not apply: extension
+/// types are inlined like any other. Options that would need an import
+/// fail with [`Error::Codegen`], as for [`OutputLayout::SingleFile`].
+pub fn generate_standalone(
+    schema: &OcsfSchema,
+    class_name: &str,
+    opts: &GenerationOptions,
+) -> Result<String> {
+    let ctx = Context {
+        inline_types: true,
+        ..Context::new(schema, opts)
+    };
+    let Some(cls) = schema.classes.get(class_name) else {
+        return Err(Error::ClassNotFound {
+            name: class_name.to_string(),
+            available: available_classes(schema),
+        });
+    };
+    check_import_free_options(opts, "standalone")?;
+    check_package_prefix(&opts.package_prefix)?;
+    check_version_slug(&ctx.version_slug)?;
+    let class_names = [class_name.to_string()];
+    let (needed_objects, _) = ctx.object_graph(&class_names);
+    check_object_collisions(&ctx, &class_names, &needed_objects)?;
+    let mut stats = GenerationStats::default();
+    let mut out = String::new();
+
+    write_file_header(
+        &mut out,
+        &ctx,
+        &format!(
+            "{}.standalone.{}",
+            ctx.package_root(None),
+            class_name.replace('/', ".")
+        ),
+    );
+    // The lock's numbers apply, but the lock itself is not updated.
+    let mut lock = FieldNumbers::new();
+    write_event_messages(
+        &mut out,
+        &ctx,
+        &cls.category,
+        &[cls],
+        "",
+        &mut stats,
+        &mut lock,
+    )?;
+    write_object_messages(&mut out, &ctx, &needed_objects, "", &mut stats, &mut lock)?;
+    write_class_enums(&mut out, &ctx, &[cls], &mut stats);
+    write_object_enums(&mut out, &ctx, &needed_objects, &mut stats);
+    Ok(out)
+}
+
+// ── JSON Schema generation ─────────────────────────────────────────────
+
+/// Dialect of the documents [`generate_json_schema`] writes.
+#[cfg(feature = "jsonschema")]
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Generate a Draft 2020-12 JSON Schema document for each requested event
+/// class, keyed by path relative to the output directory:
+/// `json-schema/<class>.schema.json` under the [`version_root`] (e.g.,
+/// `ocsf/v1_7_0/json-schema/`), with extension classes under `<ext>/`.
+///
+/// Each document is self-contained: the objects the class transitively
+/// references are defined under `$defs` and referenced with `$ref`.
+/// Properties use OCSF attribute names and follow the requirement, profile,
+/// and deprecation options, and `required` lists the required ones.
+/// Integer enums become `integer` with an `enum` of their values; `json_t`
+/// and objects with no fields (e.g., `unmapped`) are left unconstrained,
+/// as are unknown types and missing objects, which fail instead under
+/// [`GenerationOptions::strict`].
+///
+/// Requires the `jsonschema` feature.
+#[cfg(feature = "jsonschema")]
+pub fn generate_json_schema(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<BTreeMap<PathBuf, String>> {
+    let ctx = Context::new(schema, opts);
+    check_package_prefix(&opts.package_prefix)?;
+    check_version_slug(&ctx.version_slug)?;
+    let json_schema_dir = version_root(schema, opts).join("json-schema");
+
+    let mut files = BTreeMap::new();
+    for class_name in class_names {
+        let Some(cls) = schema.classes.get(class_name) else {
+            return Err(Error::ClassNotFound {
+                name: class_name.clone(),
+                available: available_classes(schema),
+            });
+        };
+        let (needed_objects, _) = ctx.object_graph(std::slice::from_ref(class_name));
+
+        let mut defs = serde_json::Map::new();
+        for obj_name in &needed_objects {
+            if let Some(obj) = lookup_object(schema, obj_name) {
+                let def = json_schema_object(
+                    &ctx,
+                    ("object", obj_name),
+                    &obj.caption,
+                    &obj.description,
+                    &obj.attributes,
+                    &needed_objects,
+                )?;
+                defs.insert(obj_name.clone(), def);
+            }
+        }
+        let mut doc = json_schema_object(
+            &ctx,
+            ("class", class_name),
+            &cls.caption,
+            &cls.description,
+            &cls.attributes,
+            &needed_objects,
+        )?;
+        doc["$schema"] = JSON_SCHEMA_DIALECT.into();
+        if !defs.is_empty() {
+            doc["$defs"] = defs.into();
+        }
+
+        let json = serde_json::to_string_pretty(&doc)
+            .map_err(|e| Error::Codegen(format!("serializing JSON Schema: {e}")))?;
+        let path = json_schema_dir.join(format!("{class_name}.schema.json"));
+        files.insert(path, json);
+    }
+    Ok(files)
+}
+
+/// The JSON Schema of an event class or object (`owner` is
+/// `(kind, name)`), with a property per emitted attribute.
+#[cfg(feature = "jsonschema")]
+fn json_schema_object(
+    ctx: &Context,
+    owner: (&str, &str),
+    caption: &str,
+    description: &str,
+    attributes: &BTreeMap<String, OcsfAttribute>,
+    needed_objects: &BTreeSet<String>,
+) -> Result<serde_json::Value> {
+    let mut properties = serde_json::Map::new();
+    let mut required = Vec::new();
+    let attributes = ctx.kept(owner.1, attributes);
+    for (attr_name, attr) in attributes
+        .into_iter()
+        .filter(|(_, attr)| ctx.emits_field(attr))
+    {
+        let mut property = json_schema_type(ctx, owner, attr_name, attr, needed_objects)?;
+        if attr.is_array {
+            property = serde_json::json!({"type": "array", "items": property});
+        }
+        property["title"] = attr.caption.clone().into();
+        if !attr.description.is_empty() {
+            property["description"] = attr.description.clone().into();
+        }
+        if attr.deprecated.is_some() {
+            property["deprecated"] = true.into();
+        }
+        if Requirement::of(attr) == Requirement::Required {
+            required.push(attr_name.clone());
+        }
+        properties.insert(attr_name.clone(), property);
+    }
+
+    let mut doc = serde_json::json!({
+        "type": "object",
+        "title": caption,
+        "properties": properties,
+    });
+    if !description.is_empty() {
+        doc["description"] = description.into();
+    }
+    if !required.is_empty() {
+        doc["required"] = required.into();
+    }
+    Ok(doc)
+}
+
+/// The JSON Schema of one value of `attr`, ignoring `is_array`.
+#[cfg(feature = "jsonschema")]
+fn json_schema_type(
+    ctx: &Context,
+    (owner_kind, owner): (&str, &str),
+    attr_name: &str,
+    attr: &OcsfAttribute,
+    needed_objects: &BTreeSet<String>,
+) -> Result<serde_json::Value> {
+    let unconstrained = |reason: String| {
+        check_strict(
+            ctx,
+            owner_kind,
+            owner,
+            attr_name,
+            &FieldType::defaulted(false, reason),
+        )
+        .map(|()| serde_json::json!({}))
+    };
+
+    if attr.type_name == "object_t" {
+        let obj_type = attr.object_type.as_deref().unwrap_or("unknown");
+        let sanitized = sanitize_object_name(obj_type);
+        if needed_objects.contains(&sanitized) {
+            return Ok(serde_json::json!({"$ref": format!("#/$defs/{sanitized}")}));
+        }
+        if lookup_object(ctx.schema, obj_type).is_some() {
+            return Ok(serde_json::json!({}));
+        }
+        return unconstrained(format!("unknown object type '{obj_type}'"));
+    }
+    if let Some(enum_vals) = attr.enum_values.as_ref().filter(|e| is_integer_enum(e)) {
+        let values: BTreeSet<i32> = enum_vals
+            .keys()
+            .filter_map(|key| key.parse().ok())
+            .collect();
+        return Ok(serde_json::json!({"type": "integer", "enum": values}));
+    }
+    if attr.type_name == "json_t" {
+        return Ok(serde_json::json!({}));
+    }
+    let json_type = match known_scalar_type(&attr.type_name) {
+        Some("string") => "string",
+        Some("int32" | "int64") => "integer",
+        Some("double") => "number",
+        Some("bool") => "boolean",
+        _ => return unconstrained(format!("unknown type '{}'", attr.type_name)),
+    };
+    Ok(serde_json::json!({"type": json_type}))
+}
+
+// ── Rust struct generation ─────────────────────────────────────────────
+
+/// Rust keywords, which [`rust_field_ident`] escapes.
+#[cfg(feature = "rustgen")]
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Generate Rust source with serde-ready structs for the requested event
+/// classes and the objects they transitively reference, skipping the
+/// proto → prost round trip.
+///
+/// Each class and object becomes a `#[derive(Serialize, Deserialize)]`
+/// struct named by [`to_pascal_case`](crate::type_map::to_pascal_case), with
+/// a field per emitted attribute: required attributes are plain, the rest
+/// `Option<T>` (arrays `Vec<T>`) left out of the JSON when empty. Fields
+/// keep their OCSF names through `#[serde(rename)]` when the Rust
+/// identifier differs. Integer enums become `#[repr(i32)]` Rust enums
+/// (de)serialized as their integer values via
+/// `#[serde(try_from = "i32", into = "i32")]`. Object references use the
+/// generated struct, boxed where objects reference each other in a cycle
+/// (see [`detect_cycles`]); `json_t` and fieldless objects map to
+/// `serde_json::Value`, as do unknown types and missing objects, which fail
+/// instead under [`GenerationOptions::strict`].
+///
+/// The source needs `serde` (with `derive`) and `serde_json`.
+///
+/// Requires the `rustgen` feature.
+#[cfg(feature = "rustgen")]
+pub fn generate_rust(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    opts: &GenerationOptions,
+) -> Result<String> {
+    let ctx = Context::new(schema, opts);
+    let mut classes = Vec::new();
+    for class_name in class_names {
+        let Some(cls) = schema.classes.get(class_name) else {
+            return Err(Error::ClassNotFound {
+                name: class_name.clone(),
+                available: available_classes(schema),
+            });
+        };
+        classes.push(cls);
+    }
+    let (needed_objects, _) = ctx.object_graph(class_names);
+    let cyclic: BTreeSet<String> = detect_cycles(schema, &needed_objects)
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut rust = RustModule {
+        ctx: &ctx,
+        needed_objects: &needed_objects,
+        cyclic: &cyclic,
+        types: BTreeMap::new(),
+        out: String::new(),
+    };
+
+    writeln!(
+        rust.out,
+        "// Generated by ocsf-proto-gen {} from OCSF {}. DO NOT EDIT.",
+        env!("CARGO_PKG_VERSION"),
+        schema.version
+    )
+    .unwrap();
+    writeln!(rust.out).unwrap();
+    writeln!(rust.out, "use serde::{{Deserialize, Serialize}};").unwrap();
+    for cls in classes {
+        let doc = format!("{} (OCSF class {}).", cls.caption, cls.uid);
+        rust.write_struct(("class", &cls.name), &doc, &cls.attributes)?;
+    }
+    for obj_name in &needed_objects {
+        if let Some(obj) = lookup_object(schema, obj_name) {
+            let doc = format!("{} (OCSF object `{}`).", obj.caption, obj.name);
+            rust.write_struct(("object", obj_name), &doc, &obj.attributes)?;
+        }
+    }
+    Ok(rust.out)
+}
+
+/// Rust source being built by [`generate_rust`].
+#[cfg(feature = "rustgen")]
+struct RustModule<'a> {
+    ctx: &'a Context<'a>,
+    needed_objects: &'a BTreeSet<String>,
+    /// Objects on a reference cycle, whose references to each other are
+    /// boxed.
+    cyclic: &'a BTreeSet<String>,
+    /// Rust type name → the class or object it was generated for.
+    types: BTreeMap<String, String>,
+    out: String,
+}
+
+#[cfg(feature = "rustgen")]
+impl RustModule<'_> {
+    /// Claim `type_name` for `owner`, failing if another class or object
+    /// already generated it.
+    fn claim(&mut self, type_name: &str, owner: &str) -> Result<()> {
+        match self.types.get(type_name) {
+            Some(other) => Err(Error::Codegen(format!(
+                "'{other}' and '{owner}' would generate the same Rust type {type_name}"
+            ))),
+            None => {
+                self.types.insert(type_name.to_string(), owner.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Write the struct for a class or object (`owner` is `(kind, name)`),
+    /// followed by its enums.
+    fn write_struct(
+        &mut self,
+        owner: (&str, &str),
+        doc: &str,
+        attributes: &BTreeMap<String, OcsfAttribute>,
+    ) -> Result<()> {
+        let (_, owner_name) = owner;
+        let struct_name = crate::type_map::to_pascal_case(owner_name);
+        self.claim(&struct_name, owner_name)?;
+
+        let mut fields = String::new();
+        let mut enums = Vec::new();
+        let field_names = field_names(attributes, self.ctx.opts.field_name_source);
+        for (attr_name, attr) in self.ctx.kept(owner_name, attributes) {
+            if !self.ctx.emits_field(attr) {
+                continue;
+            }
+            let (ident, _) = &field_names[attr_name.as_str()];
+            let mut ty = self.field_type(owner, attr_name, attr)?;
+            if let Some(enum_vals) = attr.enum_values.as_ref().filter(|e| is_integer_enum(e)) {
+                enums.push((ty.clone(), &attr.caption, enum_vals));
+            }
+
+            let mut serde_attrs = Vec::new();
+            if ident != attr_name {
+                serde_attrs.push(format!("rename = \"{attr_name}\""));
+            }
+            let required = Requirement::of(attr) == Requirement::Required;
+            if attr.is_array {
+                ty = format!("Vec<{ty}>");
+                if !required {
+                    serde_attrs.push("default, skip_serializing_if = \"Vec::is_empty\"".into());
+                }
+            } else if !required {
+                ty = format!("Option<{ty}>");
+                serde_attrs.push("default, skip_serializing_if = \"Option::is_none\"".into());
+            }
+
+            writeln!(fields, "    /// {}", attr.caption).unwrap();
+            if let Some(deprecated) = &attr.deprecated {
+                writeln!(fields, "    ///").unwrap();
+                writeln!(fields, "    /// Deprecated: {}", deprecated.message).unwrap();
+            }
+            if !serde_attrs.is_empty() {
+                writeln!(fields, "    #[serde({})]", serde_attrs.join(", ")).unwrap();
+            }
+            writeln!(fields, "    pub {}: {ty},", rust_field_ident(ident)).unwrap();
+        }
+
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "/// {doc}").unwrap();
+        writeln!(
+            self.out,
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]"
+        )
+        .unwrap();
+        writeln!(self.out, "pub struct {struct_name} {{").unwrap();
+        self.out.push_str(&fields);
+        writeln!(self.out, "}}").unwrap();
+
+        for (enum_name, caption, enum_vals) in enums {
+            self.claim(&enum_name, owner_name)?;
+            self.write_enum(&enum_name, caption, &struct_name, enum_vals);
+        }
+        Ok(())
+    }
+
+    /// The Rust type of one value of `attr`, ignoring `is_array` and
+    /// requirement.
+    fn field_type(
+        &self,
+        (owner_kind, owner): (&str, &str),
+        attr_name: &str,
+        attr: &OcsfAttribute,
+    ) -> Result<String> {
+        let untyped = |reason: String| {
+            check_strict(
+                self.ctx,
+                owner_kind,
+                owner,
+                attr_name,
+                &FieldType::defaulted(false, reason),
+            )
+            .map(|()| "serde_json::Value".to_string())
+        };
+
+        if attr.type_name == "object_t" {
+            let obj_type = attr.object_type.as_deref().unwrap_or("unknown");
+            if lookup_object(self.ctx.schema, obj_type).is_none() {
+                return untyped(format!("unknown object type '{obj_type}'"));
+            }
+            let sanitized = sanitize_object_name(obj_type);
+            if !self.needed_objects.contains(&sanitized) {
+                return Ok("serde_json::Value".to_string());
+            }
+            let struct_name = crate::type_map::to_pascal_case(&sanitized);
+            let recursive = self.cyclic.contains(owner) && self.cyclic.contains(&sanitized);
+            if recursive && !attr.is_array {
+                return Ok(format!("Box<{struct_name}>"));
+            }
+            return Ok(struct_name);
+        }
+        if attr.enum_values.as_ref().is_some_and(is_integer_enum) {
+            let owner = sanitize_object_name(owner);
+            return Ok(crate::type_map::to_pascal_case(&format!(
+                "{owner}_{attr_name}"
+            )));
+        }
+        if attr.type_name == "json_t" {
+            return Ok("serde_json::Value".to_string());
+        }
+        let rust_type = match known_scalar_type(&attr.type_name) {
+            Some("string") => "String",
+            Some("int32") => "i32",
+            Some("int64") => "i64",
+            Some("double") => "f64",
+            Some("bool") => "bool",
+            _ => return untyped(format!("unknown type '{}'", attr.type_name)),
+        };
+        Ok(rust_type.to_string())
+    }
+
+    /// Write an integer enum with its `i32` conversions.
+    ///
+    /// Keys parsing to a value already listed are dropped, and a variant
+    /// name already taken gets the value appended (`Other99`).
+    fn write_enum(
+        &mut self,
+        enum_name: &str,
+        caption: &str,
+        struct_name: &str,
+        enum_vals: &BTreeMap<String, OcsfEnumValue>,
+    ) {
+        let mut variants: Vec<(i32, String, &str)> = Vec::new();
+        let mut taken = BTreeSet::new();
+        for (value, variant, enum_value) in enum_entries(enum_vals, &DefaultNaming) {
+            if variants.iter().any(|(v, _, _)| *v == value) {
+                continue;
+            }
+            let mut variant = crate::type_map::to_pascal_case(&variant.to_lowercase());
+            if !taken.insert(variant.clone()) {
+                variant = format!("{variant}{}", value.unsigned_abs());
+                taken.insert(variant.clone());
+            }
+            variants.push((value, variant, &enum_value.caption));
+        }
+
+        let out = &mut self.out;
+        writeln!(out).unwrap();
+        writeln!(out, "/// {caption} values of [`{struct_name}`].").unwrap();
+        writeln!(
+            out,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]"
+        )
+        .unwrap();
+        writeln!(out, "#[serde(try_from = \"i32\", into = \"i32\")]").unwrap();
+        writeln!(out, "#[repr(i32)]").unwrap();
+        writeln!(out, "pub enum {enum_name} {{").unwrap();
+        for (value, variant, caption) in &variants {
+            writeln!(out, "    /// {caption}").unwrap();
+            writeln!(out, "    {variant} = {value},").unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+
+        writeln!(out).unwrap();
+        writeln!(out, "impl From<{enum_name}> for i32 {{").unwrap();
+        writeln!(out, "    fn from(value: {enum_name}) -> Self {{").unwrap();
+        writeln!(out, "        value as i32").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "}}").unwrap();
+
+        writeln!(out).unwrap();
+        writeln!(out, "impl TryFrom<i32> for {enum_name} {{").unwrap();
+        writeln!(out, "    type Error = String;").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "    fn try_from(value: i32) -> Result<Self, Self::Error> {{"
+        )
+        .unwrap();
+        writeln!(out, "        match value {{").unwrap();
+        for (value, variant, _) in &variants {
+            writeln!(out, "            {value} => Ok(Self::{variant}),").unwrap();
+        }
+        writeln!(
+            out,
+            "            other => Err(format!(\"unknown {enum_name} value {{other}}\")),"
+        )
+        .unwrap();
+        writeln!(out, "        }}").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "}}").unwrap();
+    }
+}
+
+/// `ident` as a Rust field identifier: keywords become raw identifiers
+/// (`r#type`), or get a `_` suffix where Rust forbids that (`self_`).
+#[cfg(feature = "rustgen")]
+fn rust_field_ident(ident: &str) -> String {
+    match ident {
+        "crate" | "self" | "Self" | "super" => format!("{ident}_"),
+        _ if RUST_KEYWORDS.contains(&ident) => format!("r#{ident}"),
+        _ => ident.to_string(),
+    }
+}
+
+// ── Buf configuration ──────────────────────────────────────────────────
+
+/// Buf module configuration written under
+/// [`GenerationOptions::emit_buf_config`], at the output directory root.
+const BUF_YAML: &str = "buf.yaml";
+
+/// Buf code generation template written beside [`BUF_YAML`].
+const BUF_GEN_YAML: &str = "buf.gen.yaml";
+
+/// Build `buf.yaml`: a v2 workspace with one module rooted at the output
+/// directory, where import paths (`ocsf/v1_7_0/...`) already start.
+///
+/// It names no OCSF version, since several can share the module.
+fn generate_buf_yaml() -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "# Generated by ocsf-proto-gen {}. DO NOT EDIT.",
+        env!("CARGO_PKG_VERSION")
+    )
+    .unwrap();
+    writeln!(out, "version: v2").unwrap();
+    writeln!(out, "modules:").unwrap();
+    writeln!(out, "  - path: .").unwrap();
+    out
+}
+
+/// Build `buf.gen.yaml`: the module as input, limited to the directories
+/// this version's files are written to and the shared options file when
+/// it is written, and a `prost` plugin writing to `gen/`.
+fn generate_buf_gen_yaml(ctx: &Context) -> String {
+    let mut paths = vec![ctx.import_root(None)];
+    if ctx.writes_options_proto() {
+        paths.push(ctx.options_proto());
+    }
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "# Generated by ocsf-proto-gen {} from OCSF {}; edits are overwritten on regeneration.",
+        env!("CARGO_PKG_VERSION"),
+        ctx.schema.version
+    )
+    .unwrap();
+    writeln!(out, "version: v2").unwrap();
+    writeln!(out, "inputs:").unwrap();
+    writeln!(out, "  - directory: .").unwrap();
+    writeln!(out, "    paths:").unwrap();
+    for path in paths {
+        writeln!(out, "      - {path}").unwrap();
+    }
+    writeln!(out, "plugins:").unwrap();
+    writeln!(out, "  - remote: buf.build/community/neoeinstein-prost").unwrap();
+    writeln!(out, "    out: gen").unwrap();
+    out
+}
+
+// ── Enum value map (JSON reference) ────────────────────────────────────
+
+fn generate_enum_value_map(
+    ctx: &Context,
+    class_names: &[String],
+    needed_objects: &BTreeSet<String>,
+) -> Result<String> {
+    let mut map: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    for (enum_name, enum_vals) in enum_map_enums(ctx, class_names, needed_objects) {
+        for (key_str, val) in enum_vals {
+            if let Ok(key) = key_str.parse::<i32>() {
+                let variant_name =
+                    variant_name_or_value(ctx.opts.naming.enum_variant_name(&val.caption), key);
+                let full_name = format!("{enum_name}_{variant_name}");
+                let mut entry = serde_json::json!({"name": val.caption, "value": key});
+                if let Some(desc) = val.description.as_deref().filter(|d| !d.is_empty()) {
+                    entry["description"] = serde_json::Value::from(desc);
+                }
+                map.insert(full_name, entry);
+            }
+        }
+    }
+
+    serde_json::to_string_pretty(&map)
+        .map_err(|e| Error::Codegen(format!("serializing enum map: {e}")))
+}
+
+/// Build `enum-value-map-by-number.json`: `enum name → value → caption`,
+/// with values in numeric order.
+fn generate_enum_value_map_by_number(
+    ctx: &Context,
+    class_names: &[String],
+    needed_objects: &BTreeSet<String>,
+) -> Result<String> {
+    let mut map: BTreeMap<String, BTreeMap<i32, &str>> = BTreeMap::new();
+    for (enum_name, enum_vals) in enum_map_enums(ctx, class_names, needed_objects) {
+        let values = map.entry(enum_name).or_default();
+        for (key_str, val) in enum_vals {
+            if let Ok(key) = key_str.parse::<i32>() {
+                values.insert(key, &val.caption);
+            }
+        }
+    }
+
+    serde_json::to_string_pretty(&map)
+        .map_err(|e| Error::Codegen(format!("serializing enum map by number: {e}")))
+}
+
+/// The integer-keyed enums the enum value maps cover, as `(enum name,
+/// values)`: those of the classes, and of the objects under
+/// [`GenerationOptions::enum_map_include_objects`], that match
+/// [`GenerationOptions::enum_map_globs`].
+fn enum_map_enums<'b>(
+    ctx: &Context<'b>,
+    class_names: &[String],
+    needed_objects: &'b BTreeSet<String>,
+) -> Vec<(String, &'b BTreeMap<String, OcsfEnumValue>)> {
+    let classes = class_names
+        .iter()
+        .filter_map(|name| ctx.schema.classes.get(name.as_str()))
+        .map(|cls| (cls.name.as_str(), &cls.attributes));
+    let objects = object_owners(ctx, needed_objects).filter(|_| ctx.opts.enum_map_include_objects);
+
+    let opts = ctx.opts;
+    let mut enums = Vec::new();
+    for (owner, attributes) in classes.chain(objects) {
+        for (attr_name, attr) in ctx.kept(owner, attributes) {
+            let Some(enum_vals) = &attr.enum_values else {
+                continue;
+            };
+            if !is_integer_enum(enum_vals) {
+                continue;
+            }
+            let enum_name = opts.naming.enum_name(owner, attr_name);
+            if !opts.enum_map_globs.is_empty()
+                && !opts
+                    .enum_map_globs
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &enum_name))
+            {
+                continue;
+            }
+            enums.push((enum_name, enum_vals));
+        }
+    }
+    enums
+}
+
+/// Check the rendered `enum_map` against the enum variants defined in the
+/// `.proto` files of `files`, for [`GenerationOptions::verify_enum_map`].
+///
+/// Only enums both the map covers ([`enum_map_enums`]) and the protos
+/// define are compared: the map also lists the enums of attributes left
+/// out of the protos (deprecated or filtered). Variant lines with a
+/// trailing comment are the synthetic zeros, which the map leaves out.
+fn verify_enum_map(
+    ctx: &Context,
+    class_names: &[String],
+    needed_objects: &BTreeSet<String>,
+    files: &BTreeMap<String, String>,
+    enum_map: &str,
+) -> Result<()> {
+    let covered: BTreeSet<String> = enum_map_enums(ctx, class_names, needed_objects)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+
+    let mut emitted: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let protos = files.iter().filter(|(path, _)| path.ends_with(".proto"));
+    for (_, content) in protos {
+        let mut current: Option<&str> = None;
+        for line in content.lines() {
+            if let Some(name) = line
+                .strip_prefix("enum ")
+                .and_then(|l| l.strip_suffix(" {"))
+            {
+                current = covered.contains(name).then_some(name);
+                if current.is_some() {
+                    emitted.entry(name.to_string()).or_default();
+                }
+                continue;
+            }
+            if line == "}" {
+                current = None;
+            }
+            let Some(enum_name) = current else {
+                continue;
+            };
+            let line = line.trim();
+            if line.starts_with("option ") || line.contains("//") {
+                continue;
+            }
+            if let Some((variant, _)) = line.split_once(" = ") {
+                emitted
+                    .entry(enum_name.to_string())
+                    .or_default()
+                    .insert(variant.to_string());
+            }
+        }
+    }
+
+    let map: BTreeMap<String, serde_json::Value> = serde_json::from_str(enum_map)
+        .map_err(|e| Error::Codegen(format!("parsing enum map: {e}")))?;
+    let mut mapped: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for full_name in map.keys() {
+        // The longest covered enum name the variant starts with owns it.
+        let owner = covered
+            .iter()
+            .filter(|name| {
+                full_name
+                    .strip_prefix(name.as_str())
+                    .is_some_and(|rest| rest.starts_with('_'))
+            })
+            .max_by_key(|name| name.len());
+        let Some(owner) = owner else {
+            return Err(Error::Codegen(format!(
+                "enum-value-map.json entry {full_name} belongs to no generated enum"
+            )));
+        };
+        mapped
+            .entry(owner.clone())
+            .or_default()
+            .insert(full_name.clone());
+    }
+
+    for (enum_name, in_protos) in &emitted {
+        let in_protos = Some(in_protos).filter(|variants| !variants.is_empty());
+        let in_map = mapped.get(enum_name);
+        if in_protos != in_map {
+            return Err(Error::Codegen(format!(
+                "enum-value-map.json disagrees with the generated enum {enum_name}: \
+                 map has {}, protos define {}",
+                describe_variants(in_map),
+                describe_variants(in_protos)
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `A, B` or `nothing`, for [`verify_enum_map`] errors.
+fn describe_variants(variants: Option<&BTreeSet<String>>) -> String {
+    match variants {
+        Some(variants) => variants.iter().cloned().collect::<Vec<_>>().join(", "),
+        None => "nothing".to_string(),
+    }
+}
+
+// ── Field metadata (JSON reference) ────────────────────────────────────
+
+/// Build `field-metadata.json`: OCSF attribute metadata keyed by
+/// `Message.field` for every emitted (non-deprecated) field.
+///
+/// Each entry carries whichever of `requirement`, `group`, `profile`, and
+/// `sibling` the attribute defines; absent keys are omitted.
+fn generate_field_metadata(
+    ctx: &Context,
+    class_names: &[String],
+    needed_objects: &BTreeSet<String>,
+) -> Result<String> {
+    let naming = &ctx.opts.naming;
+    let mut map: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+
+    for name in class_names {
+        if let Some(cls) = ctx.schema.classes.get(name.as_str()) {
+            let message = naming.message_name(&cls.name);
+            collect_field_metadata(ctx, &message, &cls.name, &cls.attributes, &mut map);
+        }
+    }
+
+    for obj_name in needed_objects {
+        if let Some(obj) = lookup_object(ctx.schema, obj_name) {
+            collect_field_metadata(
+                ctx,
+                &naming.message_name(obj_name),
+                obj_name,
+                &obj.attributes,
+                &mut map,
+            );
+        }
+    }
+
+    serde_json::to_string_pretty(&map)
+        .map_err(|e| Error::Codegen(format!("serializing field metadata: {e}")))
+}
+
+fn collect_field_metadata(
+    ctx: &Context,
+    message: &str,
+    owner: &str,
+    attributes: &BTreeMap<String, OcsfAttribute>,
+    map: &mut BTreeMap<String, serde_json::Value>,
+) {
+    let field_names = field_names(attributes, ctx.opts.field_name_source);
+    for (attr_name, attr) in ctx.kept(owner, attributes) {
+        if !ctx.emits_field(attr) {
+            continue;
+        }
+        let mut entry = serde_json::Map::new();
+        let fields = [
+            ("requirement", &attr.requirement),
+            ("group", &attr.group),
+            ("profile", &attr.profile),
+            ("sibling", &attr.sibling),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                entry.insert(key.to_string(), serde_json::Value::String(value.clone()));
+            }
+        }
+        let field_name = &field_names[attr_name.as_str()].0;
+        map.insert(
+            format!("{message}.{field_name}"),
+            serde_json::Value::Object(entry),
+        );
+    }
+}
+
+// ── Object name map (JSON reference) ───────────────────────────────────
+
+/// Build `object-name-map.json`: generated object message name → original
+/// OCSF object name.
+///
+/// Message names drop the extension prefix and change case
+/// (`win/win_service` → `WinService`), so this map is the only way back to
+/// the canonical name at runtime.
+fn generate_object_name_map(ctx: &Context, needed_objects: &BTreeSet<String>) -> Result<String> {
+    let mut map: BTreeMap<String, String> = BTreeMap::new();
+    for obj_name in needed_objects {
+        if let Some(original) = lookup_object_key(ctx.schema, obj_name) {
+            map.insert(ctx.opts.naming.message_name(obj_name), original.to_string());
+        }
+    }
+
+    serde_json::to_string_pretty(&map)
+        .map_err(|e| Error::Codegen(format!("serializing object name map: {e}")))
+}
+
+/// Find the schema key of the object [`lookup_object`] would return.
+fn lookup_object_key<'a>(schema: &'a OcsfSchema, name: &str) -> Option<&'a str> {
+    if let Some((key, _)) = schema.objects.get_key_value(name) {
+        return Some(key);
+    }
+    let sanitized = sanitize_object_name(name);
+    schema
+        .objects
+        .get_key_value(&sanitized)
+        .or_else(|| {
+            schema
+                .objects
+                .iter()
+                .find(|(_, o)| sanitize_object_name(&o.name) == sanitized)
+        })
+        .map(|(key, _)| key.as_str())
+}
+
+// ── Presence caveats (JSON reference) ──────────────────────────────────
+
+/// Build `presence-caveats.json`: fields whose proto3 implicit default
+/// collides with a real OCSF value, keyed by `Message.field`.
+///
+/// Proto3 cannot tell an unset enum from one set to 0. When OCSF defines
+/// value 0 itself (typically `Unknown`), no synthetic `UNSPECIFIED` is
+/// emitted, so an absent field reads back as that real value. Each entry
+/// names the enum and the caption of its 0 value. Repeated fields carry no
+/// per-element presence and are not listed.
+fn generate_presence_caveats(
+    ctx: &Context,
+    class_names: &[String],
+    needed_objects: &BTreeSet<String>,
+) -> Result<String> {
+    let naming = &ctx.opts.naming;
+    let mut map: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+
+    for name in class_names {
+        if let Some(cls) = ctx.schema.classes.get(name.as_str()) {
+            let message = naming.message_name(&cls.name);
+            collect_presence_caveats(ctx, &message, &cls.name, &cls.attributes, &mut map);
+        }
+    }
+
+    for obj_name in needed_objects {
+        if let Some(obj) = lookup_object(ctx.schema, obj_name) {
+            let message = naming.message_name(obj_name);
+            collect_presence_caveats(ctx, &message, obj_name, &obj.attributes, &mut map);
+        }
+    }
+
+    serde_json::to_string_pretty(&map)
+        .map_err(|e| Error::Codegen(format!("serializing presence caveats: {e}")))
+}
+
+fn collect_presence_caveats(
+    ctx: &Context,
+    message: &str,
+    owner: &str,
+    attributes: &BTreeMap<String, OcsfAttribute>,
+    map: &mut BTreeMap<String, serde_json::Value>,
+) {
+    let field_names = field_names(attributes, ctx.opts.field_name_source);
+    for (attr_name, attr) in ctx.kept(owner, attributes) {
+        if !ctx.emits_field(attr) || attr.is_array {
+            continue;
+        }
+        let Some(enum_vals) = &attr.enum_values else {
+            continue;
+        };
+        if !is_integer_enum(enum_vals) {
+            continue;
+        }
+        let Some(zero) = enum_vals
+            .iter()
+            .find(|(k, _)| k.parse::<i32>() == Ok(0))
+            .map(|(_, v)| v)
+        else {
+            continue;
+        };
+        let field_name = &field_names[attr_name.as_str()].0;
+        map.insert(
+            format!("{message}.{field_name}"),
+            serde_json::json!({
+                "enum": ctx.opts.naming.enum_name(owner, attr_name),
+                "zero_value": zero.caption,
+            }),
+        );
+    }
+}
+
+// ── Field type resolution ──────────────────────────────────────────────
+
+/// A resolved proto field type.
+struct FieldType {
+    /// Emit as `repeated`.
+    repeated: bool,
+
+    /// Proto scalar type or qualified message/enum reference.
+    proto_type: String,
+
+    /// A numeric scalar or enum, which proto3 packs when repeated.
+    packable: bool,
+
+    /// Why the type fell back to `string` (e.g., `unknown type 'x_t'`),
+    /// for TODO markers and [`GenerationOptions::strict`].
+    defaulted: Option<String>,
+}
+
+impl FieldType {
+    fn new(repeated: bool, proto_type: impl Into<String>) -> Self {
+        Self {
+            repeated,
+            proto_type: proto_type.into(),
+            packable: false,
+            defaulted: None,
+        }
+    }
+
+    /// A proto scalar type; every scalar but `string` and `bytes` is packable.
+    fn scalar(repeated: bool, proto_type: &str) -> Self {
+        Self {
+            packable: !matches!(proto_type, "string" | "bytes"),
+            ..Self::new(repeated, proto_type)
+        }
+    }
+
+    /// A reference to a generated enum.
+    fn enum_ref(repeated: bool, enum_type: String) -> Self {
+        Self {
+            packable: true,
+            ..Self::new(repeated, enum_type)
+        }
+    }
+
+    /// A `string` fallback for a type that could not be resolved.
+    fn defaulted(repeated: bool, reason: String) -> Self {
+        Self {
+            repeated,
+            proto_type: "string".to_string(),
+            packable: false,
+            defaulted: Some(reason),
+        }
+    }
+}
+
+/// Resolve the proto type for an event class or object attribute.
+///
+/// For integer-keyed enum attributes, returns a qualified reference to the
+/// generated enum type in `enum_package`, the enums package of the file the
+/// owner is emitted in (e.g., `ocsf.v1_7_0.events.iam.enums` for event
+/// classes, `ocsf.v1_7_0.objects.enums` for objects).
+fn resolve_field_type(
+    ctx: &Context,
+    attr: &OcsfAttribute,
+    attr_name: &str,
+    owner: &str,
+    enum_package: &str,
+    stats: &mut GenerationStats,
+) -> FieldType {
+    let repeated = attr.is_array;
+
+    if let Some(proto_type) = field_override(ctx, owner, attr_name, attr) {
+        if PROTO_SCALARS.contains(&proto_type) {
+            return FieldType::scalar(repeated, proto_type);
+        }
+        return FieldType::new(repeated, proto_type);
+    }
+
+    if let Some((proto_type, _)) = well_known_type(ctx, owner, attr_name, attr) {
+        return FieldType::new(repeated, proto_type);
+    }
+
+    // Without the well-known types, inferred maps hold JSON strings.
+    if infers_map(ctx, owner, attr_name, attr) {
+        return FieldType::new(repeated, "map<string, string>");
+    }
+
+    // Object references → qualified message type.
+    if attr.type_name == "object_t" {
+        return resolve_object_ref(ctx, attr, attr_name, owner, repeated, stats);
+    }
+
+    // Integer-keyed enum → qualified enum type reference, or plain int32.
+    if let Some(enum_vals) = &attr.enum_values {
+        if is_integer_enum(enum_vals) {
+            if !ctx.generates_enums() {
+                return FieldType::scalar(repeated, "int32");
+            }
+            let enum_name = ctx.opts.naming.enum_name(owner, attr_name);
+            if ctx.inline_types {
+                return FieldType::enum_ref(repeated, enum_name);
+            }
+            return FieldType::enum_ref(repeated, format!("{enum_package}.{enum_name}"));
+        }
+        stats.string_enum_fields_skipped += 1;
+    }
+
+    // Primitive type.
+    match known_scalar_type(&attr.type_name) {
+        Some(proto_type) => FieldType::scalar(repeated, proto_type),
+        None => {
+            stats.warn(format!(
+                "{owner}.{attr_name}: unknown type '{}' defaulted to string",
+                attr.type_name
+            ));
+            stats.unknown_types_defaulted += 1;
+            FieldType::defaulted(repeated, format!("unknown type '{}'", attr.type_name))
+        }
+    }
+}
+
+/// Proto scalar type names.
+const PROTO_SCALARS: [&str; 15] = [
+    "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32",
+    "fixed64", "sfixed32", "sfixed64", "bool", "string", "bytes",
+];
+
+/// The [`GenerationOptions::type_overrides`] entry for `attr`'s type, unless
+/// it is an object reference or an integer enum.
+fn type_override<'c>(ctx: &'c Context, attr: &OcsfAttribute) -> Option<&'c str> {
+    if attr.type_name == "object_t" || attr.enum_values.as_ref().is_some_and(is_integer_enum) {
+        return None;
+    }
+    ctx.opts
+        .type_overrides
+        .get(&attr.type_name)
+        .map(String::as_str)
+}
+
+/// The proto type `owner`'s `attr_name` field is overridden to: its
+/// [`GenerationOptions::json_field_overrides`] entry for a `json_t`
+/// attribute, otherwise its [`type_override`].
+fn field_override<'c>(
+    ctx: &'c Context,
+    owner: &str,
+    attr_name: &str,
+    attr: &OcsfAttribute,
+) -> Option<&'c str> {
+    let json_override = || {
+        let message = ctx.opts.naming.message_name(owner);
+        ctx.opts
+            .json_field_overrides
+            .get(&format!("{message}.{attr_name}"))
+            .map(String::as_str)
+    };
+    if attr.type_name == "json_t" {
+        if let Some(proto_type) = json_override() {
+            return Some(proto_type);
+        }
+    }
+    type_override(ctx, attr)
+}
+
+/// Whether `owner`'s `attr_name` field is emitted as a map under
+/// [`GenerationOptions::infer_maps`]: a dictionary-named `json_t` attribute,
+/// or `object_t` reference to an object that gets no message, without a
+/// [`field_override`].
+fn infers_map(ctx: &Context, owner: &str, attr_name: &str, attr: &OcsfAttribute) -> bool {
+    if !ctx.opts.infer_maps
+        || !ctx.opts.map_attribute_names.contains(attr_name)
+        || field_override(ctx, owner, attr_name, attr).is_some()
+    {
+        return false;
+    }
+    match attr.type_name.as_str() {
+        "json_t" => true,
+        "object_t" => attr
+            .object_type
+            .as_deref()
+            .is_some_and(|obj_type| ctx.references_no_message(obj_type)),
+        _ => false,
+    }
+}
+
+/// Write [`GenerationOptions::extra_imports`] if one of `fields`, as
+/// `(owner, attribute name, attribute)`, has a [`field_override`].
+fn write_override_imports<'b>(
+    out: &mut String,
+    ctx: &Context,
+    mut fields: impl Iterator<Item = (&'b str, &'b str, &'b OcsfAttribute)>,
+) {
+    if fields.any(|(owner, attr_name, attr)| {
+        ctx.emits_field(attr) && field_override(ctx, owner, attr_name, attr).is_some()
+    }) {
+        for import in &ctx.opts.extra_imports {
+            writeln!(out, "import \"{import}\";").unwrap();
+        }
+    }
+}
+
+/// The well-known type `attr` is emitted as under
+/// [`GenerationOptions::timestamp_mapping`] and
+/// [`GenerationOptions::json_mapping`], with the file that defines it.
+fn well_known_type(
+    ctx: &Context,
+    owner: &str,
+    attr_name: &str,
+    attr: &OcsfAttribute,
+) -> Option<(&'static str, &'static str)> {
+    if attr.enum_values.as_ref().is_some_and(is_integer_enum)
+        || field_override(ctx, owner, attr_name, attr).is_some()
+    {
+        return None;
+    }
+    let structured = ctx.opts.json_mapping == JsonMapping::WellKnownStruct;
+    if structured && infers_map(ctx, owner, attr_name, attr) {
+        return Some((
+            "map<string, google.protobuf.Value>",
+            "google/protobuf/struct.proto",
+        ));
+    }
+    match attr.type_name.as_str() {
+        "timestamp_t" if ctx.opts.timestamp_mapping == TimestampMapping::WellKnownTimestamp => {
+            Some((
+                "google.protobuf.Timestamp",
+                "google/protobuf/timestamp.proto",
+            ))
+        }
+        "json_t" if structured => Some(("google.protobuf.Struct", "google/protobuf/struct.proto")),
+        // The same no-message case `resolve_object_ref` emits as `string`.
+        "object_t" if structured => attr
+            .object_type
+            .as_deref()
+            .filter(|obj_type| ctx.references_no_message(obj_type))
+            .map(|_| ("google.protobuf.Struct", "google/protobuf/struct.proto")),
+        _ => None,
+    }
+}
+
+/// Import the files defining every [`well_known_type`] used by one of
+/// `fields`, as `(owner, attribute name, attribute)`, in sorted order.
+fn write_well_known_imports<'b>(
+    out: &mut String,
+    ctx: &Context,
+    fields: impl Iterator<Item = (&'b str, &'b str, &'b OcsfAttribute)>,
+) {
+    let files: BTreeSet<&str> = fields
+        .filter(|(_, _, attr)| ctx.emits_field(attr))
+        .filter_map(|(owner, attr_name, attr)| well_known_type(ctx, owner, attr_name, attr))
+        .map(|(_, file)| file)
+        .collect();
+    for file in files {
+        writeln!(out, "import \"{file}\";").unwrap();
+    }
+}
+
+/// Name of the OCSF base object: the untyped "any object" that `unmapped`
+/// and other free-form attributes reference.
+pub(crate) const BASE_OBJECT: &str = "object";
+
+/// Resolve an `object_t` attribute to a qualified proto message reference.
+///
+/// A reference to the base [`BASE_OBJECT`], or to an object with no
+/// attributes to emit, becomes `string` instead (for JSON). Under
+/// [`JsonMapping::WellKnownStruct`] such fields are resolved earlier, by
+/// [`well_known_type`].
+///
+/// A reference to an object that is not generated is recorded in
+/// [`GenerationStats::dangling_references`] and also emitted as `string`,
+/// so no message ever names a type that does not exist.
+fn resolve_object_ref(
+    ctx: &Context,
+    attr: &OcsfAttribute,
+    attr_name: &str,
+    owner: &str,
+    repeated: bool,
+    stats: &mut GenerationStats,
+) -> FieldType {
+    let obj_type = attr.object_type.as_deref().unwrap_or("unknown");
+    let sanitized = sanitize_object_name(obj_type);
+
+    // The base `object` stands for arbitrary data (e.g., the `unmapped`
+    // field), so it is `string` whatever fields the schema gives it, and
+    // even if the schema leaves it out.
+    if obj_type == BASE_OBJECT {
+        return FieldType::new(repeated, "string");
+    }
+    let Some(obj) = lookup_object(ctx.schema, obj_type) else {
+        stats.warn(format!(
+            "{owner}.{attr_name}: object type '{obj_type}' not found, defaulted to string"
+        ));
+        stats.unknown_types_defaulted += 1;
+        stats
+            .dangling_references
+            .push(format!("{owner}.{attr_name} -> {obj_type}"));
+        return FieldType::defaulted(repeated, format!("unknown object type '{obj_type}'"));
+    };
+
+    // Empty objects would produce proto messages that cannot hold data, so
+    // they are `string` too, letting the field carry JSON.
+    if !ctx.gets_message(obj) {
+        return FieldType::new(repeated, "string");
+    }
+
+    let pascal = ctx.opts.naming.message_name(&sanitized);
+    if ctx.inline_types {
+        return FieldType::new(repeated, pascal);
+    }
+    let package_root = ctx.package_root(ctx.object_extension(obj_type).as_deref());
+    FieldType::new(repeated, format!("{package_root}.objects.{pascal}"))
+}
+
+// ── Field naming ───────────────────────────────────────────────────────
+
+/// Compute proto field names for every attribute of a message.
+///
+/// Names are derived per `source`: the attribute key passed through
+/// [`sanitize_field_name`], or the snake_cased caption. Derived names equal
+/// to the attribute key claim their identifier first; the rest get a numeric
+/// suffix (`_2`, `_3`, ...) on collision, assigned in alphabetical attribute
+/// order so the result is deterministic.
+///
+/// Returns `attr_name → (field_name, collided)`.
+fn field_names(
+    attributes: &BTreeMap<String, OcsfAttribute>,
+    source: FieldNameSource,
+) -> BTreeMap<&str, (String, bool)> {
+    let mut names: BTreeMap<&str, (String, bool)> = BTreeMap::new();
+    let mut taken: BTreeSet<String> = BTreeSet::new();
+
+    // Pass 1: attributes whose derived name is their own key keep it.
+    let mut pending = Vec::new();
+    for (attr_name, attr) in attributes {
+        let sanitized = match source {
+            FieldNameSource::Caption if !attr.caption.trim().is_empty() => {
+                caption_to_field_name(&attr.caption)
+            }
+            _ => sanitize_field_name(attr_name),
+        };
+        if sanitized == *attr_name {
+            taken.insert(sanitized.clone());
+            names.insert(attr_name, (sanitized, false));
+        } else {
+            pending.push((attr_name.as_str(), sanitized));
+        }
+    }
+
+    // Pass 2: sanitized names, disambiguated on collision.
+    for (attr_name, sanitized) in pending {
+        let mut candidate = sanitized.clone();
+        let mut suffix = 2;
+        while taken.contains(&candidate) {
+            candidate = format!("{sanitized}_{suffix}");
+            suffix += 1;
+        }
+        let collided = candidate != sanitized;
+        taken.insert(candidate.clone());
+        names.insert(attr_name, (candidate, collided));
+    }
+
+    names
+}
+
+/// A proto field name chosen for an attribute.
+struct FieldName {
+    /// Proto field identifier.
+    ident: String,
+
+    /// Emit `[json_name = "<attribute key>"]` on the field.
+    json_name: bool,
+
+    /// Proto field name of the emitted sibling attribute, under
+    /// [`GenerationOptions::group_siblings`].
+    sibling: Option<String>,
+}
+
+/// [`field_names`] for message emission: reports and counts collisions.
+fn assign_field_names<'a>(
+    ctx: &Context,
+    owner: &str,
+    attributes: &'a BTreeMap<String, OcsfAttribute>,
+    stats: &mut GenerationStats,
+) -> BTreeMap<&'a str, FieldName> {
+    let source = ctx.opts.field_name_source;
+    let mut names: BTreeMap<&str, FieldName> = field_names(attributes, source)
+        .into_iter()
+        .map(|(attr_name, (ident, collided))| {
+            if collided {
+                stats.warn(format!(
+                    "field '{attr_name}' in '{owner}' collides after sanitization, \
+                     renamed to '{ident}'"
+                ));
+                stats.field_name_collisions_resolved += 1;
+            }
+            let json_name = source == FieldNameSource::Caption;
+            let field = FieldName {
+                ident,
+                json_name,
+                sibling: None,
+            };
+            (attr_name, field)
+        })
+        .collect();
+    if ctx.opts.group_siblings {
+        for (a, b) in sibling_pairs(ctx, owner, attributes) {
+            let ident = |name: &str| names.get(name).map(|field| field.ident.clone());
+            let (Some(a_ident), Some(b_ident)) = (ident(a), ident(b)) else {
+                continue;
+            };
+            if let Some(field) = names.get_mut(a) {
+                field.sibling = Some(b_ident);
+            }
+            if let Some(field) = names.get_mut(b) {
+                field.sibling = Some(a_ident);
+            }
+        }
+    }
+    names
+}
+
+/// Pairs of emitted attributes linked by OCSF `sibling`, as
+/// `(attribute, sibling)`. A sibling naming an attribute the owner does not
+/// define, or one that is not emitted, is ignored.
+fn sibling_pairs<'a>(
+    ctx: &Context,
+    owner: &str,
+    attributes: &'a BTreeMap<String, OcsfAttribute>,
+) -> Vec<(&'a str, &'a str)> {
+    let emitted =
+        |name: &str, attr: &OcsfAttribute| !ctx.excludes(owner, name) && ctx.emits_field(attr);
+    attributes
+        .iter()
+        .filter(|(name, attr)| emitted(name, attr))
+        .filter_map(|(name, attr)| {
+            let (sibling, sibling_attr) = attributes.get_key_value(attr.sibling.as_deref()?)?;
+            (sibling != name && emitted(sibling, sibling_attr))
+                .then_some((name.as_str(), sibling.as_str()))
+        })
+        .collect()
+}
+
+/// Apply [`GenerationOptions::deprecated_handling`] to a deprecated
+/// attribute: skip it, or reserve its number and name. Returns `None` if
+/// the field is still emitted ([`DeprecatedHandling::Emit`]).
+fn deprecated_field_slot(ctx: &Context, stats: &mut GenerationStats) -> Option<FieldSlot> {
+    match ctx.opts.deprecated_handling {
+        DeprecatedHandling::Skip => {
+            stats.deprecated_fields_skipped += 1;
+            Some(FieldSlot::Dropped)
+        }
+        DeprecatedHandling::Reserve => {
+            stats.deprecated_fields_reserved += 1;
+            Some(FieldSlot::Reserved)
+        }
+        DeprecatedHandling::Emit => {
+            stats.deprecated_fields_emitted += 1;
+            None
+        }
+    }
+}
+
+/// Leave out an attribute the requirement or profile filter rejects,
+/// reserving its number and name under [`DeprecatedHandling::Reserve`].
+fn left_out_field_slot(
+    ctx: &Context,
+    attr: &OcsfAttribute,
+    excluded: bool,
+    stats: &mut GenerationStats,
+) -> FieldSlot {
+    if excluded {
+        stats.attributes_excluded += 1;
+    } else if !ctx.meets_requirement(attr) {
+        stats.requirement_filtered += 1;
+    } else {
+        stats.profile_filtered += 1;
+    }
+    if ctx.opts.deprecated_handling == DeprecatedHandling::Reserve {
+        FieldSlot::Reserved
+    } else {
+        FieldSlot::Dropped
+    }
+}
+
+/// What becomes of one attribute in its owner's message.
+enum FieldSlot {
+    /// Left out entirely.
+    Dropped,
+
+    /// Left out, with the number and name it would take reserved.
+    Reserved,
+
+    /// Emitted as a field of this type.
+    Emitted(FieldType),
+}
+
+/// A message's fields: what [`field_slots`] decided for each attribute,
+/// numbered by the message's allocator.
+struct MessageFields<'a> {
+    /// Proto field name of every attribute.
+    names: BTreeMap<&'a str, FieldName>,
+
+    /// Reserved and emitted attributes in field order, with their numbers.
+    fields: Vec<(&'a String, &'a OcsfAttribute, FieldSlot, u32)>,
+
+    /// The allocator that numbered them, for freed and locked numbers.
+    numbers: FieldAllocator,
+}
+
+/// Name, decide and number the fields of `owner`'s `message_name` message.
+///
+/// The message writers and [`estimate`] both go through here, so an
+/// estimate counts, and fails, as generation would.
+fn message_fields<'a>(
+    ctx: &Context,
+    owner_kind: &str,
+    owner: &str,
+    message_name: &str,
+    attributes: &'a BTreeMap<String, OcsfAttribute>,
+    enum_package: &str,
+    stats: &mut GenerationStats,
+) -> Result<MessageFields<'a>> {
+    let names = assign_field_names(ctx, owner, attributes, stats);
+    let slots = field_slots(ctx, owner_kind, owner, attributes, enum_package, stats)?;
+    let mut numbers = ctx.field_allocator(message_name);
+    let mut fields = Vec::new();
+    for (attr_name, attr, slot) in slots {
+        let ident = &names[attr_name.as_str()].ident;
+        let number = match slot {
+            FieldSlot::Dropped => continue,
+            FieldSlot::Reserved => numbers.reserve(ident)?,
+            FieldSlot::Emitted(_) => numbers.assign(ident)?,
+        };
+        fields.push((attr_name, attr, slot, number));
+    }
+    Ok(MessageFields {
+        names,
+        fields,
+        numbers,
+    })
+}
+
+/// Decide, in field order, what becomes of each attribute of `owner`'s
+/// message, counting the left-out, deprecated, defaulted and emitted fields
+/// in `stats`.
+fn field_slots<'a>(
+    ctx: &Context,
+    owner_kind: &str,
+    owner: &str,
+    attributes: &'a BTreeMap<String, OcsfAttribute>,
+    enum_package: &str,
+    stats: &mut GenerationStats,
+) -> Result<Vec<(&'a String, &'a OcsfAttribute, FieldSlot)>> {
+    let mut slots = Vec::new();
+    for (attr_name, attr) in ctx.ordered(attributes) {
+        let excluded = ctx.excludes(owner, attr_name);
+        if excluded || !ctx.selects(attr) {
+            slots.push((
+                attr_name,
+                attr,
+                left_out_field_slot(ctx, attr, excluded, stats),
+            ));
+            continue;
+        }
+        if attr.deprecated.is_some() {
+            if let Some(slot) = deprecated_field_slot(ctx, stats) {
+                slots.push((attr_name, attr, slot));
+                continue;
+            }
+        }
+
+        let field_type = resolve_field_type(ctx, attr, attr_name, owner, enum_package, stats);
+        check_strict(ctx, owner_kind, owner, attr_name, &field_type)?;
+        check_map_field(owner_kind, owner, attr_name, &field_type)?;
+        stats.fields_generated += 1;
+        slots.push((attr_name, attr, FieldSlot::Emitted(field_type)));
+    }
+    Ok(slots)
+}
+
+/// Write a message's fields and reserved numbers from its
+/// [`message_fields`].
+fn write_fields(out: &mut String, ctx: &Context, fields: &MessageFields) {
+    for (attr_name, attr, slot, number) in &fields.fields {
+        let field = &fields.names[attr_name.as_str()];
+        match slot {
+            FieldSlot::Dropped => {}
+            FieldSlot::Reserved => write_reserved(out, field, *number),
+            FieldSlot::Emitted(field_type) => {
+                write_field(out, ctx, field_type, attr_name, field, *number, attr);
+            }
+        }
+    }
+}
+
+/// Reserve the numbers and names of locked fields that are no longer
+/// generated, per [`GenerationOptions::field_numbers_lock`].
+fn write_freed_numbers(out: &mut String, numbers: &FieldAllocator) {
+    for (field, number) in numbers.freed() {
+        writeln!(out, "\treserved {number};").unwrap();
+        writeln!(out, "\treserved \"{field}\";").unwrap();
+    }
+}
+
+/// Reserve the number `field` would have taken, and its name.
+fn write_reserved(out: &mut String, field: &FieldName, number: u32) {
+    writeln!(out, "\treserved {number};").unwrap();
+    writeln!(out, "\treserved \"{}\";", field.ident).unwrap();
+}
+
+/// Open a message, marking it deprecated (with a comment carrying the OCSF
+/// deprecation message) when the class or object is deprecated.
+fn write_message_open(
+    out: &mut String,
+    message_name: &str,
+    deprecated: Option<&OcsfDeprecated>,
+    stats: &mut GenerationStats,
+) {
+    let Some(deprecated) = deprecated else {
+        writeln!(out, "message {message_name} {{").unwrap();
+        return;
+    };
+    stats.deprecated_messages += 1;
+    if deprecated.since.is_empty() {
+        writeln!(out, "// Deprecated: {}", deprecated.message).unwrap();
+    } else {
+        writeln!(
+            out,
+            "// Deprecated since {}: {}",
+            deprecated.since, deprecated.message
+        )
+        .unwrap();
+    }
+    writeln!(out, "message {message_name} {{").unwrap();
+    writeln!(out, "\toption deprecated = true;").unwrap();
+}
+
+/// Write a single message field line.
+///
+/// The trailing comment records the OCSF caption, plus the original
+/// attribute name when it differs from the proto field name. Under
+/// [`GenerationOptions::emit_todo_markers`], a defaulted type gets a
+/// `TODO` comment on the line above.
+fn write_field(
+    out: &mut String,
+    ctx: &Context,
+    field_type: &FieldType,
+    attr_name: &str,
+    field: &FieldName,
+    field_num: u32,
+    attr: &OcsfAttribute,
+) {
+    if ctx.opts.emit_descriptions {
+        for line in wrap_comment(&attr.description) {
+            writeln!(out, "\t{line}").unwrap();
+        }
+    }
+    if let Some(reason) = field_type
+        .defaulted
+        .as_ref()
+        .filter(|_| ctx.opts.emit_todo_markers)
+    {
+        writeln!(
+            out,
+            "\t// TODO(ocsf-proto-gen): {reason} defaulted to string"
+        )
+        .unwrap();
+    }
+    let proto_type = &field_type.proto_type;
+    let repeated_kw = if field_type.repeated { "repeated " } else { "" };
+    let field_name = &field.ident;
+    write!(
+        out,
+        "\t{repeated_kw}{proto_type} {field_name} = {field_num}"
+    )
+    .unwrap();
+    let mut options = Vec::new();
+    if attr.deprecated.is_some() {
+        options.push("deprecated = true".to_string());
+    }
+    if field.json_name {
+        options.push(format!("json_name = \"{attr_name}\""));
+    }
+    if let Some(rule) = pgv_rule(ctx, attr) {
+        options.push(rule);
+    }
+    if let Some(packed) = ctx.opts.packed_repeated {
+        if field_type.repeated && field_type.packable {
+            options.push(format!("packed = {packed}"));
+        }
+    }
+    if !options.is_empty() {
+        write!(out, " [{}]", options.join(", ")).unwrap();
+    }
+    write!(out, "; // Caption: {};", attr.caption).unwrap();
+    if field_name != attr_name {
+        write!(out, " OCSF name: {attr_name};").unwrap();
+    }
+    if let Some(sibling) = &field.sibling {
+        write!(out, " Sibling: {sibling};").unwrap();
+    }
+    if let Some(enum_vals) = attr
+        .enum_values
+        .as_ref()
+        .filter(|vals| !ctx.generates_enums() && is_integer_enum(vals))
+    {
+        let values: Vec<String> = enum_entries(enum_vals, ctx.opts.naming.as_ref())
+            .into_iter()
+            .map(|(value, _, val)| format!("{value}={}", val.caption))
+            .collect();
+        write!(out, " Values: {};", values.join(", ")).unwrap();
+    }
+    if let Some(deprecated) = &attr.deprecated {
+        if deprecated.since.is_empty() {
+            write!(out, " Deprecated: {};", deprecated.message).unwrap();
+        } else {
+            write!(
+                out,
+                " Deprecated since {}: {};",
+                deprecated.since, deprecated.message
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out).unwrap();
+}
+
+/// Write `description` as the comment block above a message, under
+/// [`GenerationOptions::emit_descriptions`].
+fn write_message_description(out: &mut String, ctx: &Context, description: &str) {
+    if ctx.opts.emit_descriptions {
+        for line in wrap_comment(description) {
+            writeln!(out, "{line}").unwrap();
+        }
+    }
+}
+
+/// Maximum width of a description comment line, including `// ` but not
+/// the indentation.
+const COMMENT_WIDTH: usize = 100;
+
+/// Format `text` as `//` comment lines of at most [`COMMENT_WIDTH`] columns,
+/// keeping its line breaks. Words longer than a line are not split.
+fn wrap_comment(text: &str) -> Vec<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::from("//");
+        for word in paragraph.split_whitespace() {
+            if line.len() > 2 && line.len() + 1 + word.len() > COMMENT_WIDTH {
+                lines.push(std::mem::replace(&mut line, String::from("//")));
+            }
+            line.push(' ');
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// The protoc-gen-validate rule for `attr` under
+/// [`GenerationOptions::emit_pgv_rules`], if any. Attributes that do not
+/// become fields get none, so they never pull in the import.
+fn pgv_rule(ctx: &Context, attr: &OcsfAttribute) -> Option<String> {
+    if !ctx.opts.emit_pgv_rules || !ctx.emits_field(attr) {
+        return None;
+    }
+    if attr.type_name == "port_t" && type_override(ctx, attr).is_none() {
+        let rule = if attr.is_array {
+            "repeated.items.int32"
+        } else {
+            "int32"
+        };
+        return Some(format!("(validate.rules).{rule} = {{gte: 0, lte: 65535}}"));
+    }
+    // Only fields emitted as a message reference can carry `message.required`.
+    let required_message = attr.type_name == "object_t"
+        && !attr.is_array
+        && attr.requirement.as_deref() == Some("required")
+        && attr
+            .object_type
+            .as_deref()
+            .and_then(|obj_type| lookup_object(ctx.schema, obj_type))
+            .is_some_and(|obj| ctx.gets_message(obj));
+    required_message.then(|| "(validate.rules).message.required = true".to_string())
+}
+
+/// Import `validate/validate.proto` if any of `attributes` gets a
+/// [`pgv_rule`].
+fn write_pgv_import<'b>(
+    out: &mut String,
+    ctx: &Context,
+    mut attributes: impl Iterator<Item = &'b OcsfAttribute>,
+) {
+    if attributes.any(|attr| pgv_rule(ctx, attr).is_some()) {
+        writeln!(out, "import \"validate/validate.proto\";").unwrap();
+    }
+}
+
+// ── Object graph export ────────────────────────────────────────────────
+
+/// Render the object references of `class_names` as a Graphviz DOT
+/// digraph, to see why generation pulls in each object.
+///
+/// Walks the same classes and objects as [`crate::schema::visit`], so the
+/// graph shows every schema reference before any [`GenerationOptions`]
+/// apply, including objects generation leaves out (see
+/// [`resolved_objects`]).
+///
+/// Classes are box nodes and objects ellipses, labeled with their sanitized
+/// names. Each referencing attribute is an edge labeled with the attribute
+/// name; array references are dashed. Returns [`Error::ClassNotFound`] for
+/// an unknown class.
+pub fn object_graph_dot(schema: &OcsfSchema, class_names: &[String]) -> Result<String> {
+    let mut graph = DotGraph::default();
+    crate::schema::visit(schema, class_names, &mut graph)?;
+
+    let mut out = String::from("digraph ocsf {\n\trankdir=LR;\n");
+    for node in &graph.nodes {
+        writeln!(out, "\t{node}").unwrap();
+    }
+    for edge in &graph.edges {
+        writeln!(out, "\t{edge}").unwrap();
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+/// [`SchemaVisitor`] collecting the node and edge statements of
+/// [`object_graph_dot`].
+#[derive(Default)]
+struct DotGraph {
+    /// Node ID of the class or object whose attributes are being visited.
+    owner: String,
+    nodes: BTreeSet<String>,
+    edges: BTreeSet<String>,
+}
+
+impl SchemaVisitor for DotGraph {
+    fn visit_class(&mut self, class: &OcsfClass) {
+        self.owner = format!("\"class/{}\"", class.name);
+        self.nodes.insert(format!(
+            "{} [label=\"{}\", shape=box];",
+            self.owner, class.name
+        ));
+    }
+
+    fn visit_object(&mut self, name: &str, _object: &OcsfObject) {
+        self.owner = format!("\"object/{name}\"");
+        self.nodes
+            .insert(format!("{} [label=\"{name}\"];", self.owner));
+    }
+
+    fn visit_attribute(&mut self, _owner: &str, name: &str, attribute: &OcsfAttribute) {
+        let Some(object_type) = &attribute.object_type else {
+            return;
+        };
+        let style = if attribute.is_array {
+            ", style=dashed"
+        } else {
+            ""
+        };
+        self.edges.insert(format!(
+            "{} -> \"object/{}\" [label=\"{name}\"{style}];",
+            self.owner,
+            sanitize_object_name(object_type)
+        ));
+    }
+}
+
+/// Find the reference cycles among `needed_objects` (e.g., from
+/// [`crate::schema::resolve_object_graph`]), which become recursive
+/// messages: `process.parent_process` makes `["process", "process"]`.
+///
+/// Each cycle starts and ends at the same object. One cycle is reported per
+/// back edge of a depth-first walk in name order, so every object on a cycle
+/// appears in at least one, though not every cycle through it is listed.
+/// References to objects outside `needed_objects` are ignored.
+pub fn detect_cycles(schema: &OcsfSchema, needed_objects: &BTreeSet<String>) -> Vec<Vec<String>> {
+    let mut graph: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for name in needed_objects {
+        let references: BTreeSet<String> = lookup_object(schema, name)
+            .into_iter()
+            .flat_map(|obj| obj.attributes.values())
+            .filter_map(|attr| attr.object_type.as_deref())
+            .map(sanitize_object_name)
+            .filter(|target| needed_objects.contains(target))
+            .collect();
+        graph.insert(name.clone(), references.into_iter().collect());
+    }
+    find_cycles(&graph)
+}
+
+// ── Import graph check ─────────────────────────────────────────────────
+
+/// Verify that the `import` statements of every `.proto` file under
+/// `output_dir` form a directed acyclic graph, as `protoc` requires.
+///
+/// Import paths are resolved relative to `output_dir` (the proto include
+/// root). Imports of files outside the tree, such as
+/// `google/protobuf/timestamp.proto`, are ignored. Returns
+/// [`Error::Codegen`] listing the first cycle found, e.g.
+/// `import cycle: a.proto -> b.proto -> a.proto`.
+pub fn check_import_graph(output_dir: &Path) -> Result<()> {
+    let mut graph: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in proto_files(output_dir)? {
+        let content = std::fs::read_to_string(&path).map_err(|e| Error::Read {
+            path: path.clone(),
+            source: e,
+        })?;
+        let relative = path.strip_prefix(output_dir).unwrap_or(&path);
+        let key = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        graph.insert(key, parse_imports(&content));
+    }
+
+    match find_cycles(&graph).into_iter().next() {
+        Some(cycle) => Err(Error::Codegen(format!(
+            "import cycle: {}",
+            cycle.join(" -> ")
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Extract the quoted paths of `import`, `import public`, and `import weak`
+/// statements.
+fn parse_imports(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("import")?;
+            if !rest.starts_with([' ', '\t', '"']) {
+                return None;
+            }
+            let rest = rest.trim_start();
+            let rest = rest
+                .strip_prefix("public")
+                .or_else(|| rest.strip_prefix("weak"))
+                .unwrap_or(rest)
+                .trim_start();
+            let rest = rest.strip_prefix('"')?;
+            let end = rest.find('"')?;
+            Some(rest[..end].to_string())
+        })
+        .collect()
+}
+
+/// Find cycles in a directed graph using an explicit-stack DFS, one per
+/// back edge, in the order the walk meets them.
+///
+/// Each cycle is a path that starts and ends at the same node. Edges to
+/// nodes not present in the graph are ignored.
+fn find_cycles(graph: &BTreeMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    enum Visit {
+        InProgress,
+        Done,
+    }
+
+    let mut cycles = Vec::new();
+    let mut state: BTreeMap<&str, Visit> = BTreeMap::new();
+    for start in graph.keys() {
+        if state.contains_key(start.as_str()) {
+            continue;
+        }
+        state.insert(start, Visit::InProgress);
+        // Each frame is (node, index of the next edge to follow).
+        let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+
+        while let Some(top) = stack.last_mut() {
+            let (node, next) = (top.0, top.1);
+            let Some(child) = graph[node].get(next) else {
+                state.insert(node, Visit::Done);
+                stack.pop();
+                continue;
+            };
+            top.1 += 1;
+            if !graph.contains_key(child) {
+                continue;
+            }
+            match state.get(child.as_str()) {
+                Some(Visit::InProgress) => {
+                    if let Some(from) = stack.iter().position(|(n, _)| *n == child) {
+                        let mut cycle: Vec<String> =
+                            stack[from..].iter().map(|(n, _)| n.to_string()).collect();
+                        cycle.push(child.clone());
+                        cycles.push(cycle);
+                    }
+                }
+                Some(Visit::Done) => {}
+                None => {
+                    state.insert(child, Visit::InProgress);
+                    stack.push((child, 0));
+                }
+            }
+        }
+    }
+    cycles
+}
+
+// ── prost-build inputs ─────────────────────────────────────────────────
+
+/// The arguments `prost_build::Config::compile_protos` takes for generated
+/// output, from [`prost_build_inputs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProstBuildInputs {
+    /// Every `.proto` of the version, sorted.
+    pub protos: Vec<PathBuf>,
+
+    /// Include roots the protos' imports resolve against: the output
+    /// directory.
+    pub includes: Vec<PathBuf>,
+}
+
+/// Collect the protos generated into `output_dir` under `version_root`, the
+/// version directory relative to it (the [`version_root`] of the run, e.g.,
+/// `ocsf/v1_7_0`), for a `build.rs`:
+///
+/// ```no_run
+/// # fn main() -> ocsf_proto_gen::error::Result<()> {
+/// let inputs =
+///     ocsf_proto_gen::codegen::prost_build_inputs("proto".as_ref(), "ocsf/v1_7_0".as_ref())?;
+/// // prost_build::compile_protos(&inputs.protos, &inputs.includes)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Every file under `version_root`, extension packages included, is listed;
+/// `ocsf_options.proto` in the prefix directory is reached through its
+/// imports. Returns [`Error::Read`] if the directory cannot be read and
+/// [`Error::Codegen`] if it has no protos.
+pub fn prost_build_inputs(output_dir: &Path, version_root: &Path) -> Result<ProstBuildInputs> {
+    let version_dir = output_dir.join(version_root);
+    let protos = proto_files(&version_dir)?;
+    if protos.is_empty() {
+        return Err(Error::Codegen(format!(
+            "no .proto files under {}",
+            version_dir.display()
+        )));
+    }
+    Ok(ProstBuildInputs {
+        protos,
+        includes: vec![output_dir.to_path_buf()],
+    })
+}
+
+/// List every `.proto` file under `dir`, sorted.
+fn proto_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current).map_err(|e| Error::Read {
+            path: current.clone(),
+            source: e,
+        })?;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| Error::Read {
+                    path: current.clone(),
+                    source: e,
+                })?
+                .path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "proto") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// ── Shared helpers ─────────────────────────────────────────────────────
 
 /// Check if an enum has integer keys (valid for proto enum) vs string keys.
 ///
 /// OCSF uses both formats:
 /// - Integer-keyed: `{"0": "Unknown", "1": "Logon"}` → becomes proto `enum`
 /// - String-keyed: `{"GET": "Get", "POST": "Post"}` → stays as `string` field
-fn is_integer_enum(enum_values: &BTreeMap<String, crate::schema::OcsfEnumValue>) -> bool {
+pub fn is_integer_enum(enum_values: &BTreeMap<String, OcsfEnumValue>) -> bool {
     enum_values.keys().all(|k| k.parse::<i32>().is_ok())
 }
 
+/// The integer-keyed values of an enum as `(value, variant name, value)`,
+/// sorted by value, with variant names as [`write_enum_definition`] emits
+/// them.
+pub(crate) fn enum_entries<'a>(
+    enum_vals: &'a BTreeMap<String, OcsfEnumValue>,
+    naming: &dyn NamingStrategy,
+) -> Vec<(i32, String, &'a OcsfEnumValue)> {
+    let mut entries: Vec<(i32, String, &OcsfEnumValue)> = Vec::new();
+    for (key_str, val) in enum_vals {
+        if let Ok(key) = key_str.parse::<i32>() {
+            let variant_name = variant_name_or_value(naming.enum_variant_name(&val.caption), key);
+            entries.push((key, variant_name, val));
+        }
+    }
+    entries.sort_by_key(|(k, _, _)| *k);
+    entries
+}
+
 /// Write a proto enum definition to the output string.
 fn write_enum_definition(
     out: &mut String,
     enum_name: &str,
-    enum_vals: &BTreeMap<String, crate::schema::OcsfEnumValue>,
+    enum_vals: &BTreeMap<String, OcsfEnumValue>,
+    opts: &GenerationOptions,
+    stats: &mut GenerationStats,
 ) {
+    count_enum(enum_vals, stats);
     // Collect and sort by integer value.
-    let mut entries: Vec<(i32, String)> = Vec::new();
-    for (key_str, val) in enum_vals {
-        if let Ok(key) = key_str.parse::<i32>() {
-            let variant_name = to_enum_variant_name(&val.caption);
-            entries.push((key, variant_name));
-        }
-    }
-    entries.sort_by_key(|(k, _)| *k);
+    let mut entries = enum_entries(enum_vals, opts.naming.as_ref());
+    // Keys like "1" and "01" with the same caption would emit the same
+    // symbol twice; keep only the first of each.
+    let mut seen = BTreeSet::new();
+    entries.retain(|(key, variant_name, _)| seen.insert((*key, variant_name.clone())));
 
     writeln!(out).unwrap();
     writeln!(out, "enum {enum_name} {{").unwrap();
 
+    // Distinct keys can parse to the same value (e.g., "1" and "01");
+    // proto3 rejects repeated values unless aliases are allowed.
+    if entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        writeln!(out, "\toption allow_alias = true;").unwrap();
+    }
+
     // Proto3 requires the first enum value to be 0.
-    // If OCSF doesn't define a 0 value, add a synthetic UNSPECIFIED.
-    if !entries.iter().any(|(k, _)| *k == 0) {
-        writeln!(out, "\t{enum_name}_UNSPECIFIED = 0;").unwrap();
+    // If OCSF doesn't define a 0 value, add a synthetic one: UNSPECIFIED,
+    // unless a caption is configured and its variant name is free.
+    if !entries.iter().any(|(k, _, _)| *k == 0) {
+        let caption = opts
+            .synthetic_zero_captions
+            .get(enum_name)
+            .or_else(|| opts.synthetic_zero_captions.get("*"));
+        let labeled = caption
+            .map(|caption| (caption, opts.naming.enum_variant_name(caption)))
+            .filter(|(_, variant)| {
+                !variant.is_empty() && !entries.iter().any(|(_, v, _)| v == variant)
+            });
+        match labeled {
+            Some((caption, variant)) => writeln!(
+                out,
+                "\t{enum_name}_{variant} = 0; // {caption} (synthetic zero value)"
+            )
+            .unwrap(),
+            None => writeln!(
+                out,
+                "\t{enum_name}_UNSPECIFIED = 0; // synthetic: OCSF has no 0 value"
+            )
+            .unwrap(),
+        }
     }
 
-    for (key, variant_name) in &entries {
+    for (key, variant_name, val) in &entries {
+        if opts.emit_enum_descriptions {
+            for line in wrap_comment(val.description.as_deref().unwrap_or_default()) {
+                writeln!(out, "\t{line}").unwrap();
+            }
+        }
         writeln!(out, "\t{enum_name}_{variant_name} = {key};").unwrap();
     }
 
+    if opts.reserve_enum_gaps {
+        // The zero value is always emitted, synthetic or not.
+        let values = entries.iter().map(|(k, _, _)| *k).chain([0]);
+        for (lo, hi) in enum_gaps(values) {
+            if lo == hi {
+                writeln!(out, "\treserved {lo};").unwrap();
+            } else {
+                writeln!(out, "\treserved {lo} to {hi};").unwrap();
+            }
+        }
+    }
+
     writeln!(out, "}}").unwrap();
 }
 
-/// Convert an OCSF version string to a proto package slug.
+/// The inclusive ranges of values missing between the lowest and highest of
+/// `values`, in order, for [`GenerationOptions::reserve_enum_gaps`].
+/// Repeated values are merged, so aliases leave no gap.
+fn enum_gaps(values: impl IntoIterator<Item = i32>) -> Vec<(i32, i32)> {
+    let values: BTreeSet<i32> = values.into_iter().collect();
+    let values: Vec<i32> = values.into_iter().collect();
+    values
+        .windows(2)
+        .filter(|pair| pair[1] > pair[0] + 1)
+        .map(|pair| (pair[0] + 1, pair[1] - 1))
+        .collect()
+}
+
+/// Count an enum [`write_enum_definition`] emits, and its synthetic zero
+/// value if OCSF defines no 0.
+fn count_enum(enum_vals: &BTreeMap<String, OcsfEnumValue>, stats: &mut GenerationStats) {
+    stats.enums_generated += 1;
+    let has_zero = enum_vals
+        .keys()
+        .any(|key| key.parse::<i32>().is_ok_and(|value| value == 0));
+    if !has_zero {
+        stats.synthetic_zero_values += 1;
+    }
+}
+
+/// Under [`GenerationOptions::reject_empty_messages`], fail if a message
+/// finished with no fields.
+fn check_not_empty(ctx: &Context, message: &str, ocsf_name: &str, empty: bool) -> Result<()> {
+    if ctx.opts.reject_empty_messages && empty {
+        return Err(Error::Codegen(format!(
+            "message {message} ('{ocsf_name}') has no fields; every attribute was \
+             deprecated or filtered out"
+        )));
+    }
+    Ok(())
+}
+
+/// Under [`GenerationOptions::strict`], fail if `field_type` fell back to
+/// `string`.
+fn check_strict(
+    ctx: &Context,
+    owner_kind: &str,
+    owner: &str,
+    attr_name: &str,
+    field_type: &FieldType,
+) -> Result<()> {
+    match &field_type.defaulted {
+        Some(reason) if ctx.opts.strict => Err(Error::Codegen(format!(
+            "{owner_kind} '{owner}', attribute '{attr_name}': {reason} (strict mode)"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Fail if an overridden `map<...>` type landed on an array attribute:
+/// proto map fields cannot be `repeated`.
+fn check_map_field(
+    owner_kind: &str,
+    owner: &str,
+    attr_name: &str,
+    field_type: &FieldType,
+) -> Result<()> {
+    if field_type.repeated && field_type.proto_type.starts_with("map<") {
+        return Err(Error::Codegen(format!(
+            "{owner_kind} '{owner}', attribute '{attr_name}': map type '{}' cannot be \
+             repeated, but the attribute is an array",
+            field_type.proto_type
+        )));
+    }
+    Ok(())
+}
+
+/// Write the provenance comment, `syntax` and `package` statements that
+/// open every file, followed by the file-level deprecation option when
+/// requested.
 ///
-/// `"1.7.0"` → `"v1_7_0"`, `"1.8.0-dev"` → `"v1_8_0_dev"`.
-fn version_to_slug(version: &str) -> String {
-    format!("v{}", version.replace(['.', '-'], "_"))
+/// The provenance names the tool and schema versions but no timestamp, so
+/// regenerating from the same inputs stays byte-identical.
+fn write_file_header(out: &mut String, ctx: &Context, package: &str) {
+    writeln!(
+        out,
+        "// Generated by ocsf-proto-gen {} from OCSF {}. DO NOT EDIT.",
+        env!("CARGO_PKG_VERSION"),
+        ctx.schema.version
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "syntax = \"proto3\";").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "package {package};").unwrap();
+    if ctx.opts.mark_file_deprecated {
+        writeln!(out).unwrap();
+        writeln!(out, "option deprecated = true;").unwrap();
+    }
+}
+
+/// Match `name` against a glob `pattern` where `*` matches any run of
+/// characters and `?` matches exactly one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` seen and the name index it was tried at.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, tried)) = backtrack {
+            // Let the last `*` absorb one more character and retry.
+            p = star + 1;
+            n = tried + 1;
+            backtrack = Some((star, n));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 /// Write content to a file, creating parent directories as needed.
@@ -0,0 +1,511 @@
+//! Differences between two OCSF schema versions.
+//!
+//! When upgrading OCSF, the most common question is which enum values
+//! appeared, disappeared, or changed caption. [`diff_enums`] answers it for
+//! every integer-keyed enum in the two schemas, keyed by the proto enum name
+//! the generator would emit (e.g., `AUTHENTICATION_ACTIVITY_ID`).
+//!
+//! [`diff_schemas`] gives the broader structural picture before
+//! regenerating: classes and objects added or removed, and per shared class
+//! or object the attributes added, removed, or retyped and the enum values
+//! that changed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::codegen::is_integer_enum;
+use crate::error::{Error, Result};
+use crate::schema::{OcsfAttribute, OcsfEnumValue, OcsfSchema};
+use crate::type_map::{DefaultNaming, NamingStrategy, sanitize_object_name};
+
+/// Per-enum variant changes between two schema versions, keyed by enum name.
+pub type EnumDiff = BTreeMap<String, EnumChange>;
+
+/// Variant changes for a single enum.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct EnumChange {
+    /// Values present only in the new schema, with their captions.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub added: BTreeMap<i32, String>,
+
+    /// Values present only in the old schema, with their captions.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub removed: BTreeMap<i32, String>,
+
+    /// Values present in both schemas whose caption changed.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub renamed: BTreeMap<i32, CaptionChange>,
+}
+
+/// A caption change for an enum value present in both versions.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct CaptionChange {
+    /// Caption in the old schema.
+    pub from: String,
+
+    /// Caption in the new schema.
+    pub to: String,
+}
+
+/// Structural differences between two schema versions.
+///
+/// Displays as one line per change: `+`/`-` for classes and objects added
+/// or removed, then each changed class and object (`~`) with its attribute
+/// and enum value changes indented below it.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SchemaDiff {
+    /// Classes present only in the new schema.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub added_classes: BTreeSet<String>,
+
+    /// Classes present only in the old schema.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub removed_classes: BTreeSet<String>,
+
+    /// Objects present only in the new schema.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub added_objects: BTreeSet<String>,
+
+    /// Objects present only in the old schema.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub removed_objects: BTreeSet<String>,
+
+    /// Attribute changes of classes present in both schemas, by class name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub changed_classes: BTreeMap<String, AttributeChanges>,
+
+    /// Attribute changes of objects present in both schemas, by object name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub changed_objects: BTreeMap<String, AttributeChanges>,
+}
+
+/// Attribute changes of a class or object present in both schema versions.
+///
+/// Types are written as the OCSF type name, or the object name for object
+/// references, with `[]` appended for arrays (e.g., `string_t`, `user[]`).
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct AttributeChanges {
+    /// Attributes present only in the new schema, with their types.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub added: BTreeMap<String, String>,
+
+    /// Attributes present only in the old schema, with their types.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub removed: BTreeMap<String, String>,
+
+    /// Attributes present in both schemas whose type changed.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub retyped: BTreeMap<String, TypeChange>,
+
+    /// Integer enum value changes of attributes present in both schemas.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub enums: BTreeMap<String, EnumChange>,
+}
+
+/// A type change for an attribute present in both versions.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct TypeChange {
+    /// Type in the old schema.
+    pub from: String,
+
+    /// Type in the new schema.
+    pub to: String,
+}
+
+impl SchemaDiff {
+    /// Whether the two schemas have no structural differences.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            ("+ class", &self.added_classes),
+            ("- class", &self.removed_classes),
+            ("+ object", &self.added_objects),
+            ("- object", &self.removed_objects),
+        ];
+        for (marker, names) in names {
+            for name in names {
+                writeln!(f, "{marker} {name}")?;
+            }
+        }
+        let changed = [
+            ("class", &self.changed_classes),
+            ("object", &self.changed_objects),
+        ];
+        for (kind, owners) in changed {
+            for (name, changes) in owners {
+                writeln!(f, "~ {kind} {name}")?;
+                write!(f, "{changes}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AttributeChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (attr, type_name) in &self.added {
+            writeln!(f, "    + {attr}: {type_name}")?;
+        }
+        for (attr, type_name) in &self.removed {
+            writeln!(f, "    - {attr}: {type_name}")?;
+        }
+        for (attr, change) in &self.retyped {
+            writeln!(f, "    ~ {attr}: {} -> {}", change.from, change.to)?;
+        }
+        for (attr, change) in &self.enums {
+            for (value, caption) in &change.added {
+                writeln!(f, "    + {attr} = {value} ({caption})")?;
+            }
+            for (value, caption) in &change.removed {
+                writeln!(f, "    - {attr} = {value} ({caption})")?;
+            }
+            for (value, caption) in &change.renamed {
+                writeln!(
+                    f,
+                    "    ~ {attr} = {value} ({} -> {})",
+                    caption.from, caption.to
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare the classes and objects of two schemas, with the attributes and
+/// integer enum values of those present in both.
+///
+/// Classes and objects are matched by their schema key.
+pub fn diff_schemas(old: &OcsfSchema, new: &OcsfSchema) -> SchemaDiff {
+    let (added_classes, removed_classes) = added_and_removed(&old.classes, &new.classes);
+    let (added_objects, removed_objects) = added_and_removed(&old.objects, &new.objects);
+    let changed_classes = old
+        .classes
+        .iter()
+        .filter_map(|(name, cls)| {
+            let new_cls = new.classes.get(name)?;
+            Some((name, diff_attributes(&cls.attributes, &new_cls.attributes)))
+        })
+        .filter(|(_, changes)| *changes != AttributeChanges::default())
+        .map(|(name, changes)| (name.clone(), changes))
+        .collect();
+    let changed_objects = old
+        .objects
+        .iter()
+        .filter_map(|(name, obj)| {
+            let new_obj = new.objects.get(name)?;
+            Some((name, diff_attributes(&obj.attributes, &new_obj.attributes)))
+        })
+        .filter(|(_, changes)| *changes != AttributeChanges::default())
+        .map(|(name, changes)| (name.clone(), changes))
+        .collect();
+    SchemaDiff {
+        added_classes,
+        removed_classes,
+        added_objects,
+        removed_objects,
+        changed_classes,
+        changed_objects,
+    }
+}
+
+/// Serialize a [`SchemaDiff`] as a pretty-printed JSON document.
+pub fn schema_diff_json(diff: &SchemaDiff) -> Result<String> {
+    serde_json::to_string_pretty(diff)
+        .map_err(|e| Error::Codegen(format!("serializing schema diff: {e}")))
+}
+
+/// The keys only in `new` and the keys only in `old`.
+fn added_and_removed<V>(
+    old: &BTreeMap<String, V>,
+    new: &BTreeMap<String, V>,
+) -> (BTreeSet<String>, BTreeSet<String>) {
+    let only_in = |a: &BTreeMap<String, V>, b: &BTreeMap<String, V>| {
+        a.keys()
+            .filter(|key| !b.contains_key(*key))
+            .cloned()
+            .collect()
+    };
+    (only_in(new, old), only_in(old, new))
+}
+
+fn diff_attributes(
+    old: &BTreeMap<String, OcsfAttribute>,
+    new: &BTreeMap<String, OcsfAttribute>,
+) -> AttributeChanges {
+    let mut changes = AttributeChanges::default();
+    for (name, attr) in new {
+        if !old.contains_key(name) {
+            changes.added.insert(name.clone(), attribute_type(attr));
+        }
+    }
+    for (name, old_attr) in old {
+        let Some(new_attr) = new.get(name) else {
+            changes
+                .removed
+                .insert(name.clone(), attribute_type(old_attr));
+            continue;
+        };
+        let (from, to) = (attribute_type(old_attr), attribute_type(new_attr));
+        if from != to {
+            changes
+                .retyped
+                .insert(name.clone(), TypeChange { from, to });
+        }
+        let enum_change = diff_enum_values(&integer_enum(old_attr), &integer_enum(new_attr));
+        if enum_change != EnumChange::default() {
+            changes.enums.insert(name.clone(), enum_change);
+        }
+    }
+    changes
+}
+
+/// The type of `attr` as [`AttributeChanges`] writes it.
+fn attribute_type(attr: &OcsfAttribute) -> String {
+    let base = attr.object_type.as_deref().unwrap_or(&attr.type_name);
+    if attr.is_array {
+        format!("{base}[]")
+    } else {
+        base.to_string()
+    }
+}
+
+/// `value → caption` of `attr`'s integer-keyed enum, empty if it has none.
+fn integer_enum(attr: &OcsfAttribute) -> BTreeMap<i32, String> {
+    attr.enum_values
+        .as_ref()
+        .filter(|enum_vals| is_integer_enum(enum_vals))
+        .map(enum_captions)
+        .unwrap_or_default()
+}
+
+fn enum_captions(enum_vals: &BTreeMap<String, OcsfEnumValue>) -> BTreeMap<i32, String> {
+    enum_vals
+        .iter()
+        .filter_map(|(k, v)| Some((k.parse::<i32>().ok()?, v.caption.clone())))
+        .collect()
+}
+
+/// Compare the integer-keyed enums of every class and object in two schemas.
+///
+/// Enums that exist in only one schema are reported with all of their values
+/// as added or removed. Enums with no changes are omitted.
+pub fn diff_enums(old: &OcsfSchema, new: &OcsfSchema) -> EnumDiff {
+    let old_enums = collect_enums(old);
+    let new_enums = collect_enums(new);
+    let empty = BTreeMap::new();
+
+    let mut diff = EnumDiff::new();
+    let names: BTreeSet<&String> = old_enums.keys().chain(new_enums.keys()).collect();
+    for name in names {
+        let before = old_enums.get(name).unwrap_or(&empty);
+        let after = new_enums.get(name).unwrap_or(&empty);
+        let change = diff_enum_values(before, after);
+        if change != EnumChange::default() {
+            diff.insert(name.clone(), change);
+        }
+    }
+    diff
+}
+
+/// The values added, removed, and renamed from `before` to `after`.
+fn diff_enum_values(before: &BTreeMap<i32, String>, after: &BTreeMap<i32, String>) -> EnumChange {
+    let mut change = EnumChange::default();
+    for (value, caption) in after {
+        match before.get(value) {
+            None => {
+                change.added.insert(*value, caption.clone());
+            }
+            Some(old_caption) if old_caption != caption => {
+                change.renamed.insert(
+                    *value,
+                    CaptionChange {
+                        from: old_caption.clone(),
+                        to: caption.clone(),
+                    },
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    for (value, caption) in before {
+        if !after.contains_key(value) {
+            change.removed.insert(*value, caption.clone());
+        }
+    }
+    change
+}
+
+/// Serialize an [`EnumDiff`] as the pretty-printed `enum-diff.json` document.
+pub fn enum_diff_json(diff: &EnumDiff) -> Result<String> {
+    serde_json::to_string_pretty(diff)
+        .map_err(|e| Error::Codegen(format!("serializing enum diff: {e}")))
+}
+
+/// Collect `enum name → (value → caption)` for every integer-keyed enum.
+fn collect_enums(schema: &OcsfSchema) -> BTreeMap<String, BTreeMap<i32, String>> {
+    let mut enums = BTreeMap::new();
+    for cls in schema.classes.values() {
+        collect_owner_enums(&cls.name, &cls.attributes, &mut enums);
+    }
+    for (name, obj) in &schema.objects {
+        collect_owner_enums(name, &obj.attributes, &mut enums);
+    }
+    enums
+}
+
+fn collect_owner_enums(
+    owner: &str,
+    attributes: &BTreeMap<String, OcsfAttribute>,
+    enums: &mut BTreeMap<String, BTreeMap<i32, String>>,
+) {
+    let owner = sanitize_object_name(owner);
+    for (attr_name, attr) in attributes {
+        let Some(enum_vals) = &attr.enum_values else {
+            continue;
+        };
+        if !is_integer_enum(enum_vals) {
+            continue;
+        }
+        enums.insert(
+            DefaultNaming.enum_name(&owner, attr_name),
+            enum_captions(enum_vals),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema_with_activity(values: &str) -> OcsfSchema {
+        let json = format!(
+            r#"{{
+                "version": "1.7.0",
+                "classes": {{
+                    "authentication": {{
+                        "name": "authentication",
+                        "uid": 3002,
+                        "caption": "Authentication",
+                        "attributes": {{
+                            "activity_id": {{"type": "integer_t", "enum": {{ {values} }} }},
+                            "message": {{"type": "string_t"}}
+                        }}
+                    }}
+                }},
+                "objects": {{}}
+            }}"#
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn reports_added_removed_and_renamed_variants() {
+        let old = schema_with_activity(
+            r#""0": {"caption": "Unknown"}, "1": {"caption": "Logon"}, "3": {"caption": "Ticket"}"#,
+        );
+        let new = schema_with_activity(
+            r#""0": {"caption": "Unknown"}, "1": {"caption": "Log On"}, "2": {"caption": "Logoff"}"#,
+        );
+
+        let diff = diff_enums(&old, &new);
+        assert_eq!(diff.len(), 1);
+        let change = &diff["AUTHENTICATION_ACTIVITY_ID"];
+        assert_eq!(change.added, BTreeMap::from([(2, "Logoff".to_string())]));
+        assert_eq!(change.removed, BTreeMap::from([(3, "Ticket".to_string())]));
+        assert_eq!(change.renamed[&1].from, "Logon");
+        assert_eq!(change.renamed[&1].to, "Log On");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&enum_diff_json(&diff).unwrap()).unwrap();
+        assert_eq!(json["AUTHENTICATION_ACTIVITY_ID"]["added"]["2"], "Logoff");
+    }
+
+    #[test]
+    fn schema_diff_reports_structural_changes() {
+        let old: OcsfSchema = serde_json::from_str(
+            r#"{
+                "version": "1.6.0",
+                "classes": {
+                    "authentication": {"name": "authentication", "caption": "authentication", "uid": 3002, "attributes": {
+                        "activity_id": {"type": "integer_t", "enum": {"1": {"caption": "Logon"}, "3": {"caption": "Ticket"}}},
+                        "auth_protocol": {"type": "string_t"},
+                        "time": {"type": "integer_t"},
+                        "user": {"type": "object_t", "object_type": "user"}
+                    }},
+                    "legacy_activity": {"name": "legacy_activity", "caption": "legacy_activity", "uid": 9, "attributes": {}}
+                },
+                "objects": {"user": {"name": "user", "caption": "user", "attributes": {}}}
+            }"#,
+        )
+        .unwrap();
+        let new: OcsfSchema = serde_json::from_str(
+            r#"{
+                "version": "1.7.0",
+                "classes": {
+                    "authentication": {"name": "authentication", "caption": "authentication", "uid": 3002, "attributes": {
+                        "activity_id": {"type": "integer_t", "enum": {"1": {"caption": "Logon"}, "2": {"caption": "Logoff"}}},
+                        "time": {"type": "timestamp_t"},
+                        "user": {"type": "object_t", "object_type": "user", "is_array": true},
+                        "session": {"type": "object_t", "object_type": "session"}
+                    }}
+                },
+                "objects": {
+                    "user": {"name": "user", "caption": "user", "attributes": {}},
+                    "session": {"name": "session", "caption": "session", "attributes": {}}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let diff = diff_schemas(&old, &new);
+        assert_eq!(
+            diff.removed_classes,
+            BTreeSet::from(["legacy_activity".to_string()])
+        );
+        assert_eq!(diff.added_objects, BTreeSet::from(["session".to_string()]));
+        assert!(diff.changed_objects.is_empty());
+        let auth = &diff.changed_classes["authentication"];
+        assert_eq!(auth.added["session"], "session");
+        assert_eq!(auth.removed["auth_protocol"], "string_t");
+        assert_eq!(auth.retyped["time"].to, "timestamp_t");
+        assert_eq!(auth.retyped["user"].to, "user[]");
+        assert_eq!(auth.enums["activity_id"].added[&2], "Logoff");
+        assert_eq!(auth.enums["activity_id"].removed[&3], "Ticket");
+
+        assert_eq!(
+            diff.to_string(),
+            "- class legacy_activity\n\
+             + object session\n\
+             ~ class authentication\n\
+             \x20   + session: session\n\
+             \x20   - auth_protocol: string_t\n\
+             \x20   ~ time: integer_t -> timestamp_t\n\
+             \x20   ~ user: user -> user[]\n\
+             \x20   + activity_id = 2 (Logoff)\n\
+             \x20   - activity_id = 3 (Ticket)\n"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&schema_diff_json(&diff).unwrap()).unwrap();
+        assert_eq!(
+            json["changed_classes"]["authentication"]["retyped"]["time"]["from"],
+            "integer_t"
+        );
+        assert!(json.get("added_classes").is_none());
+        assert!(diff_schemas(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn unchanged_enums_are_omitted() {
+        let values = r#""0": {"caption": "Unknown"}, "1": {"caption": "Logon"}"#;
+        let diff = diff_enums(&schema_with_activity(values), &schema_with_activity(values));
+        assert!(diff.is_empty());
+        assert_eq!(enum_diff_json(&diff).unwrap(), "{}");
+    }
+}
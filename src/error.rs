@@ -36,6 +36,11 @@ pub enum Error {
     #[error("download failed: {0}")]
     Download(String),
 
+    /// The schema export does not conform to the OCSF meta-schema.
+    #[cfg(feature = "validate-meta")]
+    #[error("meta-schema validation failed: {0}")]
+    MetaSchema(String),
+
     /// Proto generation error.
     #[error("codegen error: {0}")]
     Codegen(String),
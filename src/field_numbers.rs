@@ -0,0 +1,393 @@
+//! Field number assignment for generated messages.
+//!
+//! By default fields are numbered sequentially from 1 in attribute order.
+//! [`GenerationOptions::pinned_field_numbers`](crate::codegen::GenerationOptions::pinned_field_numbers)
+//! overrides that for selected fields, so a migration from a hand-written
+//! proto can keep the numbers its consumers already depend on. With the
+//! `descriptors` feature, the pins can be read from an existing `.proto`
+//! (`field_numbers_from_proto`) or a serialized `FileDescriptorSet`
+//! (`field_numbers_from_descriptor_set`).
+//!
+//! [`GenerationOptions::field_numbers_lock`](crate::codegen::GenerationOptions::field_numbers_lock)
+//! goes further and records every number in a [`FIELD_NUMBERS_LOCK`] file,
+//! so numbers survive schema changes: fields keep their locked number, new
+//! fields are appended, and the numbers of removed fields are reserved
+//! rather than reused.
+
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "descriptors")]
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// Largest valid proto field number.
+pub(crate) const MAX_FIELD_NUMBER: u32 = 536_870_911;
+
+/// Field numbers reserved for the protobuf implementation, which `protoc`
+/// rejects.
+pub(crate) const RESERVED_FIELD_NUMBERS: std::ops::RangeInclusive<u32> = 19_000..=19_999;
+
+/// The field number after `current`, skipping the 19000–19999 range
+/// reserved for the protobuf implementation (18999 is followed by 20000)
+/// and capped at the largest valid number, 536,870,911.
+pub fn next_field_number(current: u32) -> u32 {
+    let next = current
+        .checked_add(1)
+        .map_or(MAX_FIELD_NUMBER, |next| next.min(MAX_FIELD_NUMBER));
+    if RESERVED_FIELD_NUMBERS.contains(&next) {
+        RESERVED_FIELD_NUMBERS.end() + 1
+    } else {
+        next
+    }
+}
+
+/// Field numbers keyed by `Message.field_name`, using the unqualified message
+/// name so pins carry across OCSF version packages.
+pub type FieldNumbers = BTreeMap<String, u32>;
+
+/// File name of the field number lock, written at the root of the output
+/// directory so one lock spans every OCSF version generated there.
+pub const FIELD_NUMBERS_LOCK: &str = "field-numbers.lock";
+
+/// Parse a [`FIELD_NUMBERS_LOCK`] file: a JSON object of
+/// `Message.field_name → number`.
+///
+/// Returns [`Error::Codegen`] naming the first field whose number is not a
+/// valid proto field number (1 to 536,870,911, outside 19000–19999).
+pub fn parse_field_numbers_lock(json: &str) -> Result<FieldNumbers> {
+    let numbers: FieldNumbers = serde_json::from_str(json)
+        .map_err(|e| Error::Codegen(format!("parsing {FIELD_NUMBERS_LOCK}: {e}")))?;
+    for (field, &number) in &numbers {
+        if number == 0 || number > MAX_FIELD_NUMBER || RESERVED_FIELD_NUMBERS.contains(&number) {
+            return Err(Error::Codegen(format!(
+                "{FIELD_NUMBERS_LOCK}: '{field}' has field number {number}, which is not \
+                 between 1 and {MAX_FIELD_NUMBER} outside the reserved 19000-19999 range"
+            )));
+        }
+    }
+    Ok(numbers)
+}
+
+/// The number to try after `current`, or `None` once the largest valid
+/// number has been handed out.
+fn following(current: u32) -> Option<u32> {
+    (current < MAX_FIELD_NUMBER).then(|| next_field_number(current))
+}
+
+/// Serialize `numbers` as the pretty-printed [`FIELD_NUMBERS_LOCK`] document.
+pub fn field_numbers_lock_json(numbers: &FieldNumbers) -> Result<String> {
+    serde_json::to_string_pretty(numbers)
+        .map_err(|e| Error::Codegen(format!("serializing {FIELD_NUMBERS_LOCK}: {e}")))
+}
+
+/// The entries of `numbers` for `message`, keyed by field name.
+fn message_entries(numbers: &FieldNumbers, message: &str) -> BTreeMap<String, u32> {
+    let prefix = format!("{message}.");
+    numbers
+        .range(prefix.clone()..)
+        .take_while(|(key, _)| key.starts_with(&prefix))
+        .map(|(key, &number)| (key[prefix.len()..].to_string(), number))
+        .collect()
+}
+
+/// Assigns field numbers within a single message.
+///
+/// Pinned fields keep their number. Every other field takes the lowest
+/// number above the previous one that no pin of the message claims, so
+/// unpinned fields never collide with pinned ones, and that lies outside
+/// [`RESERVED_FIELD_NUMBERS`].
+pub(crate) struct FieldAllocator {
+    message: String,
+    pinned: BTreeMap<String, u32>,
+    taken: BTreeSet<u32>,
+    /// The next number to try; `None` once every number is used.
+    next: Option<u32>,
+    assigned: usize,
+    /// Fields of the lock, which hold their number even once removed.
+    locked: BTreeMap<String, u32>,
+    /// Every number handed out, by field.
+    numbers: BTreeMap<String, u32>,
+}
+
+impl FieldAllocator {
+    /// Create an allocator for `message`, honoring the pins in `pinned`.
+    pub(crate) fn new(pinned: &FieldNumbers, message: &str) -> Self {
+        Self::with_lock(pinned, &FieldNumbers::new(), message)
+    }
+
+    /// Create an allocator for `message` that also honors `lock`, the
+    /// numbers of earlier runs.
+    ///
+    /// Locked fields keep their number unless a pin claims the field or its
+    /// number. Other fields are numbered after the message's highest locked
+    /// number, so a number freed by a removed field is never reused.
+    pub(crate) fn with_lock(pinned: &FieldNumbers, lock: &FieldNumbers, message: &str) -> Self {
+        let pinned = message_entries(pinned, message);
+        let pinned_numbers: BTreeSet<u32> = pinned.values().copied().collect();
+        let locked: BTreeMap<String, u32> = message_entries(lock, message)
+            .into_iter()
+            .filter(|(field, number)| {
+                !pinned.contains_key(field) && !pinned_numbers.contains(number)
+            })
+            .collect();
+        let next = match locked.values().max() {
+            Some(&max) => following(max),
+            None => Some(1),
+        };
+        let mut taken = pinned_numbers;
+        taken.extend(locked.values());
+        let mut pinned = pinned;
+        pinned.extend(locked.clone());
+        Self {
+            message: message.to_string(),
+            pinned,
+            taken,
+            next,
+            assigned: 0,
+            locked,
+            numbers: BTreeMap::new(),
+        }
+    }
+
+    /// Assign the number for `field`, failing with [`Error::Codegen`] if
+    /// the message has no valid number left.
+    pub(crate) fn assign(&mut self, field: &str) -> Result<u32> {
+        self.assigned += 1;
+        if let Some(&number) = self.pinned.get(field) {
+            self.numbers.insert(field.to_string(), number);
+            return Ok(number);
+        }
+        while let Some(next) = self.next.filter(|next| self.taken.contains(next)) {
+            self.next = following(next);
+        }
+        let Some(number) = self.next else {
+            return Err(Error::Codegen(format!(
+                "message '{}' has no field number left for '{field}' (the largest is \
+                 {MAX_FIELD_NUMBER})",
+                self.message
+            )));
+        };
+        self.next = following(number);
+        self.numbers.insert(field.to_string(), number);
+        Ok(number)
+    }
+
+    /// Take the number `field` would be assigned without counting it as a
+    /// field, for a reserved number.
+    pub(crate) fn reserve(&mut self, field: &str) -> Result<u32> {
+        let number = self.assign(field);
+        self.assigned -= 1;
+        number
+    }
+
+    /// Whether any field has been assigned a number.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.assigned == 0
+    }
+
+    /// Locked fields that were not assigned, i.e. removed since the lock
+    /// was written, in number order.
+    pub(crate) fn freed(&self) -> Vec<(&str, u32)> {
+        let mut freed: Vec<(&str, u32)> = self
+            .locked
+            .iter()
+            .filter(|(field, _)| !self.numbers.contains_key(field.as_str()))
+            .map(|(field, &number)| (field.as_str(), number))
+            .collect();
+        freed.sort_by_key(|&(_, number)| number);
+        freed
+    }
+
+    /// Record every number handed out in `lock`, keyed by
+    /// `message.field_name`.
+    pub(crate) fn record(&self, message: &str, lock: &mut FieldNumbers) {
+        for (field, &number) in &self.numbers {
+            lock.insert(format!("{message}.{field}"), number);
+        }
+    }
+}
+
+/// Extract `Message.field_name → number` from a serialized `FileDescriptorSet`
+/// (e.g., the output of `protoc --descriptor_set_out`).
+///
+/// Map entry messages synthesized by `protoc` are ignored.
+#[cfg(feature = "descriptors")]
+pub fn field_numbers_from_descriptor_set(bytes: &[u8]) -> Result<FieldNumbers> {
+    let pool = protox::prost_reflect::DescriptorPool::decode(bytes)
+        .map_err(|e| Error::Codegen(format!("decoding descriptor set: {e}")))?;
+    let mut numbers = FieldNumbers::new();
+    for message in pool.all_messages().filter(|m| !m.is_map_entry()) {
+        for field in message.fields() {
+            numbers.insert(
+                format!("{}.{}", message.name(), field.name()),
+                field.number(),
+            );
+        }
+    }
+    Ok(numbers)
+}
+
+/// Extract `Message.field_name → number` from an existing `.proto` file,
+/// resolving its imports against `includes`.
+///
+/// Messages defined in imported files are read as well.
+#[cfg(feature = "descriptors")]
+pub fn field_numbers_from_proto(proto: &Path, includes: &[&Path]) -> Result<FieldNumbers> {
+    let mut compiler = protox::Compiler::new(includes)
+        .map_err(|e| Error::Codegen(format!("opening proto includes: {e}")))?;
+    compiler.include_imports(true);
+    compiler
+        .open_file(proto)
+        .map_err(|e| Error::Codegen(format!("compiling {}: {e}", proto.display())))?;
+    field_numbers_from_descriptor_set(&compiler.encode_file_descriptor_set())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpinned_fields_skip_pinned_numbers() {
+        let pinned = FieldNumbers::from([
+            ("Authentication.message".to_string(), 1),
+            ("Authentication.time".to_string(), 3),
+            ("AuthenticationExtra.other".to_string(), 2),
+        ]);
+        let mut alloc = FieldAllocator::new(&pinned, "Authentication");
+        assert!(alloc.is_empty());
+
+        assert_eq!(alloc.assign("activity_id").unwrap(), 2);
+        assert_eq!(alloc.assign("message").unwrap(), 1);
+        assert_eq!(alloc.assign("status").unwrap(), 4);
+        assert_eq!(alloc.assign("time").unwrap(), 3);
+        assert!(!alloc.is_empty());
+    }
+
+    #[test]
+    fn reserved_numbers_are_not_reused() {
+        let mut alloc = FieldAllocator::new(&FieldNumbers::new(), "Device");
+        assert_eq!(alloc.reserve("old").unwrap(), 1);
+        assert!(alloc.is_empty());
+        assert_eq!(alloc.assign("new").unwrap(), 2);
+    }
+
+    #[test]
+    fn next_field_number_skips_reserved_range() {
+        assert_eq!(next_field_number(1), 2);
+        assert_eq!(next_field_number(18_998), 18_999);
+        assert_eq!(next_field_number(18_999), 20_000);
+        assert_eq!(next_field_number(20_000), 20_001);
+        assert_eq!(next_field_number(MAX_FIELD_NUMBER), MAX_FIELD_NUMBER);
+        assert_eq!(next_field_number(u32::MAX), MAX_FIELD_NUMBER);
+    }
+
+    #[test]
+    fn allocator_fails_past_the_largest_number() {
+        let lock = FieldNumbers::from([("Big.last".to_string(), MAX_FIELD_NUMBER)]);
+        let mut alloc = FieldAllocator::with_lock(&FieldNumbers::new(), &lock, "Big");
+        assert_eq!(alloc.assign("last").unwrap(), MAX_FIELD_NUMBER);
+        let err = alloc.assign("new").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("message 'Big' has no field number left for 'new'")
+        );
+    }
+
+    #[test]
+    fn allocator_skips_reserved_range() {
+        let pinned = FieldNumbers::from([("Big.last".to_string(), 18_999)]);
+        let mut alloc = FieldAllocator::new(&pinned, "Big");
+        alloc.next = Some(18_998);
+        assert_eq!(alloc.assign("a").unwrap(), 18_998);
+        assert_eq!(alloc.assign("b").unwrap(), 20_000);
+        assert_eq!(alloc.assign("last").unwrap(), 18_999);
+    }
+
+    #[test]
+    fn no_pins_numbers_sequentially() {
+        let mut alloc = FieldAllocator::new(&FieldNumbers::new(), "Device");
+        assert_eq!(alloc.assign("a").unwrap(), 1);
+        assert_eq!(alloc.assign("b").unwrap(), 2);
+    }
+
+    #[test]
+    fn locked_numbers_survive_added_and_removed_fields() {
+        let lock = FieldNumbers::from([
+            ("Device.hostname".to_string(), 1),
+            ("Device.ip".to_string(), 2),
+            ("Device.name".to_string(), 3),
+        ]);
+        let mut alloc = FieldAllocator::with_lock(&FieldNumbers::new(), &lock, "Device");
+
+        // `ip` is gone; `agent` sorts first but is appended after the lock.
+        assert_eq!(alloc.assign("agent").unwrap(), 4);
+        assert_eq!(alloc.assign("hostname").unwrap(), 1);
+        assert_eq!(alloc.assign("name").unwrap(), 3);
+        assert_eq!(alloc.freed(), [("ip", 2)]);
+
+        let mut recorded = FieldNumbers::new();
+        alloc.record("Device", &mut recorded);
+        assert_eq!(recorded["Device.agent"], 4);
+        assert!(!recorded.contains_key("Device.ip"));
+    }
+
+    #[test]
+    fn pins_override_the_lock() {
+        let lock = FieldNumbers::from([
+            ("Device.hostname".to_string(), 1),
+            ("Device.ip".to_string(), 2),
+        ]);
+        let pinned = FieldNumbers::from([("Device.name".to_string(), 2)]);
+        let mut alloc = FieldAllocator::with_lock(&pinned, &lock, "Device");
+        assert_eq!(alloc.assign("hostname").unwrap(), 1);
+        assert_eq!(alloc.assign("ip").unwrap(), 3);
+        assert_eq!(alloc.assign("name").unwrap(), 2);
+        assert!(alloc.freed().is_empty());
+    }
+
+    #[test]
+    fn lock_round_trips_through_json() {
+        let lock = FieldNumbers::from([("Device.ip".to_string(), 2)]);
+        let json = field_numbers_lock_json(&lock).unwrap();
+        assert_eq!(parse_field_numbers_lock(&json).unwrap(), lock);
+        assert!(parse_field_numbers_lock("[1]").is_err());
+    }
+
+    #[test]
+    fn lock_rejects_invalid_field_numbers() {
+        for number in [0, 19_000, 19_999, MAX_FIELD_NUMBER + 1, u32::MAX] {
+            let json = format!(r#"{{"Device.ip": 2, "Device.name": {number}}}"#);
+            let err = parse_field_numbers_lock(&json).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains(&format!("'Device.name' has field number {number}")),
+                "{err}"
+            );
+        }
+        let json = format!(r#"{{"Device.ip": 18999, "Device.name": {MAX_FIELD_NUMBER}}}"#);
+        assert_eq!(parse_field_numbers_lock(&json).unwrap().len(), 2);
+    }
+
+    #[cfg(feature = "descriptors")]
+    #[test]
+    fn reads_numbers_from_proto() {
+        let dir = std::env::temp_dir().join(format!("ocsf-pin-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("legacy.proto"),
+            "syntax = \"proto3\";\npackage legacy;\n\
+             message Authentication {\n  string message = 7;\n  map<string, string> tags = 9;\n}\n",
+        )
+        .unwrap();
+
+        let numbers = field_numbers_from_proto(&dir.join("legacy.proto"), &[&dir]).unwrap();
+        assert_eq!(
+            numbers,
+            FieldNumbers::from([
+                ("Authentication.message".to_string(), 7),
+                ("Authentication.tags".to_string(), 9),
+            ])
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
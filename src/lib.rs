@@ -10,6 +10,7 @@
 //! - Generates per-class and shared-object enum definitions
 //! - Resolves transitive object dependencies automatically
 //! - Skips deprecated attributes
+//! - Marks deprecated classes and objects with `option deprecated = true`
 //! - Maps `json_t` to `string` (avoids `google.protobuf.Struct` compatibility issues)
 //! - Handles extension-prefixed objects (e.g., `win/win_service`)
 //! - Deterministic output: byte-identical across runs
@@ -30,6 +31,9 @@
 //! ```
 
 pub mod codegen;
+pub mod diff;
 pub mod error;
+pub mod field_numbers;
+pub mod lint;
 pub mod schema;
 pub mod type_map;
@@ -0,0 +1,468 @@
+//! Schema checks for problems that break or degrade generation.
+//!
+//! [`lint_schema`] looks at every class and object attribute for hazards the
+//! generator would otherwise hit silently or leave to `protoc`: references to
+//! missing objects, integer enums with duplicate values or colliding variant
+//! names, captions with no usable variant name, and unknown types. Objects
+//! are looked up and variants named exactly as [`crate::codegen`] does, with
+//! the default naming strategy.
+//!
+//! It also applies one naming heuristic, which [`LintOptions`] can turn off
+//! or waive per attribute: an attribute named `time` or ending in `_time` or
+//! `_dt` should have type `timestamp_t` or `datetime_t`, and an attribute of
+//! either type should be named that way. A time value typed as something
+//! else silently becomes a `string` or plain integer field.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::codegen::{BASE_OBJECT, enum_entries, is_integer_enum};
+use crate::schema::{OcsfAttribute, OcsfSchema, lookup_object};
+use crate::type_map::{DefaultNaming, NamingStrategy, known_scalar_type, sanitize_object_name};
+
+/// A problem found on one attribute of a class or object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaIssue {
+    /// `"class"` or `"object"`.
+    pub owner_kind: &'static str,
+
+    /// Class or object name, as keyed in the schema.
+    pub owner: String,
+
+    /// Attribute name.
+    pub attribute: String,
+
+    /// What is wrong.
+    pub kind: IssueKind,
+}
+
+/// Settings for [`lint_schema_with`]. The default runs every check.
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    /// Skip the time naming check ([`IssueKind::TimeNameNotTimeType`] and
+    /// [`IssueKind::TimeTypeNotTimeName`]).
+    pub skip_time_check: bool,
+
+    /// Attributes exempt from the time naming check, as `owner.attribute`
+    /// with the owner keyed as in the schema (e.g., `file.modified_time`).
+    pub time_check_allow: BTreeSet<String>,
+}
+
+/// The kinds of problem [`lint_schema`] reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// `object_type` names an object the schema does not define; the field
+    /// would be emitted as `string`.
+    MissingObject {
+        /// The referenced object name.
+        object_type: String,
+    },
+
+    /// Several keys of an integer enum parse to the same value (e.g., `"1"`
+    /// and `"01"`), forcing `allow_alias`.
+    DuplicateEnumValue {
+        /// The shared value.
+        value: i32,
+        /// The keys that parse to it.
+        keys: Vec<String>,
+    },
+
+    /// An enum caption yields no variant name, so the variant falls back to
+    /// `VALUE_<n>`.
+    EmptyVariantName {
+        /// The enum value.
+        value: i32,
+        /// Its caption.
+        caption: String,
+    },
+
+    /// Distinct enum values get the same variant name, which `protoc`
+    /// rejects. The synthetic zero `UNSPECIFIED` counts when OCSF defines no
+    /// 0 value.
+    VariantNameCollision {
+        /// The generated enum name.
+        enum_name: String,
+        /// The shared variant name.
+        variant: String,
+        /// The values sharing it.
+        values: Vec<i32>,
+    },
+
+    /// `type` is not a known OCSF type; the field would default to `string`.
+    UnknownType {
+        /// The attribute's type name.
+        type_name: String,
+    },
+
+    /// The name suggests a time (`time`, `*_time`, `*_dt`) but the type is
+    /// neither `timestamp_t` nor `datetime_t`.
+    TimeNameNotTimeType {
+        /// The attribute's type name.
+        type_name: String,
+    },
+
+    /// The type is `timestamp_t` or `datetime_t` but the name is not
+    /// `time` and does not end in `_time` or `_dt`.
+    TimeTypeNotTimeName {
+        /// The attribute's type name.
+        type_name: String,
+    },
+}
+
+impl IssueKind {
+    /// Whether the issue makes generated output wrong rather than merely
+    /// degraded: a field that silently becomes `string`, or variant names
+    /// `protoc` rejects.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Self::MissingObject { .. }
+                | Self::VariantNameCollision { .. }
+                | Self::UnknownType { .. }
+        )
+    }
+}
+
+impl fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} '{}', attribute '{}': ",
+            self.owner_kind, self.owner, self.attribute
+        )?;
+        match &self.kind {
+            IssueKind::MissingObject { object_type } => {
+                write!(f, "references missing object '{object_type}'")
+            }
+            IssueKind::DuplicateEnumValue { value, keys } => {
+                write!(f, "enum keys {} all have value {value}", keys.join(", "))
+            }
+            IssueKind::EmptyVariantName { value, caption } => write!(
+                f,
+                "enum value {value} caption {caption:?} has no variant name; \
+                 it becomes VALUE_{value}"
+            ),
+            IssueKind::VariantNameCollision {
+                enum_name,
+                variant,
+                values,
+            } => {
+                let values: Vec<String> = values.iter().map(i32::to_string).collect();
+                write!(
+                    f,
+                    "enum values {} all become {enum_name}_{variant}",
+                    values.join(", ")
+                )
+            }
+            IssueKind::UnknownType { type_name } => {
+                write!(f, "unknown type '{type_name}' defaults to string")
+            }
+            IssueKind::TimeNameNotTimeType { type_name } => write!(
+                f,
+                "name suggests a time but type is '{type_name}', \
+                 not timestamp_t or datetime_t"
+            ),
+            IssueKind::TimeTypeNotTimeName { type_name } => write!(
+                f,
+                "type '{type_name}' is a time but the name is not time, *_time, or *_dt"
+            ),
+        }
+    }
+}
+
+/// Check every class and object attribute in `schema`, returning issues
+/// for classes (by name) and then objects (by name), each in attribute
+/// order.
+pub fn lint_schema(schema: &OcsfSchema) -> Vec<SchemaIssue> {
+    lint_schema_with(schema, &LintOptions::default())
+}
+
+/// [`lint_schema`] with the checks configured by `options`.
+pub fn lint_schema_with(schema: &OcsfSchema, options: &LintOptions) -> Vec<SchemaIssue> {
+    let mut issues = Vec::new();
+    for (name, cls) in &schema.classes {
+        lint_owner(schema, options, "class", name, &cls.attributes, &mut issues);
+    }
+    for (name, obj) in &schema.objects {
+        lint_owner(
+            schema,
+            options,
+            "object",
+            name,
+            &obj.attributes,
+            &mut issues,
+        );
+    }
+    issues
+}
+
+/// Whether an attribute name suggests a time value: `time`, `*_time`, or
+/// `*_dt`.
+fn is_time_name(attr_name: &str) -> bool {
+    attr_name == "time" || attr_name.ends_with("_time") || attr_name.ends_with("_dt")
+}
+
+fn lint_owner(
+    schema: &OcsfSchema,
+    options: &LintOptions,
+    owner_kind: &'static str,
+    owner: &str,
+    attributes: &BTreeMap<String, OcsfAttribute>,
+    issues: &mut Vec<SchemaIssue>,
+) {
+    for (attr_name, attr) in attributes {
+        let mut report = |kind| {
+            issues.push(SchemaIssue {
+                owner_kind,
+                owner: owner.to_string(),
+                attribute: attr_name.clone(),
+                kind,
+            })
+        };
+
+        match &attr.object_type {
+            // The base object is generated as `string` even when missing.
+            Some(object_type)
+                if object_type != BASE_OBJECT && lookup_object(schema, object_type).is_none() =>
+            {
+                report(IssueKind::MissingObject {
+                    object_type: object_type.clone(),
+                });
+            }
+            Some(_) => {}
+            None if attr.type_name != "object_t"
+                && known_scalar_type(&attr.type_name).is_none() =>
+            {
+                report(IssueKind::UnknownType {
+                    type_name: attr.type_name.clone(),
+                });
+            }
+            None => {}
+        }
+
+        if !options.skip_time_check
+            && !options
+                .time_check_allow
+                .contains(&format!("{owner}.{attr_name}"))
+        {
+            let time_type = matches!(attr.type_name.as_str(), "timestamp_t" | "datetime_t");
+            let type_name = attr.type_name.clone();
+            match (is_time_name(attr_name), time_type) {
+                (true, false) => report(IssueKind::TimeNameNotTimeType { type_name }),
+                (false, true) => report(IssueKind::TimeTypeNotTimeName { type_name }),
+                _ => {}
+            }
+        }
+
+        let Some(enum_vals) = attr.enum_values.as_ref().filter(|e| is_integer_enum(e)) else {
+            continue;
+        };
+        let mut keys_by_value: BTreeMap<i32, Vec<String>> = BTreeMap::new();
+        for key in enum_vals.keys() {
+            if let Ok(value) = key.parse() {
+                keys_by_value.entry(value).or_default().push(key.clone());
+            }
+        }
+        for (value, keys) in keys_by_value {
+            if keys.len() > 1 {
+                report(IssueKind::DuplicateEnumValue { value, keys });
+            }
+        }
+
+        let entries = enum_entries(enum_vals, &DefaultNaming);
+        let mut values_by_variant: BTreeMap<String, BTreeSet<i32>> = BTreeMap::new();
+        if !entries.iter().any(|(value, _, _)| *value == 0) {
+            values_by_variant.insert("UNSPECIFIED".to_string(), BTreeSet::from([0]));
+        }
+        for (value, variant, enum_value) in &entries {
+            if DefaultNaming
+                .enum_variant_name(&enum_value.caption)
+                .is_empty()
+            {
+                report(IssueKind::EmptyVariantName {
+                    value: *value,
+                    caption: enum_value.caption.clone(),
+                });
+            }
+            values_by_variant
+                .entry(variant.clone())
+                .or_default()
+                .insert(*value);
+        }
+        let enum_name = DefaultNaming.enum_name(&sanitize_object_name(owner), attr_name);
+        for (variant, values) in values_by_variant {
+            if values.len() > 1 {
+                report(IssueKind::VariantNameCollision {
+                    enum_name: enum_name.clone(),
+                    variant,
+                    values: values.into_iter().collect(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(attributes: &str) -> OcsfSchema {
+        let json = format!(
+            r#"{{
+                "version": "1.7.0",
+                "classes": {{
+                    "authentication": {{
+                        "name": "authentication",
+                        "uid": 3002,
+                        "caption": "Authentication",
+                        "attributes": {{ {attributes} }}
+                    }}
+                }},
+                "objects": {{
+                    "win/win_service": {{
+                        "name": "win/win_service",
+                        "caption": "Windows Service",
+                        "attributes": {{}}
+                    }}
+                }}
+            }}"#
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn only_breaking_issues_are_errors() {
+        let schema = schema(
+            r#""user": {"type": "object_t", "object_type": "nope"},
+               "weird": {"type": "mystery_t"},
+               "login_time": {"type": "string_t"}"#,
+        );
+        let errors: Vec<bool> = lint_schema(&schema)
+            .iter()
+            .map(|issue| issue.kind.is_error())
+            .collect();
+        // `login_time`, `user`, `weird`.
+        assert_eq!(errors, [false, true, true]);
+    }
+
+    #[test]
+    fn clean_schema_has_no_issues() {
+        let schema = schema(
+            r#""activity_id": {"type": "integer_t", "enum": {"0": {"caption": "Unknown"}, "1": {"caption": "Logon"}}},
+               "service": {"type": "object_t", "object_type": "win_service"},
+               "time": {"type": "timestamp_t"}"#,
+        );
+        assert_eq!(lint_schema(&schema), []);
+    }
+
+    #[test]
+    fn reports_each_hazard_with_context() {
+        let schema = schema(
+            r#""activity_id": {"type": "integer_t", "enum": {
+                   "1": {"caption": "Logon"}, "01": {"caption": "Log On"},
+                   "2": {"caption": "Other"}, "99": {"caption": "Other"},
+                   "3": {"caption": "—"}, "4": {"caption": "Unspecified"}}},
+               "http_method": {"type": "string_t", "enum": {"GET": {"caption": "Get"}}},
+               "service": {"type": "object_t", "object_type": "device"},
+               "size": {"type": "bigint_t"}"#,
+        );
+
+        let issues = lint_schema(&schema);
+        let kinds: Vec<&IssueKind> = issues.iter().map(|issue| &issue.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &IssueKind::DuplicateEnumValue {
+                    value: 1,
+                    keys: vec!["01".to_string(), "1".to_string()],
+                },
+                &IssueKind::EmptyVariantName {
+                    value: 3,
+                    caption: "—".to_string(),
+                },
+                &IssueKind::VariantNameCollision {
+                    enum_name: "AUTHENTICATION_ACTIVITY_ID".to_string(),
+                    variant: "OTHER".to_string(),
+                    values: vec![2, 99],
+                },
+                &IssueKind::VariantNameCollision {
+                    enum_name: "AUTHENTICATION_ACTIVITY_ID".to_string(),
+                    variant: "UNSPECIFIED".to_string(),
+                    values: vec![0, 4],
+                },
+                &IssueKind::MissingObject {
+                    object_type: "device".to_string(),
+                },
+                &IssueKind::UnknownType {
+                    type_name: "bigint_t".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            issues[4].to_string(),
+            "class 'authentication', attribute 'service': references missing object 'device'"
+        );
+    }
+
+    #[test]
+    fn flags_time_names_and_types_that_disagree() {
+        let schema = schema(
+            r#""time": {"type": "timestamp_t"},
+               "time_dt": {"type": "datetime_t"},
+               "logon_time": {"type": "string_t"},
+               "expiry_dt": {"type": "long_t"},
+               "created": {"type": "timestamp_t"},
+               "timezone_offset": {"type": "integer_t"}"#,
+        );
+
+        let issues = lint_schema(&schema);
+        let flagged: Vec<(&str, &IssueKind)> = issues
+            .iter()
+            .map(|issue| (issue.attribute.as_str(), &issue.kind))
+            .collect();
+        assert_eq!(
+            flagged,
+            [
+                (
+                    "created",
+                    &IssueKind::TimeTypeNotTimeName {
+                        type_name: "timestamp_t".to_string(),
+                    }
+                ),
+                (
+                    "expiry_dt",
+                    &IssueKind::TimeNameNotTimeType {
+                        type_name: "long_t".to_string(),
+                    }
+                ),
+                (
+                    "logon_time",
+                    &IssueKind::TimeNameNotTimeType {
+                        type_name: "string_t".to_string(),
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            issues[2].to_string(),
+            "class 'authentication', attribute 'logon_time': name suggests a time but type \
+             is 'string_t', not timestamp_t or datetime_t"
+        );
+
+        let waived = LintOptions {
+            time_check_allow: ["authentication.created", "authentication.expiry_dt"]
+                .map(String::from)
+                .into(),
+            ..Default::default()
+        };
+        let remaining = lint_schema_with(&schema, &waived);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].attribute, "logon_time");
+
+        let skipped = LintOptions {
+            skip_time_check: true,
+            ..Default::default()
+        };
+        assert_eq!(lint_schema_with(&schema, &skipped), []);
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use clap::{Parser, Subcommand};
@@ -15,6 +15,8 @@ struct Cli {
     command: Commands,
 }
 
+// Parsed once at startup, so the size of `Generate` doesn't matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Download the OCSF schema export and cache locally.
@@ -35,6 +37,154 @@ enum Commands {
             env = "OCSF_SCHEMA_URL"
         )]
         schema_url: String,
+
+        /// Retries after a connection error or 5xx response, with
+        /// exponential backoff from 500ms.
+        #[arg(long, default_value_t = 2)]
+        retries: u32,
+
+        /// Expected SHA-256 of the export (hex); a mismatch fails without
+        /// writing. The hash is printed after every download.
+        #[arg(long)]
+        sha256: Option<String>,
+    },
+
+    /// Download several OCSF schema exports concurrently.
+    #[cfg(feature = "download")]
+    DownloadSchemas {
+        /// Comma-separated OCSF versions to download (e.g., "1.6.0,1.7.0").
+        #[arg(long)]
+        versions: String,
+
+        /// Output directory for cached schemas (<output-dir>/<version>/schema.json).
+        #[arg(long, default_value = ".")]
+        output_dir: PathBuf,
+
+        /// Base URL for the OCSF schema export API.
+        #[arg(
+            long,
+            default_value = "https://schema.ocsf.io/export/schema",
+            env = "OCSF_SCHEMA_URL"
+        )]
+        schema_url: String,
+
+        /// Maximum number of downloads in flight at once.
+        #[arg(long, default_value_t = 4)]
+        max_concurrent: usize,
+    },
+
+    /// Report enum values added, removed, or renamed between two OCSF versions.
+    DiffEnums {
+        /// Directory containing the cached old schema (<old>/<version-old>/schema.json).
+        #[arg(long)]
+        old: PathBuf,
+
+        /// Directory containing the cached new schema (<new>/<version-new>/schema.json).
+        #[arg(long)]
+        new: PathBuf,
+
+        /// Old OCSF version (e.g., "1.6.0").
+        #[arg(long)]
+        version_old: String,
+
+        /// New OCSF version (e.g., "1.7.0").
+        #[arg(long)]
+        version_new: String,
+
+        /// Path of the enum diff JSON to write.
+        #[arg(long, default_value = "enum-diff.json")]
+        output: PathBuf,
+    },
+
+    /// Report classes, objects, attributes, and enum values that changed
+    /// between two OCSF versions.
+    Diff {
+        /// Directory containing the cached old schema (<old>/<version-old>/schema.json).
+        #[arg(long)]
+        old: PathBuf,
+
+        /// Directory containing the cached new schema (<new>/<version-new>/schema.json).
+        #[arg(long)]
+        new: PathBuf,
+
+        /// Old OCSF version (e.g., "1.6.0").
+        #[arg(long)]
+        version_old: String,
+
+        /// New OCSF version (e.g., "1.7.0").
+        #[arg(long)]
+        version_new: String,
+
+        /// Print the differences as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print the proto type an OCSF type name maps to.
+    MapType {
+        /// OCSF type name (e.g., "timestamp_t").
+        ocsf_type: String,
+    },
+
+    /// List the event classes in a cached OCSF schema.
+    ListClasses {
+        /// OCSF version to list.
+        #[arg(long, default_value = "1.7.0")]
+        ocsf_version: String,
+
+        /// Directory containing cached schema files.
+        /// Schema is expected at <schema-dir>/<version>/schema.json.
+        #[arg(long, default_value = ".")]
+        schema_dir: PathBuf,
+
+        /// Only list classes in this category (e.g., "iam").
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Print a JSON array of {name, uid, category} objects.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show a class's attributes with their resolved proto types, and the
+    /// objects it transitively references.
+    DescribeClass {
+        /// Class name (e.g., "authentication").
+        #[arg(long)]
+        name: String,
+
+        /// OCSF version to inspect.
+        #[arg(long, default_value = "1.7.0")]
+        ocsf_version: String,
+
+        /// Directory containing cached schema files.
+        /// Schema is expected at <schema-dir>/<version>/schema.json.
+        #[arg(long, default_value = ".")]
+        schema_dir: PathBuf,
+    },
+
+    /// Check a cached OCSF schema for codegen hazards (missing objects,
+    /// duplicate or colliding enum values, unknown types), failing if any
+    /// are found.
+    ValidateSchema {
+        /// OCSF version to check.
+        #[arg(long, default_value = "1.7.0")]
+        ocsf_version: String,
+
+        /// Directory containing cached schema files.
+        /// Schema is expected at <schema-dir>/<version>/schema.json.
+        #[arg(long, default_value = ".")]
+        schema_dir: PathBuf,
+
+        /// Skip the check that time-named attributes (time, *_time, *_dt)
+        /// have a time type and vice versa.
+        #[arg(long)]
+        no_time_check: bool,
+
+        /// Exempt one attribute from the time naming check, as
+        /// OWNER.ATTRIBUTE (e.g., file.modified_time). Repeatable.
+        #[arg(long)]
+        time_check_allow: Vec<String>,
     },
 
     /// Generate .proto files from a cached OCSF schema.
@@ -43,11 +193,17 @@ enum Commands {
         #[arg(long, default_value = "1.7.0")]
         ocsf_version: String,
 
-        /// Comma-separated event class names, or "all" for every class.
+        /// Comma-separated event class names, "category:<name>" for every
+        /// class in a category, or "all" for every class.
         ///
-        /// Example: --classes authentication,security_finding,network_activity
+        /// Example: --classes authentication,category:findings,network_activity
+        #[arg(long, required_unless_present = "classes_file")]
+        classes: Option<String>,
+
+        /// File of class names, one per line or comma-separated, merged with
+        /// --classes. Text after `#` is a comment.
         #[arg(long)]
-        classes: String,
+        classes_file: Option<PathBuf>,
 
         /// Output directory for generated .proto files.
         #[arg(long, default_value = ".")]
@@ -58,14 +214,839 @@ enum Commands {
         #[arg(long, default_value = ".")]
         schema_dir: PathBuf,
 
+        /// Clean up the loaded schema before generating (trim captions,
+        /// drop empty enums and unreferenced empty objects, lowercase type
+        /// names).
+        #[arg(long)]
+        normalize: bool,
+
+        /// Comma-separated profiles (e.g., "cloud,host"). Keep base
+        /// attributes plus attributes from these profiles; drop all other
+        /// profiled attributes.
+        #[arg(long)]
+        only_profiles: Option<String>,
+
+        /// Validate the schema export against this JSON Schema before
+        /// generating (requires the `validate-meta` feature).
+        #[cfg(feature = "validate-meta")]
+        #[arg(long)]
+        meta_schema: Option<PathBuf>,
+
+        /// Comma-separated glob patterns selecting which enums appear in
+        /// enum-value-map.json (e.g., "AUTHENTICATION_*,*_SEVERITY_ID").
+        #[arg(long)]
+        enum_map_filter: Option<String>,
+
+        /// Leave shared-object enums out of enum-value-map.json.
+        #[arg(long)]
+        enum_map_skip_objects: bool,
+
+        /// Also write enum-value-map-by-number.json, mapping each enum to its
+        /// values' captions.
+        #[arg(long)]
+        enum_map_by_number: bool,
+
+        /// Write presence-caveats.json listing enum fields whose proto3
+        /// default (0) is a real OCSF value rather than UNSPECIFIED.
+        #[arg(long)]
+        presence_caveats: bool,
+
+        /// Write buf.yaml and buf.gen.yaml at the output directory root,
+        /// matching the generated tree.
+        #[arg(long)]
+        emit_buf: bool,
+
+        /// Mark every generated file `option deprecated = true;` (for
+        /// superseded OCSF versions).
+        #[arg(long)]
+        mark_file_deprecated: bool,
+
+        /// Fail if any event message would have no fields.
+        #[arg(long)]
+        no_empty_messages: bool,
+
+        /// Fail on attributes of unknown type or referencing missing objects
+        /// instead of emitting them as string.
+        #[arg(long)]
+        strict: bool,
+
+        /// Derive field names from attribute captions ("Activity ID" →
+        /// activity_id), keeping the attribute key as json_name.
+        #[arg(long)]
+        caption_field_names: bool,
+
+        /// Emit fields, and assign new field numbers, in the order the
+        /// schema export lists attributes instead of alphabetically.
+        #[arg(long)]
+        schema_field_order: bool,
+
+        /// Put extension classes and objects in extension-scoped packages
+        /// (e.g., ocsf.v1_7_0.ext.win.objects).
+        #[arg(long)]
+        extension_packages: bool,
+
+        /// Add a `// TODO(ocsf-proto-gen)` comment above fields whose type
+        /// fell back to string.
+        #[arg(long)]
+        todo_markers: bool,
+
+        /// Write ocsf/ocsf_options.proto and set the (ocsf.profiles) option on
+        /// event messages of classes with active profiles.
+        #[arg(long)]
+        profile_options: bool,
+
+        /// Write ocsf/ocsf_options.proto and set the (ocsf.class_uid) and
+        /// (ocsf.category_uid) options on every event class message.
+        #[arg(long)]
+        uid_options: bool,
+
+        /// Write category.proto with an OcsfCategory enum of the generated
+        /// classes' categories.
+        #[arg(long)]
+        category_enum: bool,
+
+        /// Write ocsf_event.proto with an OcsfEvent message holding a oneof
+        /// over every generated class.
+        #[arg(long)]
+        event_envelope: bool,
+
+        /// Write one file per enum (enums/<enum_name>.proto) instead of a
+        /// shared enums.proto; message files import only the enums they use.
+        #[arg(long)]
+        per_enum_files: bool,
+
+        /// Emit timestamp_t fields as google.protobuf.Timestamp instead of
+        /// int64 epoch milliseconds.
+        #[arg(long)]
+        well_known_timestamps: bool,
+
+        /// Emit json_t fields and references to field-less objects (e.g.,
+        /// unmapped) as google.protobuf.Struct instead of string.
+        #[arg(long)]
+        struct_json: bool,
+
+        /// Reserve the field number and name of each deprecated attribute
+        /// instead of dropping it, so later fields keep their numbers.
+        #[arg(long)]
+        reserve_deprecated: bool,
+
+        /// Keep deprecated attributes as fields marked [deprecated = true].
+        #[arg(long, conflicts_with = "reserve_deprecated")]
+        emit_deprecated: bool,
+
+        /// Write class, object, and attribute descriptions as comments above
+        /// their messages and fields.
+        #[arg(long)]
+        descriptions: bool,
+
+        /// Write enum value descriptions as comments above their variants.
+        #[arg(long)]
+        enum_descriptions: bool,
+
+        /// Write each object's OCSF observable type number as a comment
+        /// above its message.
+        #[arg(long)]
+        observable_comments: bool,
+
+        /// Reserve the unused values between each integer enum's defined
+        /// ones, so later OCSF values cannot be reused by hand.
+        #[arg(long)]
+        reserve_enum_gaps: bool,
+
+        /// Leave out attributes below this OCSF requirement level (missing
+        /// requirements count as optional).
+        #[arg(long, value_parser = ["optional", "recommended", "required"])]
+        min_requirement: Option<String>,
+
+        /// Leave out one attribute of a class or object, as OWNER.ATTRIBUTE
+        /// (e.g., user.email_addr); objects take their name without the
+        /// extension prefix. Repeatable.
+        #[arg(long, value_parser = parse_exclude_attribute)]
+        exclude_attribute: Vec<(String, String)>,
+
+        /// Note each sibling pair (e.g., activity_id and activity_name) in
+        /// both fields' trailing comments.
+        #[arg(long)]
+        group_siblings: bool,
+
+        /// Proto package prefix replacing `ocsf` in packages, imports, and
+        /// output directories (e.g., com.acme.ocsf).
+        #[arg(long, default_value = "ocsf")]
+        package_prefix: String,
+
+        /// Version segment of packages and directories: v1_7_0 (default) or
+        /// 1_7_0 (no-prefix).
+        #[arg(long, value_parser = ["default", "no-prefix"], default_value = "default")]
+        slug_style: String,
+
+        /// Emit integer enum fields as int32 with their values listed in the
+        /// trailing comment, generating no enum types or enum files.
+        #[arg(long)]
+        int32_enums: bool,
+
+        /// Map an OCSF type to a proto type instead of the built-in mapping,
+        /// as OCSF_TYPE=PROTO_TYPE (e.g., uuid_t=acme.Uuid). Repeatable.
+        #[arg(long, value_parser = parse_type_override)]
+        type_override: Vec<(String, String)>,
+
+        /// Import this file in every proto with an overridden field type.
+        /// Repeatable.
+        #[arg(long)]
+        extra_import: Vec<String>,
+
+        /// Give one json_t attribute its own proto type, as
+        /// MESSAGE.ATTRIBUTE=PROTO_TYPE (e.g., 'Metadata.labels=map<string,
+        /// string>'). Repeatable.
+        #[arg(long, value_parser = parse_json_field_override)]
+        json_field_override: Vec<(String, String)>,
+
+        /// Emit dictionary-named json_t attributes (and references to
+        /// field-less objects) as map<string, string>, or map<string,
+        /// google.protobuf.Value> with --struct-json.
+        #[arg(long)]
+        infer_maps: bool,
+
+        /// Attribute name --infer-maps treats as a dictionary, replacing the
+        /// default data and labels. Repeatable.
+        #[arg(long, requires = "infer_maps")]
+        map_attribute: Vec<String>,
+
+        /// Emit protoc-gen-validate rules (required object fields, port
+        /// ranges), importing validate/validate.proto.
+        #[arg(long)]
+        with_pgv: bool,
+
+        /// Name and comment the synthetic zero of enums without an OCSF 0
+        /// value from this caption (e.g., "Unknown") instead of UNSPECIFIED.
+        #[arg(long)]
+        synthetic_zero_caption: Option<String>,
+
+        /// Emit an explicit [packed = true|false] on repeated numeric and
+        /// enum fields.
+        #[arg(long)]
+        packed_repeated: Option<bool>,
+
+        /// Keep the field numbers of an existing .proto or serialized
+        /// FileDescriptorSet for matching Message.field names (requires the
+        /// `descriptors` feature).
+        #[cfg(feature = "descriptors")]
+        #[arg(long)]
+        pin_field_numbers: Option<PathBuf>,
+
+        /// Number fields from <output-dir>/field-numbers.lock, creating it if
+        /// missing: new fields are appended and removed fields reserved.
+        #[arg(long)]
+        lock_field_numbers: bool,
+
+        /// Write a single self-contained <class>.proto (no imports) for one
+        /// class into the output directory.
+        #[arg(long)]
+        standalone: bool,
+
+        /// Validate and generate in memory without writing files, failing on
+        /// references to objects missing from the schema, unknown types, and
+        /// lint errors in the generated classes and objects.
+        #[arg(long, conflicts_with = "standalone")]
+        check: bool,
+
+        /// Run the full generation but write nothing, listing each file that
+        /// would be written with its size instead.
+        #[arg(long, conflicts_with = "check")]
+        dry_run: bool,
+
+        /// Report the classes, objects, fields and enums generation would
+        /// produce, without rendering or writing anything.
+        #[arg(long, conflicts_with_all = ["check", "dry_run", "standalone", "clean", "force"])]
+        count_only: bool,
+
+        /// Remove the whole ocsf/<version>/ directory under the output
+        /// directory before writing.
+        #[arg(long, conflicts_with_all = ["check", "standalone", "force"])]
+        clean: bool,
+
+        /// After writing, remove files under ocsf/<version>/ that this run
+        /// did not generate, and any directories left empty.
+        #[arg(long, conflicts_with_all = ["check", "standalone"])]
+        force: bool,
+
+        /// Check after generation that enum-value-map.json matches the
+        /// variants defined in the generated enums.
+        #[arg(long)]
+        verify: bool,
+
+        /// Write one import-free ocsf/<version>/ocsf.proto holding every
+        /// message and enum in a single package.
+        #[arg(long, conflicts_with = "standalone")]
+        single_file: bool,
+
+        /// Also write the class → object reference graph to this path as a
+        /// Graphviz DOT digraph.
+        #[arg(long)]
+        emit_graph: Option<PathBuf>,
+
+        /// Warn about object reference cycles (e.g., process →
+        /// parent_process), which become recursive messages.
+        #[arg(long)]
+        report_cycles: bool,
+
+        /// Also write a Draft 2020-12 JSON Schema document per class under
+        /// json-schema/ in the version directory (requires the `jsonschema`
+        /// feature).
+        #[cfg(feature = "jsonschema")]
+        #[arg(long)]
+        json_schema: bool,
+
+        /// Also write serde-ready Rust structs for the classes and their
+        /// objects to this path (requires the `rustgen` feature).
+        #[cfg(feature = "rustgen")]
+        #[arg(long)]
+        emit_rust: Option<PathBuf>,
+
+        /// Pack the generated files into this zip archive, with the same
+        /// relative paths, instead of writing them under the output
+        /// directory (requires the `archive` feature).
+        #[cfg(feature = "archive")]
+        #[arg(long, conflicts_with_all = ["standalone", "clean", "force", "count_only"])]
+        archive: Option<PathBuf>,
+
         /// Suppress non-error output.
         #[arg(long, short)]
         quiet: bool,
     },
 }
 
+/// Print generation stats and diagnostics to stderr.
+fn report_stats(stats: &ocsf_proto_gen::codegen::GenerationStats) {
+    eprintln!(
+        "Generated {} classes, {} objects, {} fields, {} enums",
+        stats.classes_generated,
+        stats.objects_generated,
+        stats.fields_generated,
+        stats.enums_generated
+    );
+    if stats.deprecated_fields_skipped > 0 {
+        eprintln!(
+            "Skipped {} deprecated fields",
+            stats.deprecated_fields_skipped
+        );
+    }
+    if stats.deprecated_fields_reserved > 0 {
+        eprintln!(
+            "Reserved {} deprecated field numbers",
+            stats.deprecated_fields_reserved
+        );
+    }
+    if stats.deprecated_fields_emitted > 0 {
+        eprintln!(
+            "Emitted {} deprecated fields marked [deprecated = true]",
+            stats.deprecated_fields_emitted
+        );
+    }
+    if stats.requirement_filtered > 0 {
+        eprintln!(
+            "Left out {} attributes below the requirement threshold",
+            stats.requirement_filtered
+        );
+    }
+    if stats.profile_filtered > 0 {
+        eprintln!(
+            "Left out {} attributes outside the included profiles",
+            stats.profile_filtered
+        );
+    }
+    if stats.attributes_excluded > 0 {
+        eprintln!("Excluded {} attributes", stats.attributes_excluded);
+    }
+    if stats.deprecated_messages > 0 {
+        eprintln!(
+            "Marked {} deprecated classes and objects",
+            stats.deprecated_messages
+        );
+    }
+    if stats.empty_objects_skipped > 0 {
+        eprintln!(
+            "Skipped {} objects with no fields (referenced as string)",
+            stats.empty_objects_skipped
+        );
+    }
+    if stats.synthetic_zero_values > 0 {
+        eprintln!(
+            "Added a synthetic 0 value to {} enums without one",
+            stats.synthetic_zero_values
+        );
+    }
+    if stats.string_enum_fields_skipped > 0 {
+        eprintln!(
+            "Skipped {} string-keyed enums (not valid proto enums)",
+            stats.string_enum_fields_skipped
+        );
+    }
+    if !stats.dangling_references.is_empty() {
+        eprintln!(
+            "Emitted {} references to objects that are not generated as string:",
+            stats.dangling_references.len()
+        );
+        for reference in &stats.dangling_references {
+            eprintln!("  {reference}");
+        }
+    }
+    if stats.field_name_collisions_resolved > 0 {
+        eprintln!(
+            "Renamed {} fields whose sanitized names collided",
+            stats.field_name_collisions_resolved
+        );
+    }
+    if stats.unknown_types_defaulted > 0 {
+        eprintln!(
+            "Defaulted {} unknown types to string",
+            stats.unknown_types_defaulted
+        );
+    }
+}
+
+/// Join the generated version directory onto the output directory, refusing
+/// any root that could reach outside it.
+fn output_root(output_dir: &Path, root: &Path) -> ocsf_proto_gen::error::Result<PathBuf> {
+    let inside = root.components().next().is_some()
+        && root
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)));
+    if !inside {
+        return Err(ocsf_proto_gen::error::Error::Codegen(format!(
+            "refusing to remove files outside {}: {}",
+            output_dir.display(),
+            root.display()
+        )));
+    }
+    Ok(output_dir.join(root))
+}
+
+/// Resolve `path` against the current directory without following symlinks,
+/// so the paths `--force` keeps compare equal however they were spelled.
+fn absolute_path(path: &Path) -> ocsf_proto_gen::error::Result<PathBuf> {
+    std::path::absolute(path).map_err(|e| ocsf_proto_gen::error::Error::Read {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+/// `generate --clean`: remove the generated version directory, or under
+/// `--dry-run` log it instead. A symlink is removed, not followed.
+fn clean_output(root: &Path, dry_run: bool) -> ocsf_proto_gen::error::Result<()> {
+    let metadata = match std::fs::symlink_metadata(root) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(ocsf_proto_gen::error::Error::Read {
+                path: root.to_path_buf(),
+                source: e,
+            });
+        }
+    };
+    if dry_run {
+        log::info!("Would remove {}", root.display());
+        return Ok(());
+    }
+    let removed = if metadata.is_dir() {
+        std::fs::remove_dir_all(root)
+    } else {
+        std::fs::remove_file(root)
+    };
+    removed.map_err(|e| ocsf_proto_gen::error::Error::Write {
+        path: root.to_path_buf(),
+        source: e,
+    })
+}
+
+/// `generate --force`: remove every file below `dir` whose absolute path is
+/// not in `keep`, then any directories that leaves empty, returning the
+/// number of files removed. Symlinked directories are removed, not walked.
+fn remove_stale_files(
+    dir: &Path,
+    keep: &std::collections::BTreeSet<PathBuf>,
+    dry_run: bool,
+) -> ocsf_proto_gen::error::Result<usize> {
+    let read_err = |path: &Path, e| ocsf_proto_gen::error::Error::Read {
+        path: path.to_path_buf(),
+        source: e,
+    };
+    let write_err = |path: &Path, e| ocsf_proto_gen::error::Error::Write {
+        path: path.to_path_buf(),
+        source: e,
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(read_err(dir, e)),
+    };
+
+    let mut removed = 0;
+    for entry in entries {
+        let entry = entry.map_err(|e| read_err(dir, e))?;
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(|e| read_err(&path, e))?;
+        if file_type.is_dir() {
+            removed += remove_stale_files(&path, keep, dry_run)?;
+            let empty = std::fs::read_dir(&path)
+                .map_err(|e| read_err(&path, e))?
+                .next()
+                .is_none();
+            if empty && !dry_run {
+                std::fs::remove_dir(&path).map_err(|e| write_err(&path, e))?;
+            }
+        } else if !keep.contains(&absolute_path(&path)?) {
+            if dry_run {
+                log::info!("Would remove {}", path.display());
+            } else {
+                std::fs::remove_file(&path).map_err(|e| write_err(&path, e))?;
+            }
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// `generate --check`: generate in memory without writing, failing on
+/// error-level issues: references to objects missing from the schema,
+/// attributes of unknown type, and lint errors in the generated classes and
+/// objects. Other lint issues are printed as warnings.
+fn check_generation(
+    schema: &ocsf_proto_gen::schema::OcsfSchema,
+    class_names: &[String],
+    opts: &ocsf_proto_gen::codegen::GenerationOptions,
+) -> ocsf_proto_gen::error::Result<ocsf_proto_gen::codegen::GenerationStats> {
+    let stats = ocsf_proto_gen::codegen::check(schema, class_names, opts)?;
+    let objects = ocsf_proto_gen::codegen::resolved_objects(schema, class_names, opts)?;
+    let mut lint_errors = 0;
+    for issue in ocsf_proto_gen::lint::lint_schema(schema) {
+        let generated = match issue.owner_kind {
+            "class" => class_names.contains(&issue.owner),
+            _ => objects.contains(&ocsf_proto_gen::type_map::sanitize_object_name(
+                &issue.owner,
+            )),
+        };
+        // A type override maps the type after all.
+        let overridden = matches!(
+            &issue.kind,
+            ocsf_proto_gen::lint::IssueKind::UnknownType { type_name }
+                if opts.type_overrides.contains_key(type_name)
+        );
+        if !generated || overridden {
+            continue;
+        }
+        if issue.kind.is_error() {
+            eprintln!("error: {issue}");
+            lint_errors += 1;
+        } else {
+            eprintln!("warning: {issue}");
+        }
+    }
+
+    if !stats.dangling_references.is_empty() {
+        report_stats(&stats);
+        return Err(ocsf_proto_gen::error::Error::Codegen(format!(
+            "check failed: {} references to objects that are not generated",
+            stats.dangling_references.len()
+        )));
+    }
+    if stats.unknown_types_defaulted > 0 {
+        report_stats(&stats);
+        return Err(ocsf_proto_gen::error::Error::Codegen(format!(
+            "check failed: {} fields have an unknown type and default to string",
+            stats.unknown_types_defaulted
+        )));
+    }
+    if lint_errors > 0 {
+        report_stats(&stats);
+        return Err(ocsf_proto_gen::error::Error::Codegen(format!(
+            "check failed: {lint_errors} error-level schema issues"
+        )));
+    }
+    Ok(stats)
+}
+
+/// Write a `generate` output file, creating its parent directory, or under
+/// `--dry-run` log its path and size instead, as generation does.
+fn write_output(path: &Path, contents: &str, dry_run: bool) -> ocsf_proto_gen::error::Result<()> {
+    if dry_run {
+        log::info!("Would write {} ({} bytes)", path.display(), contents.len());
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ocsf_proto_gen::error::Error::Write {
+            path: parent.to_path_buf(),
+            source: e,
+        })?;
+    }
+    std::fs::write(path, contents).map_err(|e| ocsf_proto_gen::error::Error::Write {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+/// Parse class names separated by newlines and/or commas, ignoring blank
+/// entries and `#` comments.
+fn parse_class_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split_once('#').map_or(line, |(before, _)| before))
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse a `--type-override` value of the form `OCSF_TYPE=PROTO_TYPE`.
+fn parse_type_override(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((ocsf_type, proto_type)) if !ocsf_type.is_empty() && !proto_type.is_empty() => {
+            Ok((ocsf_type.to_string(), proto_type.to_string()))
+        }
+        _ => Err(format!("expected OCSF_TYPE=PROTO_TYPE, got '{value}'")),
+    }
+}
+
+/// Parse a `--json-field-override` value of the form
+/// `MESSAGE.ATTRIBUTE=PROTO_TYPE`.
+fn parse_json_field_override(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((field, proto_type))
+            if !proto_type.is_empty()
+                && field
+                    .split_once('.')
+                    .is_some_and(|(message, attr)| !message.is_empty() && !attr.is_empty()) =>
+        {
+            Ok((field.to_string(), proto_type.to_string()))
+        }
+        _ => Err(format!(
+            "expected MESSAGE.ATTRIBUTE=PROTO_TYPE, got '{value}'"
+        )),
+    }
+}
+
+/// Parse an `--exclude-attribute` value of the form `OWNER.ATTRIBUTE`. The
+/// attribute is everything after the last `.`.
+fn parse_exclude_attribute(value: &str) -> Result<(String, String), String> {
+    match value.rsplit_once('.') {
+        Some((owner, attribute)) if !owner.is_empty() && !attribute.is_empty() => {
+            Ok((owner.to_string(), attribute.to_string()))
+        }
+        _ => Err(format!("expected OWNER.ATTRIBUTE, got '{value}'")),
+    }
+}
+
+/// Read a `--classes-file` list.
+fn read_classes_file(path: &Path) -> ocsf_proto_gen::error::Result<Vec<String>> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| ocsf_proto_gen::error::Error::Read {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+    Ok(parse_class_list(&content))
+}
+
+/// Read pinned field numbers from a `.proto` file (imports resolved against
+/// its directory) or a serialized `FileDescriptorSet`.
+#[cfg(feature = "descriptors")]
+fn read_pinned_field_numbers(
+    path: &Path,
+) -> ocsf_proto_gen::error::Result<ocsf_proto_gen::field_numbers::FieldNumbers> {
+    use ocsf_proto_gen::field_numbers;
+
+    if path.extension().is_some_and(|ext| ext == "proto") {
+        let include = path.parent().unwrap_or(Path::new("."));
+        return field_numbers::field_numbers_from_proto(path, &[include]);
+    }
+    let bytes = std::fs::read(path).map_err(|e| ocsf_proto_gen::error::Error::Read {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    field_numbers::field_numbers_from_descriptor_set(&bytes)
+}
+
+/// Read a `field-numbers.lock`, or start an empty lock if there is none.
+fn read_field_numbers_lock(
+    path: &Path,
+) -> ocsf_proto_gen::error::Result<ocsf_proto_gen::field_numbers::FieldNumbers> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => ocsf_proto_gen::field_numbers::parse_field_numbers_lock(&json),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+        Err(e) => Err(ocsf_proto_gen::error::Error::Read {
+            path: path.to_path_buf(),
+            source: e,
+        }),
+    }
+}
+
+/// Format the classes of `schema` sorted by category then name, optionally
+/// only those in `category`: one `name<TAB>uid<TAB>category` line per class,
+/// or a JSON array.
+fn list_classes(
+    schema: &ocsf_proto_gen::schema::OcsfSchema,
+    category: Option<&str>,
+    json: bool,
+) -> String {
+    let mut classes: Vec<&ocsf_proto_gen::schema::OcsfClass> = schema
+        .classes
+        .values()
+        .filter(|cls| category.is_none_or(|category| cls.category == category))
+        .collect();
+    classes.sort_by(|a, b| (&a.category, &a.name).cmp(&(&b.category, &b.name)));
+
+    if json {
+        let entries: Vec<serde_json::Value> = classes
+            .iter()
+            .map(|cls| {
+                serde_json::json!({
+                    "name": cls.name,
+                    "uid": cls.uid,
+                    "category": cls.category,
+                })
+            })
+            .collect();
+        return serde_json::Value::from(entries).to_string();
+    }
+    classes
+        .iter()
+        .map(|cls| format!("{}\t{}\t{}", cls.name, cls.uid, cls.category))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Describe class `name`: one `name<TAB>ocsf type<TAB>proto type<TAB>flags`
+/// line per attribute (flags among `repeated`, `enum`, `deprecated`),
+/// followed by the objects it transitively references.
+fn describe_class(
+    schema: &ocsf_proto_gen::schema::OcsfSchema,
+    name: &str,
+) -> ocsf_proto_gen::error::Result<String> {
+    use std::fmt::Write;
+
+    let Some(cls) = schema.classes.get(name) else {
+        return Err(ocsf_proto_gen::error::Error::ClassNotFound {
+            name: name.to_string(),
+            available: ocsf_proto_gen::schema::available_classes(schema),
+        });
+    };
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{} (uid {}, category {})",
+        cls.name, cls.uid, cls.category
+    )
+    .unwrap();
+    writeln!(out, "\nAttributes:").unwrap();
+    for (attr_name, attr) in &cls.attributes {
+        let proto_type = match ocsf_proto_gen::type_map::ocsf_to_proto_type(&attr.type_name) {
+            Some(proto_type) => proto_type.to_string(),
+            None => format!("message {}", attr.object_type.as_deref().unwrap_or("?")),
+        };
+        let integer_enum = attr
+            .enum_values
+            .as_ref()
+            .is_some_and(ocsf_proto_gen::codegen::is_integer_enum);
+        let flags: Vec<&str> = [
+            (attr.is_array, "repeated"),
+            (integer_enum, "enum"),
+            (attr.deprecated.is_some(), "deprecated"),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .collect();
+        writeln!(
+            out,
+            "  {attr_name}\t{}\t{proto_type}\t{}",
+            attr.type_name,
+            flags.join(",")
+        )
+        .unwrap();
+    }
+    let objects =
+        ocsf_proto_gen::schema::resolve_object_graph(schema, std::slice::from_ref(&cls.name));
+    writeln!(out, "\nObjects ({}):", objects.len()).unwrap();
+    for object in &objects {
+        writeln!(out, "  {object}").unwrap();
+    }
+    Ok(out)
+}
+
+/// Describe the proto type `ocsf_type` maps to, noting object references
+/// and unknown types that fall back to `string`.
+fn describe_type(ocsf_type: &str) -> String {
+    use ocsf_proto_gen::type_map::{known_scalar_type, ocsf_to_proto_type};
+
+    match ocsf_to_proto_type(ocsf_type) {
+        None => "message (reference to the attribute's object_type)".to_string(),
+        Some(proto) if known_scalar_type(ocsf_type).is_none() => {
+            format!("{proto} (unknown type, defaulted)")
+        }
+        Some(proto) => proto.to_string(),
+    }
+}
+
+/// Expand `all` to every class in the schema and `category:<name>` to the
+/// classes of that category (by name), then drop duplicates, keeping the
+/// first occurrence of each name.
+///
+/// A `category:` selector that matches no class is an error.
+fn resolve_class_names(
+    requested: Vec<String>,
+    schema: &ocsf_proto_gen::schema::OcsfSchema,
+) -> ocsf_proto_gen::error::Result<Vec<String>> {
+    if requested.iter().any(|name| name == "all") {
+        return Ok(schema.classes.keys().cloned().collect());
+    }
+    let mut expanded = Vec::new();
+    for name in requested {
+        let Some(category) = name.strip_prefix("category:") else {
+            expanded.push(name);
+            continue;
+        };
+        let before = expanded.len();
+        expanded.extend(
+            schema
+                .classes
+                .iter()
+                .filter(|(_, cls)| cls.category == category)
+                .map(|(class_name, _)| class_name.clone()),
+        );
+        if expanded.len() == before {
+            let categories: std::collections::BTreeSet<&str> = schema
+                .classes
+                .values()
+                .map(|cls| cls.category.as_str())
+                .collect();
+            return Err(ocsf_proto_gen::error::Error::Codegen(format!(
+                "selector '{name}' matches no classes (categories: {})",
+                categories.into_iter().collect::<Vec<_>>().join(", ")
+            )));
+        }
+    }
+    let mut seen = std::collections::BTreeSet::new();
+    Ok(expanded
+        .into_iter()
+        .filter(|name| seen.insert(name.clone()))
+        .collect())
+}
+
+/// Route library log records to stderr: `info` as plain progress lines,
+/// other levels prefixed (`warning: ...`). `RUST_LOG` overrides the default
+/// `info` filter.
+fn init_logger() {
+    use std::io::Write;
+
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format(|buf, record| match record.level() {
+            log::Level::Info => writeln!(buf, "{}", record.args()),
+            log::Level::Warn => writeln!(buf, "warning: {}", record.args()),
+            level => writeln!(buf, "{}: {}", level.as_str().to_lowercase(), record.args()),
+        })
+        .init();
+}
+
 fn main() {
     let cli = Cli::parse();
+    init_logger();
 
     if let Err(e) = run(cli) {
         eprintln!("error: {e}");
@@ -88,29 +1069,250 @@ fn run(cli: Cli) -> ocsf_proto_gen::error::Result<()> {
             ocsf_version,
             output_dir,
             schema_url,
+            retries,
+            sha256,
         } => {
             let path = output_dir.join(&ocsf_version).join("schema.json");
             let rt = tokio::runtime::Runtime::new()
                 .map_err(|e| ocsf_proto_gen::error::Error::Schema(e.to_string()))?;
-            rt.block_on(ocsf_proto_gen::schema::download_schema(
+            let opts = ocsf_proto_gen::schema::DownloadOptions {
+                retry: ocsf_proto_gen::schema::RetryPolicy {
+                    retries,
+                    ..Default::default()
+                },
+                expected_sha256: sha256,
+            };
+            rt.block_on(ocsf_proto_gen::schema::download_schema_with_options(
                 &ocsf_version,
                 &path,
                 &schema_url,
+                &opts,
             ))?;
         }
 
+        #[cfg(feature = "download")]
+        Commands::DownloadSchemas {
+            versions,
+            output_dir,
+            schema_url,
+            max_concurrent,
+        } => {
+            let versions: Vec<String> = versions
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            let rt = tokio::runtime::Runtime::new()
+                .map_err(|e| ocsf_proto_gen::error::Error::Schema(e.to_string()))?;
+            rt.block_on(ocsf_proto_gen::schema::download_schemas(
+                &versions,
+                &output_dir,
+                &schema_url,
+                max_concurrent,
+            ))?;
+        }
+
+        Commands::DiffEnums {
+            old,
+            new,
+            version_old,
+            version_new,
+            output,
+        } => {
+            let old_schema =
+                ocsf_proto_gen::schema::load_schema(&old.join(&version_old).join("schema.json"))?;
+            let new_schema =
+                ocsf_proto_gen::schema::load_schema(&new.join(&version_new).join("schema.json"))?;
+            let diff = ocsf_proto_gen::diff::diff_enums(&old_schema, &new_schema);
+            let json = ocsf_proto_gen::diff::enum_diff_json(&diff)?;
+            std::fs::write(&output, json).map_err(|e| ocsf_proto_gen::error::Error::Write {
+                path: output.clone(),
+                source: e,
+            })?;
+            eprintln!(
+                "{} enums changed between v{version_old} and v{version_new}; wrote {}",
+                diff.len(),
+                output.display()
+            );
+        }
+
+        Commands::Diff {
+            old,
+            new,
+            version_old,
+            version_new,
+            json,
+        } => {
+            let old_schema =
+                ocsf_proto_gen::schema::load_schema(&old.join(&version_old).join("schema.json"))?;
+            let new_schema =
+                ocsf_proto_gen::schema::load_schema(&new.join(&version_new).join("schema.json"))?;
+            let diff = ocsf_proto_gen::diff::diff_schemas(&old_schema, &new_schema);
+            if json {
+                println!("{}", ocsf_proto_gen::diff::schema_diff_json(&diff)?);
+            } else if diff.is_empty() {
+                eprintln!("No differences between v{version_old} and v{version_new}");
+            } else {
+                print!("{diff}");
+            }
+        }
+
+        Commands::MapType { ocsf_type } => {
+            println!("{}", describe_type(&ocsf_type));
+        }
+
+        Commands::DescribeClass {
+            name,
+            ocsf_version,
+            schema_dir,
+        } => {
+            let schema = ocsf_proto_gen::schema::load_schema(
+                &schema_dir.join(&ocsf_version).join("schema.json"),
+            )?;
+            print!("{}", describe_class(&schema, &name)?);
+        }
+
+        Commands::ValidateSchema {
+            ocsf_version,
+            schema_dir,
+            no_time_check,
+            time_check_allow,
+        } => {
+            let schema = ocsf_proto_gen::schema::load_schema(
+                &schema_dir.join(&ocsf_version).join("schema.json"),
+            )?;
+            let options = ocsf_proto_gen::lint::LintOptions {
+                skip_time_check: no_time_check,
+                time_check_allow: time_check_allow.into_iter().collect(),
+            };
+            let issues = ocsf_proto_gen::lint::lint_schema_with(&schema, &options);
+            for issue in &issues {
+                println!("{issue}");
+            }
+            if !issues.is_empty() {
+                return Err(ocsf_proto_gen::error::Error::Codegen(format!(
+                    "{} schema issues found in OCSF v{}",
+                    issues.len(),
+                    schema.version
+                )));
+            }
+            eprintln!("No schema issues found in OCSF v{}", schema.version);
+        }
+
+        Commands::ListClasses {
+            ocsf_version,
+            schema_dir,
+            category,
+            json,
+        } => {
+            let schema = ocsf_proto_gen::schema::load_schema(
+                &schema_dir.join(&ocsf_version).join("schema.json"),
+            )?;
+            let listing = list_classes(&schema, category.as_deref(), json);
+            if !listing.is_empty() {
+                println!("{listing}");
+            }
+        }
+
         Commands::Generate {
             ocsf_version,
             classes,
+            classes_file,
             output_dir,
             schema_dir,
+            normalize,
+            only_profiles,
+            #[cfg(feature = "validate-meta")]
+            meta_schema,
+            enum_map_filter,
+            enum_map_skip_objects,
+            enum_map_by_number,
+            presence_caveats,
+            emit_buf,
+            mark_file_deprecated,
+            no_empty_messages,
+            strict,
+            caption_field_names,
+            schema_field_order,
+            extension_packages,
+            todo_markers,
+            profile_options,
+            uid_options,
+            category_enum,
+            event_envelope,
+            packed_repeated,
+            synthetic_zero_caption,
+            well_known_timestamps,
+            struct_json,
+            reserve_deprecated,
+            emit_deprecated,
+            descriptions,
+            enum_descriptions,
+            observable_comments,
+            reserve_enum_gaps,
+            min_requirement,
+            exclude_attribute,
+            group_siblings,
+            package_prefix,
+            slug_style,
+            int32_enums,
+            type_override,
+            extra_import,
+            json_field_override,
+            infer_maps,
+            map_attribute,
+            with_pgv,
+            per_enum_files,
+            #[cfg(feature = "descriptors")]
+            pin_field_numbers,
+            lock_field_numbers,
+            standalone,
+            check,
+            dry_run,
+            count_only,
+            clean,
+            force,
+            verify,
+            single_file,
+            emit_graph,
+            report_cycles,
+            #[cfg(feature = "jsonschema")]
+            json_schema,
+            #[cfg(feature = "rustgen")]
+            emit_rust,
+            #[cfg(feature = "archive")]
+            archive,
             quiet,
         } => {
+            // Read the class list first so a bad --classes-file fails before
+            // the schema is loaded.
+            let mut requested = classes.as_deref().map(parse_class_list).unwrap_or_default();
+            if let Some(path) = &classes_file {
+                requested.extend(read_classes_file(path)?);
+            }
+
             let schema_path = schema_dir.join(&ocsf_version).join("schema.json");
             if !quiet {
                 eprintln!("Loading schema from {}", schema_path.display());
             }
-            let schema = ocsf_proto_gen::schema::load_schema(&schema_path)?;
+            #[cfg(feature = "validate-meta")]
+            if let Some(meta_schema) = &meta_schema {
+                ocsf_proto_gen::schema::validate_against_meta(&schema_path, meta_schema)?;
+            }
+            let mut schema = ocsf_proto_gen::schema::load_schema(&schema_path)?;
+            if normalize {
+                let report = ocsf_proto_gen::schema::normalize(&mut schema);
+                if !quiet {
+                    eprintln!(
+                        "Normalized schema: {} captions trimmed, {} empty enums removed, \
+                         {} empty objects dropped, {} type names lowercased",
+                        report.captions_trimmed,
+                        report.empty_enums_removed,
+                        report.empty_objects_dropped.len(),
+                        report.type_names_lowercased
+                    );
+                }
+            }
             if !quiet {
                 eprintln!(
                     "Loaded OCSF v{}: {} classes, {} objects",
@@ -120,45 +1322,573 @@ fn run(cli: Cli) -> ocsf_proto_gen::error::Result<()> {
                 );
             }
 
-            let class_names: Vec<String> = if classes == "all" {
-                schema.classes.keys().cloned().collect()
-            } else {
-                classes.split(',').map(|s| s.trim().to_string()).collect()
-            };
+            let class_names = resolve_class_names(requested, &schema)?;
 
             if !quiet {
                 eprintln!("Generating protos for {} classes", class_names.len());
             }
 
-            let stats = ocsf_proto_gen::codegen::generate(&schema, &class_names, &output_dir)?;
+            #[cfg(feature = "descriptors")]
+            let pinned_field_numbers = match &pin_field_numbers {
+                Some(path) => {
+                    let pinned = read_pinned_field_numbers(path)?;
+                    if !quiet {
+                        eprintln!(
+                            "Pinned {} field numbers from {}",
+                            pinned.len(),
+                            path.display()
+                        );
+                    }
+                    pinned
+                }
+                None => Default::default(),
+            };
+            let field_numbers_lock = if lock_field_numbers {
+                let path = output_dir.join(ocsf_proto_gen::field_numbers::FIELD_NUMBERS_LOCK);
+                Some(read_field_numbers_lock(&path)?)
+            } else {
+                None
+            };
+            let mut exclude_attributes: std::collections::BTreeMap<
+                _,
+                std::collections::BTreeSet<_>,
+            > = Default::default();
+            for (owner, attribute) in exclude_attribute {
+                exclude_attributes
+                    .entry(owner)
+                    .or_default()
+                    .insert(attribute);
+            }
+            let map_attribute_names = if map_attribute.is_empty() {
+                ocsf_proto_gen::codegen::GenerationOptions::default().map_attribute_names
+            } else {
+                map_attribute.into_iter().collect()
+            };
 
-            if !quiet {
-                eprintln!(
-                    "Generated {} classes, {} objects, {} enums",
-                    stats.classes_generated, stats.objects_generated, stats.enums_generated
-                );
-                if stats.deprecated_fields_skipped > 0 {
-                    eprintln!(
-                        "Skipped {} deprecated fields",
-                        stats.deprecated_fields_skipped
-                    );
+            let opts = ocsf_proto_gen::codegen::GenerationOptions {
+                enum_map_globs: enum_map_filter
+                    .map(|f| f.split(',').map(|s| s.trim().to_string()).collect())
+                    .unwrap_or_default(),
+                enum_map_include_objects: !enum_map_skip_objects,
+                emit_enum_map_by_number: enum_map_by_number,
+                emit_presence_caveats: presence_caveats,
+                emit_buf_config: emit_buf,
+                mark_file_deprecated,
+                reject_empty_messages: no_empty_messages,
+                strict,
+                verify_enum_map: verify,
+                field_name_source: if caption_field_names {
+                    ocsf_proto_gen::codegen::FieldNameSource::Caption
+                } else {
+                    ocsf_proto_gen::codegen::FieldNameSource::AttributeKey
+                },
+                field_order: if schema_field_order {
+                    ocsf_proto_gen::codegen::FieldOrder::SchemaOrder
+                } else {
+                    ocsf_proto_gen::codegen::FieldOrder::Alphabetical
+                },
+                extension_packages,
+                emit_todo_markers: todo_markers,
+                emit_descriptions: descriptions,
+                emit_enum_descriptions: enum_descriptions,
+                emit_observable_comments: observable_comments,
+                reserve_enum_gaps,
+                min_requirement: min_requirement.map(|level| match level.as_str() {
+                    "required" => ocsf_proto_gen::codegen::Requirement::Required,
+                    "recommended" => ocsf_proto_gen::codegen::Requirement::Recommended,
+                    _ => ocsf_proto_gen::codegen::Requirement::Optional,
+                }),
+                include_profiles: only_profiles
+                    .map(|p| p.split(',').map(|s| s.trim().to_string()).collect()),
+                exclude_attributes,
+                group_siblings,
+                package_prefix,
+                slug_style: if slug_style == "no-prefix" {
+                    ocsf_proto_gen::codegen::SlugStyle::NoPrefix
+                } else {
+                    ocsf_proto_gen::codegen::SlugStyle::Default
+                },
+                enum_mode: if int32_enums {
+                    ocsf_proto_gen::codegen::EnumMode::Int32WithComment
+                } else {
+                    ocsf_proto_gen::codegen::EnumMode::Generate
+                },
+                type_overrides: type_override.into_iter().collect(),
+                extra_imports: extra_import,
+                json_field_overrides: json_field_override.into_iter().collect(),
+                infer_maps,
+                map_attribute_names,
+                layout: if single_file {
+                    ocsf_proto_gen::codegen::OutputLayout::SingleFile
+                } else {
+                    ocsf_proto_gen::codegen::OutputLayout::Split
+                },
+                emit_profile_options: profile_options,
+                emit_uid_options: uid_options,
+                emit_category_enum: category_enum,
+                emit_event_envelope: event_envelope,
+                packed_repeated,
+                emit_pgv_rules: with_pgv,
+                enum_layout: if per_enum_files {
+                    ocsf_proto_gen::codegen::EnumLayout::PerEnum
+                } else {
+                    ocsf_proto_gen::codegen::EnumLayout::Shared
+                },
+                timestamp_mapping: if well_known_timestamps {
+                    ocsf_proto_gen::codegen::TimestampMapping::WellKnownTimestamp
+                } else {
+                    ocsf_proto_gen::codegen::TimestampMapping::Int64Millis
+                },
+                json_mapping: if struct_json {
+                    ocsf_proto_gen::codegen::JsonMapping::WellKnownStruct
+                } else {
+                    ocsf_proto_gen::codegen::JsonMapping::StringJson
+                },
+                deprecated_handling: if reserve_deprecated {
+                    ocsf_proto_gen::codegen::DeprecatedHandling::Reserve
+                } else if emit_deprecated {
+                    ocsf_proto_gen::codegen::DeprecatedHandling::Emit
+                } else {
+                    ocsf_proto_gen::codegen::DeprecatedHandling::Skip
+                },
+                synthetic_zero_captions: synthetic_zero_caption
+                    .map(|caption| [("*".to_string(), caption)].into())
+                    .unwrap_or_default(),
+                #[cfg(feature = "descriptors")]
+                pinned_field_numbers,
+                field_numbers_lock,
+                dry_run,
+                ..Default::default()
+            };
+
+            if report_cycles {
+                let needed = ocsf_proto_gen::schema::resolve_object_graph(&schema, &class_names);
+                let cycles = ocsf_proto_gen::codegen::detect_cycles(&schema, &needed);
+                for cycle in &cycles {
+                    eprintln!("warning: object reference cycle: {}", cycle.join(" -> "));
                 }
-                if stats.string_enum_fields_skipped > 0 {
-                    eprintln!(
-                        "Skipped {} string-keyed enums (not valid proto enums)",
-                        stats.string_enum_fields_skipped
-                    );
+                if cycles.is_empty() && !quiet {
+                    eprintln!("No object reference cycles");
                 }
-                if stats.unknown_types_defaulted > 0 {
-                    eprintln!(
-                        "Defaulted {} unknown types to string",
-                        stats.unknown_types_defaulted
-                    );
+            }
+
+            if check {
+                let stats = check_generation(&schema, &class_names, &opts)?;
+                if !quiet {
+                    report_stats(&stats);
+                    eprintln!("Check passed; no files written.");
+                }
+                return Ok(());
+            }
+
+            if count_only {
+                let stats = ocsf_proto_gen::codegen::estimate(&schema, &class_names, &opts)?;
+                if !quiet {
+                    report_stats(&stats);
+                    eprintln!("Count only; no files written.");
+                }
+                return Ok(());
+            }
+
+            let version_root = output_root(
+                &output_dir,
+                &ocsf_proto_gen::codegen::version_root(&schema, &opts),
+            )?;
+            if clean {
+                clean_output(&version_root, dry_run)?;
+            }
+            let mut keep = std::collections::BTreeSet::new();
+            if force {
+                for relative in
+                    ocsf_proto_gen::codegen::planned_outputs(&schema, &class_names, &opts)?
+                {
+                    keep.insert(absolute_path(&output_dir.join(relative))?);
+                }
+            }
+
+            if let Some(path) = &emit_graph {
+                let dot = ocsf_proto_gen::codegen::object_graph_dot(&schema, &class_names)?;
+                write_output(path, &dot, dry_run)?;
+                if force {
+                    keep.insert(absolute_path(path)?);
+                }
+                if !quiet && !dry_run {
+                    eprintln!("Wrote object graph to {}", path.display());
+                }
+            }
+
+            #[cfg(feature = "rustgen")]
+            if let Some(path) = &emit_rust {
+                let source = ocsf_proto_gen::codegen::generate_rust(&schema, &class_names, &opts)?;
+                write_output(path, &source, dry_run)?;
+                if force {
+                    keep.insert(absolute_path(path)?);
+                }
+                if !quiet && !dry_run {
+                    eprintln!("Wrote Rust structs to {}", path.display());
+                }
+            }
+
+            if standalone {
+                let [class_name] = class_names.as_slice() else {
+                    return Err(ocsf_proto_gen::error::Error::Codegen(format!(
+                        "--standalone requires exactly one class, got {}",
+                        class_names.len()
+                    )));
+                };
+                let proto =
+                    ocsf_proto_gen::codegen::generate_standalone(&schema, class_name, &opts)?;
+                let file_name = ocsf_proto_gen::type_map::sanitize_object_name(class_name);
+                let path = output_dir.join(format!("{file_name}.proto"));
+                write_output(&path, &proto, dry_run)?;
+                if !quiet && !dry_run {
+                    eprintln!("Wrote standalone {}", path.display());
+                }
+                return Ok(());
+            }
+            #[cfg(feature = "archive")]
+            let stats = match &archive {
+                Some(path) => {
+                    let stats = ocsf_proto_gen::codegen::generate_archive(
+                        &schema,
+                        &class_names,
+                        path,
+                        &opts,
+                    )?;
+                    if !quiet && !dry_run {
+                        eprintln!("Wrote archive {}", path.display());
+                    }
+                    stats
+                }
+                None => ocsf_proto_gen::codegen::generate_with_options(
+                    &schema,
+                    &class_names,
+                    &output_dir,
+                    &opts,
+                )?,
+            };
+            #[cfg(not(feature = "archive"))]
+            let stats = ocsf_proto_gen::codegen::generate_with_options(
+                &schema,
+                &class_names,
+                &output_dir,
+                &opts,
+            )?;
+
+            #[cfg(feature = "jsonschema")]
+            if json_schema {
+                let files =
+                    ocsf_proto_gen::codegen::generate_json_schema(&schema, &class_names, &opts)?;
+                for (relative, json) in &files {
+                    let path = output_dir.join(relative);
+                    write_output(&path, json, dry_run)?;
+                    if force {
+                        keep.insert(absolute_path(&path)?);
+                    }
+                }
+                if !quiet && !dry_run {
+                    eprintln!("Wrote {} JSON Schema documents", files.len());
+                }
+            }
+
+            if force {
+                let removed = remove_stale_files(&version_root, &keep, dry_run)?;
+                if !quiet && !dry_run {
+                    eprintln!("Removed {removed} stale files");
+                }
+            }
+
+            if !quiet {
+                report_stats(&stats);
+                if dry_run {
+                    eprintln!("Dry run; no files written.");
+                } else {
+                    eprintln!("Done.");
                 }
-                eprintln!("Done.");
             }
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_fails_on_missing_objects_and_writes_nothing() {
+        let schema_json = |object_type: &str| {
+            format!(
+                r#"{{
+                    "version": "1.7.0",
+                    "classes": {{
+                        "authentication": {{
+                            "name": "authentication",
+                            "uid": 3002,
+                            "caption": "Authentication",
+                            "category": "iam",
+                            "attributes": {{
+                                "user": {{"type": "object_t", "object_type": "{object_type}"}}
+                            }}
+                        }}
+                    }},
+                    "objects": {{
+                        "user": {{
+                            "name": "user",
+                            "caption": "User",
+                            "attributes": {{"name": {{"type": "string_t"}}}}
+                        }}
+                    }}
+                }}"#
+            )
+        };
+        let classes = ["authentication".to_string()];
+        let opts = ocsf_proto_gen::codegen::GenerationOptions::default();
+        let cwd_entries = || std::fs::read_dir(".").unwrap().count();
+        let before = cwd_entries();
+
+        let bad: ocsf_proto_gen::schema::OcsfSchema =
+            serde_json::from_str(&schema_json("account")).unwrap();
+        let err = check_generation(&bad, &classes, &opts).unwrap_err();
+        assert!(err.to_string().contains("1 references to objects"));
+
+        let good: ocsf_proto_gen::schema::OcsfSchema =
+            serde_json::from_str(&schema_json("user")).unwrap();
+        let stats = check_generation(&good, &classes, &opts).unwrap();
+        assert_eq!(stats.objects_generated, 1);
+        assert_eq!(cwd_entries(), before);
+    }
+
+    #[test]
+    fn dry_run_writes_nothing_and_excludes_check() {
+        let dir = std::env::temp_dir().join(format!("ocsf-proto-gen-dry-run-{}", process::id()));
+        let path = dir.join("graph").join("objects.dot");
+        write_output(&path, "digraph {}", true).unwrap();
+        assert!(!dir.exists());
+        write_output(&path, "digraph {}", false).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "digraph {}");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let err = Cli::try_parse_from([
+            "ocsf-proto-gen",
+            "generate",
+            "--classes",
+            "authentication",
+            "--dry-run",
+            "--check",
+        ])
+        .err()
+        .unwrap();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn clean_and_force_stay_inside_the_version_root() {
+        let dir = std::env::temp_dir().join(format!("ocsf-proto-gen-force-{}", process::id()));
+        let root = output_root(&dir, Path::new("ocsf/v1_7_0")).unwrap();
+        assert!(output_root(&dir, Path::new("../ocsf")).is_err());
+        assert!(output_root(&dir, Path::new("/ocsf")).is_err());
+        assert!(output_root(&dir, Path::new("")).is_err());
+
+        let fresh = root.join("events/iam/iam.proto");
+        let stale = root.join("events/findings/findings.proto");
+        let outside = dir.join("field-numbers.lock");
+        for path in [&fresh, &stale, &outside] {
+            write_output(path, "", false).unwrap();
+        }
+        let keep = [absolute_path(&fresh).unwrap()].into();
+
+        assert_eq!(remove_stale_files(&root, &keep, true).unwrap(), 1);
+        assert!(stale.exists());
+        assert_eq!(remove_stale_files(&root, &keep, false).unwrap(), 1);
+        assert!(fresh.exists() && outside.exists());
+        assert!(!root.join("events/findings").exists());
+
+        clean_output(&root, true).unwrap();
+        assert!(fresh.exists());
+        clean_output(&root, false).unwrap();
+        assert!(!root.exists() && outside.exists());
+        clean_output(&root, false).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let err = Cli::try_parse_from([
+            "ocsf-proto-gen",
+            "generate",
+            "--classes",
+            "authentication",
+            "--clean",
+            "--force",
+        ])
+        .err()
+        .unwrap();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn list_classes_sorts_by_category_and_filters() {
+        let schema: ocsf_proto_gen::schema::OcsfSchema = serde_json::from_str(
+            r#"{
+                "version": "1.7.0",
+                "classes": {
+                    "network_activity": {"name": "network_activity", "uid": 4001, "caption": "Network Activity", "category": "network", "attributes": {}},
+                    "authentication": {"name": "authentication", "uid": 3002, "caption": "Authentication", "category": "iam", "attributes": {}},
+                    "account_change": {"name": "account_change", "uid": 3001, "caption": "Account Change", "category": "iam", "attributes": {}}
+                },
+                "objects": {}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            list_classes(&schema, None, false),
+            "account_change\t3001\tiam\nauthentication\t3002\tiam\nnetwork_activity\t4001\tnetwork"
+        );
+        assert_eq!(
+            list_classes(&schema, Some("network"), false),
+            "network_activity\t4001\tnetwork"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&list_classes(&schema, Some("iam"), true)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"name": "account_change", "uid": 3001, "category": "iam"},
+                {"name": "authentication", "uid": 3002, "category": "iam"},
+            ])
+        );
+        assert_eq!(list_classes(&schema, Some("findings"), true), "[]");
+    }
+
+    #[test]
+    fn category_selectors_expand_to_their_classes() {
+        let schema: ocsf_proto_gen::schema::OcsfSchema = serde_json::from_str(
+            r#"{
+                "version": "1.7.0",
+                "classes": {
+                    "network_activity": {"name": "network_activity", "uid": 4001, "caption": "Network Activity", "category": "network", "attributes": {}},
+                    "authentication": {"name": "authentication", "uid": 3002, "caption": "Authentication", "category": "iam", "attributes": {}},
+                    "account_change": {"name": "account_change", "uid": 3001, "caption": "Account Change", "category": "iam", "attributes": {}}
+                },
+                "objects": {}
+            }"#,
+        )
+        .unwrap();
+
+        let requested = parse_class_list("authentication,category:iam,category:network");
+        assert_eq!(
+            resolve_class_names(requested, &schema).unwrap(),
+            ["authentication", "account_change", "network_activity"]
+        );
+
+        let err = resolve_class_names(parse_class_list("category:findings"), &schema).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "codegen error: selector 'category:findings' matches no classes \
+             (categories: iam, network)"
+        );
+    }
+
+    #[test]
+    fn describe_class_lists_attributes_and_objects() {
+        let schema: ocsf_proto_gen::schema::OcsfSchema = serde_json::from_str(
+            r#"{
+                "version": "1.7.0",
+                "classes": {
+                    "authentication": {
+                        "name": "authentication",
+                        "uid": 3002,
+                        "caption": "Authentication",
+                        "category": "iam",
+                        "attributes": {
+                            "activity_id": {"type": "integer_t", "enum": {"1": {"caption": "Logon"}}},
+                            "user": {"type": "object_t", "object_type": "user"},
+                            "old": {"type": "string_t", "@deprecated": {"message": "Gone.", "since": "1.1.0"}}
+                        }
+                    }
+                },
+                "objects": {
+                    "user": {"name": "user", "caption": "User", "attributes": {
+                        "groups": {"type": "object_t", "object_type": "group", "is_array": true}
+                    }},
+                    "group": {"name": "group", "caption": "Group", "attributes": {
+                        "name": {"type": "string_t"}
+                    }}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            describe_class(&schema, "authentication").unwrap(),
+            "authentication (uid 3002, category iam)\n\
+             \nAttributes:\n\
+             \x20 activity_id\tinteger_t\tint32\tenum\n\
+             \x20 old\tstring_t\tstring\tdeprecated\n\
+             \x20 user\tobject_t\tmessage user\t\n\
+             \nObjects (2):\n\
+             \x20 group\n\
+             \x20 user\n"
+        );
+        assert!(describe_class(&schema, "missing").is_err());
+    }
+
+    #[test]
+    fn map_type_describes_resolved_type() {
+        assert_eq!(describe_type("timestamp_t"), "int64");
+        assert_eq!(describe_type("ip_t"), "string");
+        assert_eq!(
+            describe_type("mystery_t"),
+            "string (unknown type, defaulted)"
+        );
+        assert!(describe_type("object_t").starts_with("message"));
+    }
+
+    #[test]
+    fn classes_file_merges_with_classes_flag() {
+        let path =
+            std::env::temp_dir().join(format!("ocsf-proto-gen-classes-{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "# Curated IAM classes\n\
+             authentication\n\
+             \n\
+             account_change, group_management  # both IAM\n\
+             network_activity\n",
+        )
+        .unwrap();
+
+        let schema: ocsf_proto_gen::schema::OcsfSchema =
+            serde_json::from_str(r#"{"version": "1.7.0", "classes": {}, "objects": {}}"#).unwrap();
+        let mut requested = parse_class_list("network_activity,http_activity");
+        requested.extend(read_classes_file(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            resolve_class_names(requested, &schema).unwrap(),
+            [
+                "network_activity",
+                "http_activity",
+                "authentication",
+                "account_change",
+                "group_management",
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_requires_classes_or_classes_file() {
+        let err = Cli::try_parse_from(["ocsf-proto-gen", "generate", "--schema-dir", "schemas"])
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+        assert!(err.to_string().contains("--classes"));
+
+        let path = std::env::temp_dir().join("ocsf-proto-gen-missing-classes.txt");
+        let err = read_classes_file(&path).unwrap_err();
+        assert!(matches!(
+            err,
+            ocsf_proto_gen::error::Error::Read { path: p, .. } if p == path
+        ));
+    }
+}
@@ -5,12 +5,13 @@
 //! classes and objects with inheritance fully resolved, eliminating the need to
 //! implement OCSF's `extends` + `$include` + profile merging logic.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 
 use serde::Deserialize;
 
 use crate::error::{Error, Result};
+use crate::type_map::sanitize_object_name;
 
 /// The full OCSF schema export from `schema.ocsf.io/export/schema`.
 ///
@@ -76,8 +77,14 @@ pub struct OcsfClass {
     #[serde(default)]
     pub profiles: Vec<String>,
 
-    /// Fully-resolved attributes keyed by name. Sorted by `BTreeMap`.
+    /// Fully-resolved attributes keyed by name. Sorted by `BTreeMap`; the
+    /// export's order is kept in [`OcsfAttribute::position`].
+    #[serde(deserialize_with = "attributes_in_order")]
     pub attributes: BTreeMap<String, OcsfAttribute>,
+
+    /// Deprecation information for the whole class.
+    #[serde(rename = "@deprecated", default)]
+    pub deprecated: Option<OcsfDeprecated>,
 }
 
 /// An OCSF object type (e.g., User, Network Endpoint).
@@ -97,12 +104,18 @@ pub struct OcsfObject {
     #[serde(default)]
     pub extends: Option<String>,
 
-    /// Object attributes keyed by name. Sorted by `BTreeMap`.
+    /// Object attributes keyed by name. Sorted by `BTreeMap`; the export's
+    /// order is kept in [`OcsfAttribute::position`].
+    #[serde(deserialize_with = "attributes_in_order")]
     pub attributes: BTreeMap<String, OcsfAttribute>,
 
     /// Observable type number (e.g., `20` for Endpoint, `21` for User).
     #[serde(default)]
     pub observable: Option<u32>,
+
+    /// Deprecation information for the whole object.
+    #[serde(rename = "@deprecated", default)]
+    pub deprecated: Option<OcsfDeprecated>,
 }
 
 /// A single attribute in an event class or object.
@@ -154,6 +167,47 @@ pub struct OcsfAttribute {
     /// Deprecation information.
     #[serde(rename = "@deprecated", default)]
     pub deprecated: Option<OcsfDeprecated>,
+
+    /// Index of the attribute in its class's or object's `attributes`
+    /// object in the export, from 0. Set when the schema is deserialized;
+    /// attributes built in code default to 0.
+    #[serde(skip)]
+    pub position: usize,
+}
+
+/// Deserialize an `attributes` object, recording each attribute's
+/// [`OcsfAttribute::position`] in the order it appears.
+fn attributes_in_order<'de, D>(
+    deserializer: D,
+) -> std::result::Result<BTreeMap<String, OcsfAttribute>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct AttributesVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for AttributesVisitor {
+        type Value = BTreeMap<String, OcsfAttribute>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a map of attributes")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            let mut attributes = BTreeMap::new();
+            let mut position = 0;
+            while let Some((name, mut attr)) = map.next_entry::<String, OcsfAttribute>()? {
+                attr.position = position;
+                position += 1;
+                attributes.insert(name, attr);
+            }
+            Ok(attributes)
+        }
+    }
+
+    deserializer.deserialize_map(AttributesVisitor)
 }
 
 /// A single value in an OCSF enum definition.
@@ -173,7 +227,7 @@ pub struct OcsfDeprecated {
     /// Deprecation message (e.g., `"Use the ancestry attribute instead."`).
     pub message: String,
 
-    /// OCSF version since which the attribute is deprecated.
+    /// OCSF version since which the attribute, class, or object is deprecated.
     #[serde(default)]
     pub since: String,
 }
@@ -190,19 +244,426 @@ pub fn load_schema(path: &Path) -> Result<OcsfSchema> {
     Ok(schema)
 }
 
-/// Download the OCSF schema export and save to disk.
+/// Validate a schema export against a JSON Schema describing its structure.
+///
+/// Catches malformed exports (wrong types, missing required keys) before
+/// generation turns them into silently broken protos. Every violation is
+/// reported with the JSON pointer of the offending value, e.g.
+/// `/classes/authentication/uid: "3002" is not of type "integer"`.
+///
+/// Requires the `validate-meta` feature.
+#[cfg(feature = "validate-meta")]
+pub fn validate_against_meta(path: &Path, meta_schema_path: &Path) -> Result<()> {
+    let read_json = |path: &Path| -> Result<serde_json::Value> {
+        let content = std::fs::read_to_string(path).map_err(|e| Error::Read {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        Ok(serde_json::from_str(&content)?)
+    };
+    let instance = read_json(path)?;
+    let meta = read_json(meta_schema_path)?;
+
+    let validator = jsonschema::validator_for(&meta).map_err(|e| {
+        Error::MetaSchema(format!(
+            "invalid meta-schema {}: {e}",
+            meta_schema_path.display()
+        ))
+    })?;
+    let violations: Vec<String> = validator
+        .iter_errors(&instance)
+        .map(|e| {
+            let pointer = e.instance_path().to_string();
+            let pointer = if pointer.is_empty() { "/" } else { &pointer };
+            format!("{pointer}: {e}")
+        })
+        .collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::MetaSchema(format!(
+            "{} ({} violation(s)): {}",
+            path.display(),
+            violations.len(),
+            violations.join("; ")
+        )))
+    }
+}
+
+/// Summary of the changes made by [`normalize`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NormalizeReport {
+    /// Number of captions (class, object, attribute, enum value) that had
+    /// leading or trailing whitespace removed.
+    pub captions_trimmed: usize,
+
+    /// Number of attributes whose `enum` map was present but empty.
+    pub empty_enums_removed: usize,
+
+    /// Names of objects dropped because they have no attributes.
+    pub empty_objects_dropped: Vec<String>,
+
+    /// Number of attribute type names that were lowercased.
+    pub type_names_lowercased: usize,
+}
+
+impl NormalizeReport {
+    /// Returns `true` if normalization made no changes.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Clean up common inconsistencies in an OCSF schema export in place.
+///
+/// - Trims whitespace around class, object, attribute, and enum value captions
+/// - Removes empty `enum` maps (an attribute with `"enum": {}` has no values)
+/// - Drops objects that have zero attributes, unless an attribute references
+///   them or they are the base `object` that `unmapped` references
+/// - Lowercases attribute type names (`"String_T"` → `"string_t"`)
+///
+/// The empty objects that are kept still get no message: `object_t`
+/// attributes referencing them are generated as `string`.
+pub fn normalize(schema: &mut OcsfSchema) -> NormalizeReport {
+    let mut report = NormalizeReport::default();
+
+    for cls in schema.classes.values_mut() {
+        trim_caption(&mut cls.caption, &mut report);
+        normalize_attributes(&mut cls.attributes, &mut report);
+    }
+
+    for obj in schema.objects.values_mut() {
+        trim_caption(&mut obj.caption, &mut report);
+        normalize_attributes(&mut obj.attributes, &mut report);
+    }
+
+    let referenced: BTreeSet<String> = schema
+        .classes
+        .values()
+        .map(|cls| &cls.attributes)
+        .chain(schema.objects.values().map(|obj| &obj.attributes))
+        .flat_map(BTreeMap::values)
+        .filter_map(|attr| attr.object_type.as_deref())
+        .filter_map(|obj_type| lookup_object(schema, obj_type))
+        .map(|obj| obj.name.clone())
+        .collect();
+    schema.objects.retain(|name, obj| {
+        if obj.attributes.is_empty()
+            && name != crate::codegen::BASE_OBJECT
+            && !referenced.contains(&obj.name)
+        {
+            report.empty_objects_dropped.push(name.clone());
+            false
+        } else {
+            true
+        }
+    });
+
+    report
+}
+
+/// Callbacks for [`visit`]. Every method defaults to doing nothing.
+pub trait SchemaVisitor {
+    /// Called once per requested class, before its attributes.
+    fn visit_class(&mut self, _class: &OcsfClass) {}
+
+    /// Called once per object the requested classes reference, directly or
+    /// transitively, before its attributes. `name` has any extension prefix
+    /// stripped (e.g., `win_service`).
+    fn visit_object(&mut self, _name: &str, _object: &OcsfObject) {}
+
+    /// Called for each attribute of a class or object, including deprecated
+    /// ones. `owner` is the class or object name.
+    fn visit_attribute(&mut self, _owner: &str, _name: &str, _attribute: &OcsfAttribute) {}
+
+    /// Called for each value of an attribute's `enum` map, after
+    /// [`SchemaVisitor::visit_attribute`] for that attribute.
+    fn visit_enum_value(
+        &mut self,
+        _owner: &str,
+        _attribute: &str,
+        _value: &str,
+        _enum_value: &OcsfEnumValue,
+    ) {
+    }
+}
+
+/// Walk the requested classes and every object they reference, invoking
+/// `visitor` in a deterministic order: classes by name, then objects by
+/// name, each followed by its attributes and their enum values in key order.
+///
+/// Objects are those of [`resolve_object_graph`]: every object reachable
+/// through any attribute, whatever generation options would later apply.
+/// That includes empty objects and the base `object`, and objects reached
+/// only through excluded, profile-filtered or below-minimum attributes. For
+/// the messages generation actually emits, use
+/// [`crate::codegen::resolved_objects`].
+pub fn visit<V: SchemaVisitor>(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    visitor: &mut V,
+) -> Result<()> {
+    let mut classes = Vec::new();
+    for name in class_names {
+        let cls = schema
+            .classes
+            .get(name.as_str())
+            .ok_or_else(|| Error::ClassNotFound {
+                name: name.clone(),
+                available: available_classes(schema),
+            })?;
+        classes.push(cls);
+    }
+    classes.sort_by(|a, b| a.name.cmp(&b.name));
+    classes.dedup_by(|a, b| a.name == b.name);
+
+    for cls in classes {
+        visitor.visit_class(cls);
+        visit_attributes(&cls.name, &cls.attributes, visitor);
+    }
+    for name in resolve_object_graph(schema, class_names) {
+        if let Some(obj) = lookup_object(schema, &name) {
+            visitor.visit_object(&name, obj);
+            visit_attributes(&name, &obj.attributes, visitor);
+        }
+    }
+    Ok(())
+}
+
+fn visit_attributes<V: SchemaVisitor>(
+    owner: &str,
+    attributes: &BTreeMap<String, OcsfAttribute>,
+    visitor: &mut V,
+) {
+    for (attr_name, attr) in attributes {
+        visitor.visit_attribute(owner, attr_name, attr);
+        for (value, enum_value) in attr.enum_values.iter().flatten() {
+            visitor.visit_enum_value(owner, attr_name, value, enum_value);
+        }
+    }
+}
+
+/// Compute the transitive closure of all objects referenced by the requested
+/// event classes via BFS.
+///
+/// Starting from objects directly referenced by event class attributes,
+/// follows `object_type` references recursively until no new objects are
+/// found. Returns sanitized object names (extension prefixes stripped).
+///
+/// This is the raw closure over every attribute, unknown classes ignored.
+/// Generation filters it further; [`crate::codegen::resolved_objects`] gives
+/// exactly the objects a run would emit.
+pub fn resolve_object_graph(schema: &OcsfSchema, class_names: &[String]) -> BTreeSet<String> {
+    resolve_object_graph_with(schema, class_names, |_, _, _| true)
+}
+
+/// [`resolve_object_graph`], following only the attributes `keep` accepts.
+/// `keep` gets the owner (a class name or sanitized object name), the
+/// attribute name, and the attribute.
+pub(crate) fn resolve_object_graph_with(
+    schema: &OcsfSchema,
+    class_names: &[String],
+    keep: impl Fn(&str, &str, &OcsfAttribute) -> bool,
+) -> BTreeSet<String> {
+    let mut needed: BTreeSet<String> = BTreeSet::new();
+    let mut queue: Vec<String> = Vec::new();
+
+    // Seed with objects directly referenced by requested classes.
+    for name in class_names {
+        if let Some(cls) = schema.classes.get(name.as_str()) {
+            let kept = cls
+                .attributes
+                .iter()
+                .filter(|(attr_name, attr)| keep(name, attr_name, attr));
+            for (_, attr) in kept {
+                if let Some(obj_type) = &attr.object_type {
+                    let key = sanitize_object_name(obj_type);
+                    if needed.insert(key.clone()) {
+                        queue.push(obj_type.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    // BFS: follow object → object references.
+    while let Some(obj_ref) = queue.pop() {
+        if let Some(obj) = lookup_object(schema, &obj_ref) {
+            let owner = sanitize_object_name(&obj_ref);
+            let kept = obj
+                .attributes
+                .iter()
+                .filter(|(attr_name, attr)| keep(&owner, attr_name, attr));
+            for (_, attr) in kept {
+                if let Some(obj_type) = &attr.object_type {
+                    let key = sanitize_object_name(obj_type);
+                    if needed.insert(key.clone()) {
+                        queue.push(obj_type.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    needed
+}
+
+/// Look up an object by name, handling extension-prefixed names.
+///
+/// OCSF extension objects use path-prefixed names (e.g., `"win/win_service"`).
+/// This function tries the original name first, then the sanitized name,
+/// then searches all objects by sanitized name comparison.
+pub fn lookup_object<'a>(schema: &'a OcsfSchema, name: &str) -> Option<&'a OcsfObject> {
+    schema.objects.get(name).or_else(|| {
+        let sanitized = sanitize_object_name(name);
+        schema.objects.get(&sanitized).or_else(|| {
+            schema
+                .objects
+                .values()
+                .find(|o| sanitize_object_name(&o.name) == sanitized)
+        })
+    })
+}
+
+/// List the schema's class names for a [`Error::ClassNotFound`] message,
+/// truncated after the first 10.
+pub fn available_classes(schema: &OcsfSchema) -> String {
+    let available: Vec<&str> = schema.classes.keys().map(|s| s.as_str()).collect();
+    if available.len() > 10 {
+        format!(
+            "{} ... and {} more",
+            available[..10].join(", "),
+            available.len() - 10
+        )
+    } else {
+        available.join(", ")
+    }
+}
+
+fn normalize_attributes(
+    attributes: &mut BTreeMap<String, OcsfAttribute>,
+    report: &mut NormalizeReport,
+) {
+    for attr in attributes.values_mut() {
+        trim_caption(&mut attr.caption, report);
+
+        if attr.type_name.chars().any(|c| c.is_uppercase()) {
+            attr.type_name = attr.type_name.to_lowercase();
+            report.type_names_lowercased += 1;
+        }
+
+        if attr.enum_values.as_ref().is_some_and(|e| e.is_empty()) {
+            attr.enum_values = None;
+            report.empty_enums_removed += 1;
+        }
+
+        if let Some(enum_vals) = &mut attr.enum_values {
+            for val in enum_vals.values_mut() {
+                trim_caption(&mut val.caption, report);
+            }
+        }
+    }
+}
+
+fn trim_caption(caption: &mut String, report: &mut NormalizeReport) {
+    let trimmed = caption.trim();
+    if trimmed.len() != caption.len() {
+        *caption = trimmed.to_string();
+        report.captions_trimmed += 1;
+    }
+}
+
+/// How schema downloads retry transient failures: connection errors,
+/// timeouts, and `5xx` responses. `4xx` responses are never retried.
+#[cfg(feature = "download")]
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts after the first one.
+    pub retries: u32,
+
+    /// Delay before the first retry, doubled before each later one.
+    pub base_delay: std::time::Duration,
+}
+
+#[cfg(feature = "download")]
+impl Default for RetryPolicy {
+    /// Two retries (three attempts), waiting 500ms and then 1s.
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            base_delay: std::time::Duration::from_millis(500),
+        }
+    }
+}
+
+/// Options for [`download_schema_with_options`].
+#[cfg(feature = "download")]
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    /// Retries for transient failures.
+    pub retry: RetryPolicy,
+
+    /// Expected SHA-256 of the schema export, as hex. A download that does
+    /// not match fails with [`Error::Download`] and is not written.
+    pub expected_sha256: Option<String>,
+}
+
+/// Download the OCSF schema export and save to disk, retrying transient
+/// failures under [`RetryPolicy::default`].
 ///
 /// Fetches from `{base_url}?version={version}` and validates the response
 /// parses as a valid [`OcsfSchema`] before writing.
+///
+/// The server's `ETag` is stored beside the file (`schema.json.etag`) and
+/// sent back as `If-None-Match` on the next download; a `304 Not Modified`
+/// leaves the existing file untouched. The tag is only sent while the
+/// schema file itself exists.
+///
+/// The SHA-256 of the schema is printed so it can be pinned with
+/// [`DownloadOptions::expected_sha256`].
 #[cfg(feature = "download")]
 pub async fn download_schema(version: &str, output_path: &Path, base_url: &str) -> Result<()> {
+    download_schema_with_options(version, output_path, base_url, &DownloadOptions::default()).await
+}
+
+/// [`download_schema`] with explicit [`DownloadOptions`].
+#[cfg(feature = "download")]
+pub async fn download_schema_with_options(
+    version: &str,
+    output_path: &Path,
+    base_url: &str,
+    opts: &DownloadOptions,
+) -> Result<()> {
     let url = format!("{base_url}?version={version}");
-    eprintln!("Downloading OCSF schema v{version} from {url}");
+    log::info!("Downloading OCSF schema v{version} from {url}");
 
-    let response = reqwest::get(&url)
-        .await
-        .map_err(|e| Error::Download(format!("GET {url}: {e}")))?;
+    let etag_path = etag_path(output_path);
+    let cached_etag = if output_path.is_file() {
+        std::fs::read_to_string(&etag_path).ok()
+    } else {
+        None
+    };
+    let mut request = reqwest::Client::new().get(&url);
+    if let Some(etag) = &cached_etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag.trim());
+    }
+    let response = send_with_retry(request, &url, opts.retry).await?;
 
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED && cached_etag.is_some() {
+        if let Some(expected) = &opts.expected_sha256 {
+            let cached = std::fs::read(output_path).map_err(|e| Error::Read {
+                path: output_path.to_path_buf(),
+                source: e,
+            })?;
+            check_sha256(&cached, expected)?;
+        }
+        log::info!(
+            "OCSF schema v{version} unchanged; keeping {}",
+            output_path.display()
+        );
+        return Ok(());
+    }
     if !response.status().is_success() {
         return Err(Error::Download(format!(
             "GET {url} returned {}",
@@ -210,12 +671,21 @@ pub async fn download_schema(version: &str, output_path: &Path, base_url: &str)
         )));
     }
 
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
     let body = response
         .text()
         .await
         .map_err(|e| Error::Download(format!("reading response body: {e}")))?;
 
     // Validate before writing.
+    let sha256 = match &opts.expected_sha256 {
+        Some(expected) => check_sha256(body.as_bytes(), expected)?,
+        None => sha256_hex(body.as_bytes()),
+    };
     let schema: OcsfSchema = serde_json::from_str(&body)
         .map_err(|e| Error::Schema(format!("downloaded schema is not valid OCSF JSON: {e}")))?;
 
@@ -230,17 +700,135 @@ pub async fn download_schema(version: &str, output_path: &Path, base_url: &str)
         path: output_path.to_path_buf(),
         source: e,
     })?;
+    match etag {
+        Some(etag) => std::fs::write(&etag_path, etag).map_err(|e| Error::Write {
+            path: etag_path.clone(),
+            source: e,
+        })?,
+        // A tag left from an earlier download no longer describes the file.
+        None => {
+            let _ = std::fs::remove_file(&etag_path);
+        }
+    }
 
-    eprintln!(
+    log::info!(
         "Saved OCSF v{} ({} classes, {} objects) to {}",
         schema.version,
         schema.classes.len(),
         schema.objects.len(),
         output_path.display()
     );
+    log::info!("SHA-256: {sha256}");
     Ok(())
 }
 
+/// Lowercase hex SHA-256 of `bytes`.
+#[cfg(feature = "download")]
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::Digest;
+
+    sha2::Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Check `bytes` against the hex SHA-256 `expected` (case-insensitive),
+/// returning the actual hash.
+#[cfg(feature = "download")]
+fn check_sha256(bytes: &[u8], expected: &str) -> Result<String> {
+    let actual = sha256_hex(bytes);
+    let expected = expected.trim().to_ascii_lowercase();
+    if actual != expected {
+        return Err(Error::Download(format!(
+            "SHA-256 mismatch: expected {expected}, got {actual}"
+        )));
+    }
+    Ok(actual)
+}
+
+/// Send `request`, retrying connection errors, timeouts, and `5xx`
+/// responses under `retry`. Any other response is returned as is.
+#[cfg(feature = "download")]
+async fn send_with_retry(
+    request: reqwest::RequestBuilder,
+    url: &str,
+    retry: RetryPolicy,
+) -> Result<reqwest::Response> {
+    let mut delay = retry.base_delay;
+    let mut attempt = 1;
+    loop {
+        let attempt_request = request
+            .try_clone()
+            .ok_or_else(|| Error::Download(format!("GET {url}: request cannot be retried")))?;
+        let failure = match attempt_request.send().await {
+            Ok(response) if !response.status().is_server_error() => return Ok(response),
+            Ok(response) => format!("returned {}", response.status()),
+            Err(e) if e.is_connect() || e.is_timeout() => format!("failed: {e}"),
+            Err(e) => return Err(Error::Download(format!("GET {url}: {e}"))),
+        };
+        if attempt > retry.retries {
+            let attempts = if attempt == 1 { "attempt" } else { "attempts" };
+            return Err(Error::Download(format!(
+                "GET {url} {failure} after {attempt} {attempts}"
+            )));
+        }
+        log::warn!("GET {url} {failure}; retrying in {}ms", delay.as_millis());
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+/// The file beside `schema_path` holding its `ETag` (`<name>.etag`).
+#[cfg(feature = "download")]
+fn etag_path(schema_path: &Path) -> std::path::PathBuf {
+    let mut path = schema_path.as_os_str().to_owned();
+    path.push(".etag");
+    std::path::PathBuf::from(path)
+}
+
+/// Download several OCSF versions concurrently.
+///
+/// Each version is written to `<output_dir>/<version>/schema.json`, as
+/// [`download_schema`] would. At most `max_concurrent` requests are in
+/// flight at once (a limit of 0 is treated as 1). The first failure is
+/// returned and any outstanding downloads are cancelled.
+#[cfg(feature = "download")]
+pub async fn download_schemas(
+    versions: &[String],
+    output_dir: &Path,
+    base_url: &str,
+    max_concurrent: usize,
+) -> Result<()> {
+    let limit = max_concurrent.max(1);
+    let mut tasks = tokio::task::JoinSet::new();
+
+    for version in versions {
+        while tasks.len() >= limit {
+            join_download(&mut tasks).await?;
+        }
+        let version = version.clone();
+        let path = output_dir.join(&version).join("schema.json");
+        let base_url = base_url.to_string();
+        tasks.spawn(async move { download_schema(&version, &path, &base_url).await });
+    }
+    while !tasks.is_empty() {
+        join_download(&mut tasks).await?;
+    }
+    Ok(())
+}
+
+/// Wait for the next download in `tasks` to finish and surface its result.
+#[cfg(feature = "download")]
+async fn join_download(tasks: &mut tokio::task::JoinSet<Result<()>>) -> Result<()> {
+    match tasks.join_next().await {
+        Some(Ok(result)) => result,
+        Some(Err(e)) => Err(Error::Download(format!("download task failed: {e}"))),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -335,6 +923,31 @@ mod tests {
         assert_eq!(schema.objects.len(), 1);
     }
 
+    #[test]
+    fn attribute_positions_follow_export_order() {
+        let schema: OcsfSchema = serde_json::from_str(&minimal_schema_json()).unwrap();
+        fn positions(attributes: &BTreeMap<String, OcsfAttribute>) -> Vec<&str> {
+            let mut names: Vec<&str> = attributes.keys().map(String::as_str).collect();
+            names.sort_by_key(|name| attributes[*name].position);
+            names
+        }
+
+        assert_eq!(
+            positions(&schema.classes["authentication"].attributes),
+            [
+                "activity_id",
+                "src_endpoint",
+                "message",
+                "severity_id",
+                "time"
+            ]
+        );
+        assert_eq!(
+            positions(&schema.objects["network_endpoint"].attributes),
+            ["ip", "port", "hostname"]
+        );
+    }
+
     #[test]
     fn parse_class_attributes() {
         let schema: OcsfSchema = serde_json::from_str(&minimal_schema_json()).unwrap();
@@ -385,4 +998,103 @@ mod tests {
         assert!(attr.deprecated.is_some());
         assert_eq!(attr.deprecated.as_ref().unwrap().since, "1.4.0");
     }
+
+    #[test]
+    fn parse_deprecated_objects() {
+        let json = r#"{
+            "version": "1.7.0",
+            "classes": {},
+            "objects": {
+                "test": {
+                    "name": "test",
+                    "caption": "Test",
+                    "attributes": {},
+                    "@deprecated": {"message": "Use other instead.", "since": "1.5.0"}
+                }
+            }
+        }"#;
+        let schema: OcsfSchema = serde_json::from_str(json).unwrap();
+        let deprecated = schema.objects["test"].deprecated.as_ref().unwrap();
+        assert_eq!(deprecated.message, "Use other instead.");
+        assert_eq!(deprecated.since, "1.5.0");
+    }
+
+    #[test]
+    fn normalize_cleans_messy_schema() {
+        let json = r#"{
+            "version": "1.7.0",
+            "classes": {
+                "authentication": {
+                    "name": "authentication",
+                    "uid": 3002,
+                    "caption": "  Authentication ",
+                    "attributes": {
+                        "activity_id": {
+                            "type": "Integer_T",
+                            "caption": "Activity ID\n",
+                            "enum": {
+                                "1": {"caption": " Logon"}
+                            }
+                        },
+                        "status": {
+                            "type": "string_t",
+                            "caption": "Status",
+                            "enum": {}
+                        },
+                        "policy": {"type": "object_t", "object_type": "policy"}
+                    }
+                }
+            },
+            "objects": {
+                "object": {
+                    "name": "object",
+                    "caption": "Object",
+                    "attributes": {}
+                },
+                "policy": {
+                    "name": "policy",
+                    "caption": "Policy",
+                    "attributes": {}
+                },
+                "scratch": {
+                    "name": "scratch",
+                    "caption": "Scratch",
+                    "attributes": {}
+                },
+                "user": {
+                    "name": "user",
+                    "caption": "User",
+                    "attributes": {
+                        "name": {"type": "string_t", "caption": "Name"}
+                    }
+                }
+            }
+        }"#;
+        let mut schema: OcsfSchema = serde_json::from_str(json).unwrap();
+        let report = normalize(&mut schema);
+
+        assert_eq!(report.captions_trimmed, 3);
+        assert_eq!(report.empty_enums_removed, 1);
+        // Only the empty object nothing references.
+        assert_eq!(report.empty_objects_dropped, vec!["scratch".to_string()]);
+        assert_eq!(report.type_names_lowercased, 1);
+
+        let auth = &schema.classes["authentication"];
+        assert_eq!(auth.caption, "Authentication");
+        let activity_id = &auth.attributes["activity_id"];
+        assert_eq!(activity_id.caption, "Activity ID");
+        assert_eq!(activity_id.type_name, "integer_t");
+        assert_eq!(
+            activity_id.enum_values.as_ref().unwrap()["1"].caption,
+            "Logon"
+        );
+        assert!(auth.attributes["status"].enum_values.is_none());
+        assert!(schema.objects.contains_key("object"));
+        assert!(schema.objects.contains_key("policy"));
+        assert!(!schema.objects.contains_key("scratch"));
+        assert!(schema.objects.contains_key("user"));
+
+        // A second pass is a no-op.
+        assert!(normalize(&mut schema).is_empty());
+    }
 }
@@ -19,6 +19,56 @@
 //! | `object_t` | message ref | — | Handled by codegen module |
 //! | Unknown types | `string` | — | Fallback |
 
+/// Naming conventions for generated proto identifiers.
+///
+/// Every method has a default implementation reproducing the built-in
+/// conventions ([`to_pascal_case`], [`to_screaming_snake`],
+/// [`to_enum_variant_name`]), so custom strategies only override what they
+/// need — e.g., keeping acronyms uppercase (`HTTPRequest` vs `HttpRequest`).
+///
+/// Strategies must be deterministic: the same input must always produce the
+/// same name.
+pub trait NamingStrategy: Send + Sync {
+    /// Proto message name for an OCSF class or object name.
+    ///
+    /// Default: `"network_endpoint"` → `"NetworkEndpoint"`.
+    fn message_name(&self, ocsf_name: &str) -> String {
+        to_pascal_case(ocsf_name)
+    }
+
+    /// Proto enum type name for an enum attribute of a class or object.
+    ///
+    /// Default: (`"authentication"`, `"activity_id"`) → `"AUTHENTICATION_ACTIVITY_ID"`,
+    /// with any extension prefix of the owner stripped
+    /// (`"win/registry_key_activity"` → `"REGISTRY_KEY_ACTIVITY_..."`).
+    fn enum_name(&self, owner: &str, attr_name: &str) -> String {
+        format!(
+            "{}_{}",
+            to_screaming_snake(&sanitize_object_name(owner)),
+            to_screaming_snake(attr_name)
+        )
+    }
+
+    /// Enum variant suffix for an OCSF enum caption.
+    ///
+    /// Default: `"Service Ticket Request"` → `"SERVICE_TICKET_REQUEST"`.
+    fn enum_variant_name(&self, caption: &str) -> String {
+        to_enum_variant_name(caption)
+    }
+}
+
+impl std::fmt::Debug for dyn NamingStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("dyn NamingStrategy")
+    }
+}
+
+/// The built-in naming conventions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultNaming;
+
+impl NamingStrategy for DefaultNaming {}
+
 /// Map an OCSF type name to a proto3 scalar type string.
 ///
 /// Returns `None` for `object_t` — object references must be resolved
@@ -26,6 +76,17 @@
 ///
 /// Returns `"string"` as a fallback for unrecognized types.
 pub fn ocsf_to_proto_type(type_name: &str) -> Option<&'static str> {
+    if type_name == "object_t" {
+        return None;
+    }
+    Some(known_scalar_type(type_name).unwrap_or("string"))
+}
+
+/// Map a recognized OCSF scalar type name to its proto3 scalar type.
+///
+/// Unlike [`ocsf_to_proto_type`], returns `None` for unrecognized types
+/// (and for `object_t`) instead of falling back to `"string"`.
+pub fn known_scalar_type(type_name: &str) -> Option<&'static str> {
     let proto = match type_name {
         // String family — all string-like OCSF types (base type: string_t).
         "string_t" | "hostname_t" | "ip_t" | "mac_t" | "url_t" | "email_t" | "file_path_t"
@@ -50,11 +111,8 @@ pub fn ocsf_to_proto_type(type_name: &str) -> Option<&'static str> {
         // Boolean.
         "boolean_t" => "bool",
 
-        // Object references — the caller must handle these.
-        "object_t" => return None,
-
-        // Fallback: unknown types emit as string.
-        _ => "string",
+        // Object references and unknown types.
+        _ => return None,
     };
     Some(proto)
 }
@@ -93,6 +151,86 @@ pub fn sanitize_object_name(s: &str) -> String {
     s.rsplit('/').next().unwrap_or(s).to_string()
 }
 
+/// Proto3 keywords, including scalar type names and literals.
+pub const PROTO_KEYWORDS: &[&str] = &[
+    "syntax",
+    "edition",
+    "import",
+    "weak",
+    "public",
+    "package",
+    "option",
+    "message",
+    "enum",
+    "service",
+    "rpc",
+    "returns",
+    "stream",
+    "oneof",
+    "map",
+    "repeated",
+    "optional",
+    "required",
+    "reserved",
+    "extensions",
+    "extend",
+    "group",
+    "to",
+    "max",
+    "true",
+    "false",
+    "inf",
+    "nan",
+    "double",
+    "float",
+    "int32",
+    "int64",
+    "uint32",
+    "uint64",
+    "sint32",
+    "sint64",
+    "fixed32",
+    "fixed64",
+    "sfixed32",
+    "sfixed64",
+    "bool",
+    "string",
+    "bytes",
+];
+
+/// Sanitize an OCSF attribute name into a valid proto field identifier.
+///
+/// - Characters other than ASCII letters, digits, and `_` become `_`
+/// - Names that do not start with a letter are prefixed with `f_`
+/// - Proto keywords ([`PROTO_KEYWORDS`]) get a `_` suffix; proto3 JSON
+///   drops it again, so the JSON name still matches OCSF
+///
+/// Valid names are returned unchanged: `"src_endpoint"` → `"src_endpoint"`,
+/// `"user-name"` → `"user_name"`, `"2fa"` → `"f_2fa"`, `"message"` →
+/// `"message_"`.
+pub fn sanitize_field_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !sanitized.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        sanitized.insert_str(0, "f_");
+    }
+    if PROTO_KEYWORDS.contains(&sanitized.as_str()) {
+        sanitized.push('_');
+    }
+    sanitized
+}
+
+/// Convert a human-readable caption to a snake_case proto field identifier.
+///
+/// - `"Activity ID"` → `"activity_id"`
+/// - `"HTTP Request"` → `"http_request"`
+/// - `"2FA Method"` → `"f_2fa_method"`
+pub fn caption_to_field_name(caption: &str) -> String {
+    sanitize_field_name(&to_enum_variant_name(caption).to_lowercase())
+}
+
 /// Convert a human-readable caption to a SCREAMING_SNAKE enum variant name.
 ///
 /// - `"Logon"` → `"LOGON"`
@@ -117,6 +255,24 @@ pub fn to_enum_variant_name(caption: &str) -> String {
         .to_string()
 }
 
+/// Use `VALUE_<value>` (`VALUE_NEG_<n>` for negative values) as the variant
+/// name when a caption has no alphanumeric characters (e.g., `"***"`) and
+/// `variant` came out empty.
+pub fn variant_name_or_value(variant: String, value: i32) -> String {
+    match (variant.is_empty(), value < 0) {
+        (false, _) => variant,
+        (true, false) => format!("VALUE_{value}"),
+        (true, true) => format!("VALUE_NEG_{}", value.unsigned_abs()),
+    }
+}
+
+/// Convert an OCSF version string to the default proto package slug.
+///
+/// `"1.7.0"` → `"v1_7_0"`, `"1.8.0-dev"` → `"v1_8_0_dev"`.
+pub fn version_to_slug(version: &str) -> String {
+    format!("v{}", version.replace(['.', '-'], "_"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,9 +378,61 @@ mod tests {
         assert_eq!(to_enum_variant_name("Other"), "OTHER");
     }
 
+    #[test]
+    fn empty_variant_name_falls_back_to_value() {
+        assert_eq!(to_enum_variant_name("\u{2014}"), "");
+        assert_eq!(
+            variant_name_or_value(to_enum_variant_name("***"), 5),
+            "VALUE_5"
+        );
+        assert_eq!(variant_name_or_value(String::new(), -1), "VALUE_NEG_1");
+        assert_eq!(variant_name_or_value("LOGON".to_string(), 1), "LOGON");
+    }
+
+    #[test]
+    fn sanitize_field_name_makes_valid_identifiers() {
+        assert_eq!(sanitize_field_name("src_endpoint"), "src_endpoint");
+        assert_eq!(sanitize_field_name("user-name"), "user_name");
+        assert_eq!(sanitize_field_name("x.y"), "x_y");
+        assert_eq!(sanitize_field_name("2fa"), "f_2fa");
+        assert_eq!(sanitize_field_name("_private"), "f__private");
+    }
+
+    #[test]
+    fn sanitize_field_name_suffixes_proto_keywords() {
+        for keyword in PROTO_KEYWORDS {
+            assert_eq!(sanitize_field_name(keyword), format!("{keyword}_"));
+        }
+        assert_eq!(sanitize_field_name("message_id"), "message_id");
+        assert_eq!(sanitize_field_name("Message"), "Message");
+        assert_eq!(caption_to_field_name("Message"), "message_");
+    }
+
+    #[test]
+    fn known_scalar_type_has_no_fallback() {
+        assert_eq!(known_scalar_type("ip_t"), Some("string"));
+        assert_eq!(known_scalar_type("mystery_t"), None);
+        assert_eq!(known_scalar_type("object_t"), None);
+        assert_eq!(ocsf_to_proto_type("mystery_t"), Some("string"));
+    }
+
+    #[test]
+    fn caption_to_field_name_conversion() {
+        assert_eq!(caption_to_field_name("Activity ID"), "activity_id");
+        assert_eq!(caption_to_field_name("HTTP Request"), "http_request");
+        assert_eq!(caption_to_field_name("Process ID (PID)"), "process_id_pid");
+        assert_eq!(caption_to_field_name("2FA Method"), "f_2fa_method");
+    }
+
     #[test]
     fn sanitize_object_name_strips_prefix() {
         assert_eq!(sanitize_object_name("win/win_service"), "win_service");
         assert_eq!(sanitize_object_name("user"), "user");
     }
+
+    #[test]
+    fn version_slug_conversion() {
+        assert_eq!(version_to_slug("1.7.0"), "v1_7_0");
+        assert_eq!(version_to_slug("1.8.0-dev"), "v1_8_0_dev");
+    }
 }
@@ -5,9 +5,13 @@
 
 use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::Arc;
 
 use ocsf_proto_gen::codegen;
-use ocsf_proto_gen::schema::{OcsfAttribute, OcsfClass, OcsfEnumValue, OcsfObject, OcsfSchema};
+use ocsf_proto_gen::schema::{
+    OcsfAttribute, OcsfClass, OcsfDeprecated, OcsfEnumValue, OcsfObject, OcsfSchema,
+    resolve_object_graph,
+};
 
 /// Build a minimal but realistic schema for testing.
 fn test_schema() -> OcsfSchema {
@@ -21,6 +25,9 @@ fn test_schema() -> OcsfSchema {
         OcsfAttribute {
             type_name: "integer_t".to_string(),
             caption: "Activity ID".to_string(),
+            requirement: Some("required".to_string()),
+            group: Some("classification".to_string()),
+            sibling: Some("activity_name".to_string()),
             enum_values: Some(BTreeMap::from([
                 (
                     "0".to_string(),
@@ -219,6 +226,7 @@ fn test_schema() -> OcsfSchema {
             category_name: "Identity & Access Management".to_string(),
             profiles: vec![],
             attributes: auth_attrs,
+            deprecated: None,
         },
     );
 
@@ -288,6 +296,7 @@ fn test_schema() -> OcsfSchema {
             extends: None,
             attributes: ep_attrs,
             observable: Some(20),
+            deprecated: None,
         },
     );
 
@@ -318,6 +327,7 @@ fn test_schema() -> OcsfSchema {
             extends: None,
             attributes: enrich_attrs,
             observable: None,
+            deprecated: None,
         },
     );
 
@@ -332,6 +342,7 @@ fn test_schema() -> OcsfSchema {
             extends: None,
             attributes: BTreeMap::new(),
             observable: None,
+            deprecated: None,
         },
     );
 
@@ -357,6 +368,7 @@ fn default_attr() -> OcsfAttribute {
         profile: None,
         enum_values: None,
         deprecated: None,
+        position: 0,
     }
 }
 
@@ -370,7 +382,8 @@ fn end_to_end_generate_and_validate() {
 
     // Verify stats.
     assert_eq!(stats.classes_generated, 1);
-    assert_eq!(stats.objects_generated, 3); // network_endpoint + enrichment + object (empty)
+    assert_eq!(stats.objects_generated, 2); // network_endpoint + enrichment
+    assert_eq!(stats.empty_objects_skipped, 1); // object, referenced by `unmapped`
     assert!(stats.deprecated_fields_skipped >= 1);
     assert!(stats.enums_generated >= 2); // activity_id + severity_id + type_id
 
@@ -381,6 +394,7 @@ fn end_to_end_generate_and_validate() {
     assert!(proto_dir.join("objects/objects.proto").exists());
     assert!(proto_dir.join("objects/enums/enums.proto").exists());
     assert!(proto_dir.join("enum-value-map.json").exists());
+    assert!(proto_dir.join("field-metadata.json").exists());
 }
 
 #[test]
@@ -392,8 +406,11 @@ fn generated_proto_has_correct_content() {
 
     let proto = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
 
-    // Verify proto3 syntax and package.
-    assert!(proto.starts_with("syntax = \"proto3\";"));
+    // Verify provenance, proto3 syntax and package.
+    assert!(proto.starts_with(&format!(
+        "// Generated by ocsf-proto-gen {} from OCSF 1.7.0. DO NOT EDIT.\n\nsyntax = \"proto3\";",
+        env!("CARGO_PKG_VERSION")
+    )));
     assert!(proto.contains("package ocsf.v1_7_0.events.iam;"));
 
     // Verify message name.
@@ -497,78 +514,4423 @@ fn enum_value_map_is_valid_json() {
 }
 
 #[test]
-fn deterministic_output() {
+fn enum_value_map_includes_descriptions_when_present() {
+    let mut schema = test_schema();
+    let activity = schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .get_mut("activity_id")
+        .unwrap();
+    activity
+        .enum_values
+        .as_mut()
+        .unwrap()
+        .get_mut("1")
+        .unwrap()
+        .description = Some("A new logon session was requested.".to_string());
+    let dir = tempdir();
+
+    codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/enum-value-map.json")).unwrap();
+    let map: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+    assert_eq!(
+        map["AUTHENTICATION_ACTIVITY_ID_LOGON"]["description"],
+        "A new logon session was requested."
+    );
+    // Entries without a description stay compact.
+    let logoff = map["AUTHENTICATION_ACTIVITY_ID_LOGOFF"]
+        .as_object()
+        .unwrap();
+    assert!(!logoff.contains_key("description"));
+}
+
+/// Add the extension object `win/win_service`, referenced by
+/// `authentication.win_service`.
+fn add_extension_object(schema: &mut OcsfSchema) {
+    schema.objects.insert(
+        "win/win_service".to_string(),
+        OcsfObject {
+            name: "win/win_service".to_string(),
+            caption: "Windows Service".to_string(),
+            description: String::new(),
+            extends: None,
+            attributes: BTreeMap::from([(
+                "name".to_string(),
+                OcsfAttribute {
+                    type_name: "string_t".to_string(),
+                    caption: "Name".to_string(),
+                    ..default_attr()
+                },
+            )]),
+            observable: None,
+            deprecated: None,
+        },
+    );
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert(
+            "win_service".to_string(),
+            OcsfAttribute {
+                type_name: "object_t".to_string(),
+                caption: "Windows Service".to_string(),
+                object_type: Some("win/win_service".to_string()),
+                ..default_attr()
+            },
+        );
+}
+
+#[test]
+fn object_name_map_recovers_original_names() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    let dir = tempdir();
+
+    codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/object-name-map.json")).unwrap();
+    let map: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+    assert_eq!(map["NetworkEndpoint"], "network_endpoint");
+    assert_eq!(map["WinService"], "win/win_service");
+}
+
+#[test]
+fn extension_packages_scope_extension_objects() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        extension_packages: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let ext_objects =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/ext/win/objects/objects.proto")).unwrap();
+    assert!(ext_objects.contains("package ocsf.v1_7_0.ext.win.objects;"));
+    assert!(ext_objects.contains("import \"ocsf/v1_7_0/ext/win/objects/enums/enums.proto\";"));
+    assert!(ext_objects.contains("message WinService {"));
+    assert!(
+        dir.join("ocsf/v1_7_0/ext/win/objects/enums/enums.proto")
+            .exists()
+    );
+
+    let core_objects =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(!core_objects.contains("WinService"));
+    assert!(core_objects.contains("message NetworkEndpoint {"));
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("import \"ocsf/v1_7_0/ext/win/objects/objects.proto\";"));
+    assert!(events.contains("ocsf.v1_7_0.ext.win.objects.WinService win_service = "));
+    assert!(events.contains("ocsf.v1_7_0.objects.NetworkEndpoint src_endpoint = "));
+
+    codegen::check_import_graph(&dir).unwrap();
+}
+
+/// Add an extension class keyed by `name` (e.g., `win/registry_key_activity`)
+/// in the `iam` category, with one enum attribute.
+fn add_extension_class(schema: &mut OcsfSchema, name: &str, uid: u32) {
+    schema.classes.insert(
+        name.to_string(),
+        OcsfClass {
+            name: name.to_string(),
+            uid,
+            caption: String::new(),
+            description: String::new(),
+            extends: "iam".to_string(),
+            category: "iam".to_string(),
+            category_uid: 3,
+            category_name: "Identity & Access Management".to_string(),
+            profiles: vec![],
+            attributes: BTreeMap::from([(
+                "activity_id".to_string(),
+                OcsfAttribute {
+                    type_name: "integer_t".to_string(),
+                    caption: "Activity ID".to_string(),
+                    enum_values: Some(BTreeMap::from([(
+                        "1".to_string(),
+                        OcsfEnumValue {
+                            caption: "Create".to_string(),
+                            description: None,
+                        },
+                    )])),
+                    ..default_attr()
+                },
+            )]),
+            deprecated: None,
+        },
+    );
+}
+
+#[test]
+fn extension_classes_get_their_own_category_packages() {
+    let mut schema = test_schema();
+    add_extension_class(&mut schema, "win/registry_key_activity", 201_001);
+    add_extension_class(&mut schema, "acme/vpn_activity", 900_001);
+    let classes = [
+        "authentication".to_string(),
+        "win/registry_key_activity".to_string(),
+        "acme/vpn_activity".to_string(),
+    ];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_event_envelope: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let core = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(core.contains("message Authentication {"));
+    assert!(!core.contains("RegistryKeyActivity"));
+    let win = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/win/iam/iam.proto")).unwrap();
+    assert!(win.contains("package ocsf.v1_7_0.events.win.iam;"));
+    assert!(win.contains("import \"ocsf/v1_7_0/events/win/iam/enums/enums.proto\";"));
+    assert!(win.contains(
+        "\tocsf.v1_7_0.events.win.iam.enums.REGISTRY_KEY_ACTIVITY_ACTIVITY_ID activity_id = 1;"
+    ));
+    let acme = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/acme/iam/iam.proto")).unwrap();
+    assert!(acme.contains("package ocsf.v1_7_0.events.acme.iam;"));
+    assert!(acme.contains("enums.VPN_ACTIVITY_ACTIVITY_ID activity_id = 1;"));
+
+    let envelope = std::fs::read_to_string(dir.join("ocsf/v1_7_0/ocsf_event.proto")).unwrap();
+    assert!(envelope.contains("import \"ocsf/v1_7_0/events/win/iam/iam.proto\";"));
+    assert!(envelope.contains(
+        "ocsf.v1_7_0.events.win.iam.RegistryKeyActivity win_registry_key_activity = 201001;"
+    ));
+
+    // Extension packages move the class under the extension's root instead.
+    let scoped = tempdir();
+    let opts = codegen::GenerationOptions {
+        extension_packages: true,
+        ..opts
+    };
+    codegen::generate_with_options(&schema, &classes, &scoped, &opts).unwrap();
+    let win =
+        std::fs::read_to_string(scoped.join("ocsf/v1_7_0/ext/win/events/iam/iam.proto")).unwrap();
+    assert!(win.contains("package ocsf.v1_7_0.ext.win.events.iam;"));
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+}
+
+#[test]
+fn extension_classes_sharing_a_message_name_are_rejected() {
+    let mut schema = test_schema();
+    add_extension_class(&mut schema, "win/registry_key_activity", 201_001);
+    add_extension_class(&mut schema, "acme/registry_key_activity", 900_001);
+    let classes = [
+        "win/registry_key_activity".to_string(),
+        "acme/registry_key_activity".to_string(),
+    ];
+
+    let err = codegen::generate_to_map(&schema, &classes, &Default::default()).unwrap_err();
+    assert!(
+        err.to_string().contains(
+            "classes 'win/registry_key_activity' and 'acme/registry_key_activity' would \
+             generate the same message RegistryKeyActivity"
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn presence_caveats_flag_enums_with_real_zero_value() {
     let schema = test_schema();
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_presence_caveats: true,
+        ..Default::default()
+    };
 
-    let dir_a = tempdir();
-    let dir_b = tempdir();
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
 
-    codegen::generate(&schema, &["authentication".to_string()], &dir_a).unwrap();
-    codegen::generate(&schema, &["authentication".to_string()], &dir_b).unwrap();
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/presence-caveats.json")).unwrap();
+    let caveats: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+    let activity = &caveats["Authentication.activity_id"];
+    assert_eq!(activity["enum"], "AUTHENTICATION_ACTIVITY_ID");
+    assert_eq!(activity["zero_value"], "Unknown");
+    // Non-enum scalars are not flagged.
+    assert!(caveats.get("Authentication.message").is_none());
+}
 
-    // Compare all generated files byte-for-byte.
-    for entry in walkdir(&dir_a) {
-        let relative = entry.strip_prefix(&dir_a).unwrap();
-        let file_a = std::fs::read_to_string(&entry).unwrap();
-        let file_b = std::fs::read_to_string(dir_b.join(relative)).unwrap();
-        assert_eq!(file_a, file_b, "files differ: {}", relative.display());
+#[test]
+fn presence_caveats_not_written_by_default() {
+    let schema = test_schema();
+    let dir = tempdir();
+
+    codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+    assert!(!dir.join("ocsf/v1_7_0/presence-caveats.json").exists());
+}
+
+#[test]
+fn fully_deprecated_object_gets_no_message() {
+    let mut schema = test_schema();
+    let enrichment = schema.objects.get_mut("enrichment").unwrap();
+    for attr in enrichment.attributes.values_mut() {
+        attr.deprecated = Some(OcsfDeprecated {
+            message: "Removed.".to_string(),
+            since: "1.7.0".to_string(),
+        });
     }
+    let classes = ["authentication".to_string()];
+    let opts = codegen::GenerationOptions {
+        reject_empty_messages: true,
+        ..Default::default()
+    };
+
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    let objects = &files[Path::new("ocsf/v1_7_0/objects/objects.proto")];
+    assert!(!objects.contains("message Enrichment"));
+    assert!(!objects.contains("message Object"));
+    assert!(objects.contains("message NetworkEndpoint {"));
+    let events = &files[Path::new("ocsf/v1_7_0/events/iam/iam.proto")];
+    assert!(events.contains("\trepeated string enrichments = "));
+    let object_names = &files[Path::new("ocsf/v1_7_0/object-name-map.json")];
+    assert!(!object_names.contains("Enrichment"));
+
+    let stats = codegen::check(&schema, &classes, &opts).unwrap();
+    assert_eq!(stats.empty_objects_skipped, 2);
+    assert_eq!(stats.objects_generated, 1);
+
+    // Reserving deprecated fields still leaves the object without a field.
+    let opts = codegen::GenerationOptions {
+        deprecated_handling: codegen::DeprecatedHandling::Reserve,
+        ..opts
+    };
+    let stats = codegen::check(&schema, &classes, &opts).unwrap();
+    assert_eq!(stats.empty_objects_skipped, 2);
 }
 
 #[test]
-fn invalid_class_name_returns_error() {
+fn reject_empty_messages_fails_on_fully_deprecated_class() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    for attr in auth.attributes.values_mut() {
+        attr.deprecated = Some(OcsfDeprecated {
+            message: "Removed.".to_string(),
+            since: "1.7.0".to_string(),
+        });
+    }
+    let opts = codegen::GenerationOptions {
+        reject_empty_messages: true,
+        ..Default::default()
+    };
+
+    let err =
+        codegen::generate_with_options(&schema, &["authentication".to_string()], &tempdir(), &opts)
+            .unwrap_err()
+            .to_string();
+    assert!(
+        err.contains("message Authentication ('authentication') has no fields"),
+        "unexpected error: {err}"
+    );
+
+    // Without the option the empty message is emitted.
+    codegen::generate(&schema, &["authentication".to_string()], &tempdir()).unwrap();
+}
+
+#[test]
+fn reject_empty_messages_passes_when_messages_have_fields() {
+    let schema = test_schema();
+    let opts = codegen::GenerationOptions {
+        reject_empty_messages: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &tempdir(), &opts)
+        .unwrap();
+}
+
+#[test]
+fn caption_field_names_keep_json_name() {
     let schema = test_schema();
     let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        field_name_source: codegen::FieldNameSource::Caption,
+        ..Default::default()
+    };
 
-    let result = codegen::generate(&schema, &["nonexistent_class".to_string()], &dir);
-    assert!(result.is_err());
-    let err = result.unwrap_err().to_string();
-    assert!(err.contains("nonexistent_class"));
-    assert!(err.contains("not found"));
-    // Should mention available classes.
-    assert!(err.contains("authentication"));
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    let field_line = |name: &str| {
+        events
+            .lines()
+            .find(|line| line.contains(&format!(" {name} = ")))
+            .unwrap_or_else(|| panic!("no field {name}"))
+            .to_string()
+    };
+    assert!(field_line("activity_id").contains("[json_name = \"activity_id\"];"));
+    assert!(field_line("event_time").contains("[json_name = \"time\"];"));
+    assert!(field_line("source_endpoint").contains("[json_name = \"src_endpoint\"];"));
+
+    // Field metadata keys follow the emitted names.
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/field-metadata.json")).unwrap();
+    let metadata: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+    assert!(metadata.get("Authentication.event_time").is_some());
+    assert!(metadata.get("Authentication.time").is_none());
+
+    // The default keeps attribute keys without json_name.
+    let plain = tempdir();
+    codegen::generate(&schema, &["authentication".to_string()], &plain).unwrap();
+    let events = std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(!events.contains("json_name"));
 }
 
 #[test]
-fn schema_load_from_file() {
+fn mark_file_deprecated_adds_file_option() {
+    let schema = test_schema();
     let dir = tempdir();
-    let path = dir.join("schema.json");
+    let opts = codegen::GenerationOptions {
+        mark_file_deprecated: true,
+        ..Default::default()
+    };
 
-    // Write a minimal schema JSON directly.
-    std::fs::write(
-        &path,
-        r#"{"version":"1.7.0","classes":{},"objects":{},"types":{},"base_event":{}}"#,
-    )
-    .unwrap();
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
 
-    let loaded = ocsf_proto_gen::schema::load_schema(&path).unwrap();
-    assert_eq!(loaded.version, "1.7.0");
-    assert_eq!(loaded.classes.len(), 0);
-    assert_eq!(loaded.objects.len(), 0);
+    for path in walkdir(&dir) {
+        if path.extension().is_some_and(|ext| ext == "proto") {
+            let content = std::fs::read_to_string(&path).unwrap();
+            let package_line = content.find("\npackage ").unwrap();
+            let option_line = content
+                .find("\noption deprecated = true;\n")
+                .unwrap_or_else(|| panic!("{} is missing the option", path.display()));
+            assert!(option_line > package_line, "{}", path.display());
+        }
+    }
+
+    let plain = tempdir();
+    codegen::generate(&schema, &["authentication".to_string()], &plain).unwrap();
+    let objects = std::fs::read_to_string(plain.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(!objects.contains("option deprecated"));
 }
 
 #[test]
-fn empty_object_type_emits_string() {
-    // An object_t referencing an object with zero attributes should emit
-    // `string` instead of an empty proto message reference.
+fn field_metadata_includes_requirement_and_group() {
     let schema = test_schema();
     let dir = tempdir();
 
     codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
 
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/field-metadata.json")).unwrap();
+    let map: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+
+    let activity_id = &map["Authentication.activity_id"];
+    assert_eq!(activity_id["requirement"], "required");
+    assert_eq!(activity_id["group"], "classification");
+    assert_eq!(activity_id["sibling"], "activity_name");
+    // Attributes without metadata are still listed, with absent keys omitted.
+    assert_eq!(map["Authentication.message_"], serde_json::json!({}));
+    assert!(map["NetworkEndpoint.ip"].is_object());
+    // Deprecated fields are not emitted, so they carry no metadata.
+    assert!(map.get("Authentication.old_field").is_none());
+}
+
+#[test]
+fn custom_naming_strategy_preserves_acronyms() {
+    use ocsf_proto_gen::type_map::{NamingStrategy, to_pascal_case};
+
+    /// Keeps `http` as `HTTP` instead of `Http` in message names.
+    struct AcronymNaming;
+
+    impl NamingStrategy for AcronymNaming {
+        fn message_name(&self, ocsf_name: &str) -> String {
+            to_pascal_case(ocsf_name).replace("Http", "HTTP")
+        }
+    }
+
+    let mut schema = test_schema();
+    schema.objects.insert(
+        "http_request".to_string(),
+        OcsfObject {
+            name: "http_request".to_string(),
+            caption: "HTTP Request".to_string(),
+            description: String::new(),
+            extends: None,
+            attributes: BTreeMap::from([(
+                "url".to_string(),
+                OcsfAttribute {
+                    type_name: "url_t".to_string(),
+                    caption: "URL".to_string(),
+                    ..default_attr()
+                },
+            )]),
+            observable: None,
+            deprecated: None,
+        },
+    );
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert(
+            "http_request".to_string(),
+            OcsfAttribute {
+                type_name: "object_t".to_string(),
+                caption: "HTTP Request".to_string(),
+                object_type: Some("http_request".to_string()),
+                ..default_attr()
+            },
+        );
+
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        naming: Arc::new(AcronymNaming),
+        ..Default::default()
+    };
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains("message HTTPRequest {"));
+    assert!(!objects.contains("HttpRequest"));
+
     let proto = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(proto.contains("ocsf.v1_7_0.objects.HTTPRequest http_request"));
+}
 
-    // unmapped is object_t → "object" (empty) → must become string.
+#[test]
+fn sanitized_field_name_collisions_are_disambiguated() {
+    let mut schema = test_schema();
+    let attrs = &mut schema.objects.get_mut("enrichment").unwrap().attributes;
+    attrs.insert(
+        "data-type".to_string(),
+        OcsfAttribute {
+            type_name: "string_t".to_string(),
+            caption: "Data Type (legacy)".to_string(),
+            ..default_attr()
+        },
+    );
+    attrs.insert(
+        "data_type".to_string(),
+        OcsfAttribute {
+            type_name: "string_t".to_string(),
+            caption: "Data Type".to_string(),
+            ..default_attr()
+        },
+    );
+
+    let dir = tempdir();
+    let stats = codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+    assert_eq!(stats.field_name_collisions_resolved, 1);
+    assert_eq!(
+        stats.warnings,
+        ["field 'data-type' in 'enrichment' collides after sanitization, renamed to 'data_type_2'"]
+    );
+
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    // The already-valid name keeps its identifier; the sanitized one is suffixed.
+    assert!(objects.contains("string data_type = 2; // Caption: Data Type;\n"));
     assert!(
-        proto.contains("string unmapped"),
-        "expected `string unmapped`, got:\n{proto}"
+        objects.contains(
+            "string data_type_2 = 1; // Caption: Data Type (legacy); OCSF name: data-type;"
+        )
     );
+    assert!(!objects.contains("data-type ="));
+
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/field-metadata.json")).unwrap();
+    let map: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+    assert!(map.get("Enrichment.data_type").is_some());
+    assert!(map.get("Enrichment.data_type_2").is_some());
+}
+
+#[test]
+fn enum_value_map_glob_filter() {
+    let schema = test_schema();
+    let dir = tempdir();
+
+    let opts = codegen::GenerationOptions {
+        enum_map_globs: vec!["AUTHENTICATION_SEV*_ID".to_string()],
+        ..Default::default()
+    };
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/enum-value-map.json")).unwrap();
+    let map: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+    let keys: Vec<&String> = map.as_object().unwrap().keys().collect();
+    assert_eq!(keys.len(), 8);
     assert!(
-        !proto.contains("Object unmapped"),
-        "empty object type should NOT produce message reference"
+        keys.iter()
+            .all(|k| k.starts_with("AUTHENTICATION_SEVERITY_ID_"))
+    );
+
+    // Proto enums are unaffected by the map filter.
+    let enums =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/enums/enums.proto")).unwrap();
+    assert!(enums.contains("enum AUTHENTICATION_ACTIVITY_ID {"));
+}
+
+#[test]
+fn enum_value_map_can_omit_object_enums() {
+    let schema = test_schema();
+    let dir = tempdir();
+
+    let opts = codegen::GenerationOptions {
+        enum_map_include_objects: false,
+        ..Default::default()
+    };
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/enum-value-map.json")).unwrap();
+    let map: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+    assert!(map.get("AUTHENTICATION_ACTIVITY_ID_LOGON").is_some());
+    assert!(map.get("NETWORK_ENDPOINT_TYPE_ID_SERVER").is_none());
+}
+
+#[test]
+fn generated_imports_form_a_dag() {
+    let schema = test_schema();
+    let dir = tempdir();
+
+    codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+    codegen::check_import_graph(&dir).unwrap();
+}
+
+#[test]
+fn import_cycle_is_detected() {
+    let schema = test_schema();
+    let dir = tempdir();
+
+    codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+    // Make the objects file import the iam events file, which already
+    // imports objects.proto.
+    let objects_path = dir.join("ocsf/v1_7_0/objects/objects.proto");
+    let objects = std::fs::read_to_string(&objects_path).unwrap();
+    let objects = objects.replace(
+        "import \"ocsf/v1_7_0/objects/enums/enums.proto\";",
+        "import \"ocsf/v1_7_0/objects/enums/enums.proto\";\nimport public \"ocsf/v1_7_0/events/iam/iam.proto\";",
+    );
+    std::fs::write(&objects_path, objects).unwrap();
+
+    let err = codegen::check_import_graph(&dir).unwrap_err().to_string();
+    assert!(err.contains("import cycle"), "unexpected error: {err}");
+    assert!(err.contains("ocsf/v1_7_0/events/iam/iam.proto -> ocsf/v1_7_0/objects/objects.proto"));
+    assert!(err.contains("ocsf/v1_7_0/objects/objects.proto -> ocsf/v1_7_0/events/iam/iam.proto"));
+}
+
+/// A cut-down meta-schema covering the parts of the export codegen relies on.
+#[cfg(feature = "validate-meta")]
+const META_SCHEMA: &str = r#"{
+    "$schema": "https://json-schema.org/draft/2020-12/schema",
+    "type": "object",
+    "required": ["version", "classes", "objects"],
+    "properties": {
+        "version": {"type": "string"},
+        "classes": {
+            "type": "object",
+            "additionalProperties": {
+                "type": "object",
+                "required": ["name", "uid"],
+                "properties": {
+                    "name": {"type": "string"},
+                    "uid": {"type": "integer"},
+                    "attributes": {"type": "object"}
+                }
+            }
+        },
+        "objects": {"type": "object"}
+    }
+}"#;
+
+#[cfg(feature = "validate-meta")]
+#[test]
+fn meta_schema_accepts_well_formed_export() {
+    let dir = tempdir();
+    let export = dir.join("schema.json");
+    let meta = dir.join("meta.json");
+    std::fs::write(
+        &export,
+        r#"{"version": "1.7.0", "classes": {"authentication": {"name": "authentication", "uid": 3002}}, "objects": {}}"#,
+    )
+    .unwrap();
+    std::fs::write(&meta, META_SCHEMA).unwrap();
+
+    ocsf_proto_gen::schema::validate_against_meta(&export, &meta).unwrap();
+}
+
+#[cfg(feature = "validate-meta")]
+#[test]
+fn meta_schema_reports_violation_pointer() {
+    let dir = tempdir();
+    let export = dir.join("schema.json");
+    let meta = dir.join("meta.json");
+    // `uid` is a string and `objects` is missing.
+    std::fs::write(
+        &export,
+        r#"{"version": "1.7.0", "classes": {"authentication": {"name": "authentication", "uid": "3002"}}}"#,
+    )
+    .unwrap();
+    std::fs::write(&meta, META_SCHEMA).unwrap();
+
+    let err = ocsf_proto_gen::schema::validate_against_meta(&export, &meta)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("meta-schema validation failed"),
+        "unexpected error: {err}"
+    );
+    assert!(err.contains("2 violation(s)"), "unexpected error: {err}");
+    assert!(
+        err.contains("/classes/authentication/uid"),
+        "missing pointer: {err}"
+    );
+    assert!(
+        err.contains("\"objects\" is a required property"),
+        "missing root error: {err}"
     );
 }
 
+/// Serve `{"version": <query version>, ...}` with `ETag: "v<version>"` for
+/// every request on a local port until the test process exits, answering
+/// `304 Not Modified` when `If-None-Match` carries that tag. Returns the base
+/// URL.
+#[cfg(feature = "download")]
+fn spawn_schema_server() -> String {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            std::thread::spawn(move || {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let version = request
+                    .split_once("version=")
+                    .and_then(|(_, rest)| rest.split([' ', '&']).next())
+                    .unwrap_or_default()
+                    .to_string();
+                let etag = format!("\"v{version}\"");
+                let not_modified =
+                    request
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .any(|(name, value)| {
+                            name.eq_ignore_ascii_case("if-none-match") && value.trim() == etag
+                        });
+                let response = if not_modified {
+                    format!(
+                        "HTTP/1.1 304 Not Modified\r\nETag: {etag}\r\nConnection: close\r\n\r\n"
+                    )
+                } else {
+                    let body =
+                        format!(r#"{{"version": "{version}", "classes": {{}}, "objects": {{}}}}"#);
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nETag: {etag}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes());
+            });
+        }
+    });
+    format!("http://{addr}/export/schema")
+}
+
+#[cfg(feature = "download")]
+#[tokio::test]
+async fn download_schemas_writes_each_version() {
+    let base_url = spawn_schema_server();
+    let dir = tempdir();
+    let versions = ["1.6.0".to_string(), "1.7.0".to_string()];
+
+    ocsf_proto_gen::schema::download_schemas(&versions, &dir, &base_url, 2)
+        .await
+        .unwrap();
+
+    for version in &versions {
+        let schema =
+            ocsf_proto_gen::schema::load_schema(&dir.join(version).join("schema.json")).unwrap();
+        assert_eq!(&schema.version, version);
+    }
+}
+
+/// Answer the first `failures` requests with `status`, then serve a schema
+/// like [`spawn_schema_server`]. Returns the base URL and the request count.
+#[cfg(feature = "download")]
+fn spawn_flaky_server(
+    failures: usize,
+    status: &'static str,
+) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+    use std::io::{Read, Write};
+    use std::sync::atomic::Ordering;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let count = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let response = if count.fetch_add(1, Ordering::SeqCst) < failures {
+                format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            } else {
+                let body = r#"{"version": "1.7.0", "classes": {}, "objects": {}}"#;
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (format!("http://{addr}/export/schema"), requests)
+}
+
+#[cfg(feature = "download")]
+#[tokio::test]
+async fn download_schema_retries_server_errors() {
+    use ocsf_proto_gen::schema::{DownloadOptions, RetryPolicy, download_schema_with_options};
+    use std::sync::atomic::Ordering;
+
+    let retry = |retries| DownloadOptions {
+        retry: RetryPolicy {
+            retries,
+            base_delay: std::time::Duration::from_millis(1),
+        },
+        ..Default::default()
+    };
+    let dir = tempdir();
+    let path = dir.join("1.7.0/schema.json");
+
+    let (url, requests) = spawn_flaky_server(2, "503 Service Unavailable");
+    download_schema_with_options("1.7.0", &path, &url, &retry(2))
+        .await
+        .unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+    assert!(path.exists());
+
+    let (url, requests) = spawn_flaky_server(2, "503 Service Unavailable");
+    let err = download_schema_with_options("1.7.0", &dir.join("x.json"), &url, &retry(1))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("503") && err.contains("after 2 attempts"),
+        "{err}"
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    // Client errors are not retried.
+    let (url, requests) = spawn_flaky_server(1, "404 Not Found");
+    let err = download_schema_with_options("1.7.0", &dir.join("y.json"), &url, &retry(3))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("404"), "{err}");
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "download")]
+#[tokio::test]
+async fn download_schema_verifies_sha256() {
+    use ocsf_proto_gen::schema::{DownloadOptions, download_schema_with_options};
+
+    // SHA-256 of the body `spawn_flaky_server` serves.
+    let sha256 = "0ea8b34a26535285e815b7fc11532996c1680bfc816d12d0adedcec36f601fb8";
+    let (url, _) = spawn_flaky_server(0, "");
+    let dir = tempdir();
+    let with_sha = |expected: &str| DownloadOptions {
+        expected_sha256: Some(expected.to_string()),
+        ..Default::default()
+    };
+
+    let path = dir.join("ok.json");
+    download_schema_with_options("1.7.0", &path, &url, &with_sha(&sha256.to_uppercase()))
+        .await
+        .unwrap();
+    assert!(path.exists());
+
+    let path = dir.join("bad.json");
+    let err = download_schema_with_options("1.7.0", &path, &url, &with_sha(&"0".repeat(64)))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(&"0".repeat(64)) && err.contains(sha256),
+        "{err}"
+    );
+    assert!(!path.exists());
+}
+
+#[cfg(feature = "download")]
+#[tokio::test]
+async fn download_schema_revalidates_with_etag() {
+    let base_url = spawn_schema_server();
+    let dir = tempdir();
+    let path = dir.join("1.7.0/schema.json");
+    let etag_path = dir.join("1.7.0/schema.json.etag");
+
+    ocsf_proto_gen::schema::download_schema("1.7.0", &path, &base_url)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&etag_path).unwrap(), "\"v1.7.0\"");
+
+    // 304: the cached file is left as is.
+    let cached = r#"{"version": "1.7.0", "classes": {}, "objects": {}, "cached": true}"#;
+    std::fs::write(&path, cached).unwrap();
+    ocsf_proto_gen::schema::download_schema("1.7.0", &path, &base_url)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), cached);
+
+    // A tag without its schema file is a cache miss.
+    std::fs::remove_file(&path).unwrap();
+    ocsf_proto_gen::schema::download_schema("1.7.0", &path, &base_url)
+        .await
+        .unwrap();
+    let schema = ocsf_proto_gen::schema::load_schema(&path).unwrap();
+    assert_eq!(schema.version, "1.7.0");
+}
+
+#[test]
+fn category_file_independent_of_class_order() {
+    let mut schema = test_schema();
+    schema.classes.insert(
+        "account_change".to_string(),
+        OcsfClass {
+            name: "account_change".to_string(),
+            uid: 3001,
+            caption: "Account Change".to_string(),
+            description: String::new(),
+            extends: "iam".to_string(),
+            category: "iam".to_string(),
+            category_uid: 3,
+            category_name: "Identity & Access Management".to_string(),
+            profiles: vec![],
+            attributes: BTreeMap::from([(
+                "message".to_string(),
+                OcsfAttribute {
+                    type_name: "string_t".to_string(),
+                    caption: "Message".to_string(),
+                    ..default_attr()
+                },
+            )]),
+            deprecated: None,
+        },
+    );
+
+    let forward = tempdir();
+    let reverse = tempdir();
+    codegen::generate(
+        &schema,
+        &["account_change".to_string(), "authentication".to_string()],
+        &forward,
+    )
+    .unwrap();
+    codegen::generate(
+        &schema,
+        &["authentication".to_string(), "account_change".to_string()],
+        &reverse,
+    )
+    .unwrap();
+
+    for file in ["iam.proto", "enums/enums.proto"] {
+        let path = Path::new("ocsf/v1_7_0/events/iam").join(file);
+        assert_eq!(
+            std::fs::read(forward.join(&path)).unwrap(),
+            std::fs::read(reverse.join(&path)).unwrap(),
+            "{} differs by input order",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn standalone_file_is_self_contained() {
+    let schema = test_schema();
+
+    let proto =
+        codegen::generate_standalone(&schema, "authentication", &Default::default()).unwrap();
+
+    assert!(proto.contains("package ocsf.v1_7_0.standalone.authentication;"));
+    assert!(!proto.contains("import "), "standalone file has imports");
+    assert!(proto.contains("message Authentication {"));
+    assert!(proto.contains("message NetworkEndpoint {"));
+    assert!(proto.contains("enum AUTHENTICATION_ACTIVITY_ID {"));
+
+    // Every non-scalar field type is defined in the file itself.
+    let scalars = ["string", "int32", "int64", "double", "bool"];
+    for line in proto
+        .lines()
+        .filter(|l| l.starts_with('\t') && l.contains(" = "))
+    {
+        let mut tokens = line
+            .trim()
+            .trim_start_matches("repeated ")
+            .split_whitespace();
+        let (Some(field_type), Some(name)) = (tokens.next(), tokens.next()) else {
+            continue;
+        };
+        // Enum values (`NAME = 1;`) carry no type.
+        if name == "=" || scalars.contains(&field_type) {
+            continue;
+        }
+        assert!(
+            proto.contains(&format!("message {field_type} {{"))
+                || proto.contains(&format!("enum {field_type} {{")),
+            "type {field_type} is not defined in the standalone file"
+        );
+    }
+}
+
+#[test]
+fn standalone_unknown_class_is_an_error() {
+    let schema = test_schema();
+    let err =
+        codegen::generate_standalone(&schema, "nonexistent", &Default::default()).unwrap_err();
+    assert!(err.to_string().contains("nonexistent"));
+}
+
+#[test]
+fn standalone_rejects_options_that_need_imports() {
+    let schema = test_schema();
+    let opts = codegen::GenerationOptions::builder()
+        .timestamp_mapping(codegen::TimestampMapping::WellKnownTimestamp)
+        .build();
+    let err = codegen::generate_standalone(&schema, "authentication", &opts)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(
+            "standalone output has no imports, so it cannot use the well-known timestamp mapping"
+        ),
+        "{err}"
+    );
+
+    // Import-free overrides still apply.
+    let opts = codegen::GenerationOptions::builder()
+        .type_overrides(BTreeMap::from([(
+            "timestamp_t".to_string(),
+            "fixed64".to_string(),
+        )]))
+        .build();
+    let proto = codegen::generate_standalone(&schema, "authentication", &opts).unwrap();
+    assert!(proto.contains("\tfixed64 time = "));
+}
+
+#[test]
+fn dangling_object_references_are_reported() {
+    let mut schema = test_schema();
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert(
+            "ticket".to_string(),
+            OcsfAttribute {
+                type_name: "object_t".to_string(),
+                caption: "Ticket".to_string(),
+                object_type: Some("kerberos_ticket".to_string()),
+                ..default_attr()
+            },
+        );
+    let dir = tempdir();
+
+    let stats = codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+
+    assert_eq!(
+        stats.dangling_references,
+        ["authentication.ticket -> kerberos_ticket"]
+    );
+    assert_eq!(
+        stats.warnings,
+        [
+            "authentication.ticket: object type 'kerberos_ticket' not found, defaulted to string",
+            "object 'kerberos_ticket' referenced but not found in schema",
+        ]
+    );
+    // The field falls back to string rather than naming a missing message.
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("\tstring ticket = "));
+    assert!(!events.contains("KerberosTicket"));
+}
+
+#[cfg(feature = "descriptors")]
+#[test]
+fn descriptor_set_compiles_in_memory() {
+    use protox::prost_reflect::{DescriptorPool, Kind};
+
+    let schema = test_schema();
+    let bytes = codegen::generate_descriptor_set(
+        &schema,
+        &["authentication".to_string()],
+        &Default::default(),
+    )
+    .unwrap();
+
+    let pool = DescriptorPool::decode(bytes.as_slice()).unwrap();
+    let auth = pool
+        .get_message_by_name("ocsf.v1_7_0.events.iam.Authentication")
+        .expect("Authentication descriptor");
+    let activity = auth.get_field_by_name("activity_id").unwrap();
+    match activity.kind() {
+        Kind::Enum(e) => assert_eq!(
+            e.full_name(),
+            "ocsf.v1_7_0.events.iam.enums.AUTHENTICATION_ACTIVITY_ID"
+        ),
+        other => panic!("activity_id is {other:?}"),
+    }
+    let endpoint = auth.get_field_by_name("src_endpoint").unwrap();
+    match endpoint.kind() {
+        Kind::Message(m) => assert_eq!(m.full_name(), "ocsf.v1_7_0.objects.NetworkEndpoint"),
+        other => panic!("src_endpoint is {other:?}"),
+    }
+}
+
+#[cfg(feature = "jsonschema")]
+#[test]
+fn json_schema_describes_class_and_object_closure() {
+    let schema = test_schema();
+    let files = codegen::generate_json_schema(
+        &schema,
+        &["authentication".to_string()],
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        files.keys().collect::<Vec<_>>(),
+        [Path::new(
+            "ocsf/v1_7_0/json-schema/authentication.schema.json"
+        )]
+    );
+    let prefixed = codegen::GenerationOptions::builder()
+        .package_prefix("acme.ocsf")
+        .build();
+    let prefixed_files =
+        codegen::generate_json_schema(&schema, &["authentication".to_string()], &prefixed).unwrap();
+    let path = prefixed_files.keys().next().unwrap();
+    assert_eq!(
+        path,
+        Path::new("acme/ocsf/v1_7_0/json-schema/authentication.schema.json")
+    );
+    assert!(path.starts_with(codegen::version_root(&schema, &prefixed)));
+
+    let doc: serde_json::Value = serde_json::from_str(files.values().next().unwrap()).unwrap();
+    assert_eq!(
+        doc["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(doc["type"], "object");
+    assert_eq!(doc["title"], "Authentication");
+    assert_eq!(doc["required"], serde_json::json!(["activity_id"]));
+    let properties = &doc["properties"];
+    assert_eq!(
+        properties["activity_id"],
+        serde_json::json!({"type": "integer", "enum": [0, 1, 2, 99], "title": "Activity ID"})
+    );
+    assert_eq!(properties["time"]["type"], "integer");
+    assert_eq!(properties["auth_protocol"]["type"], "string");
+    assert_eq!(
+        properties["src_endpoint"]["$ref"],
+        "#/$defs/network_endpoint"
+    );
+    assert_eq!(properties["enrichments"]["type"], "array");
+    assert_eq!(
+        properties["enrichments"]["items"]["$ref"],
+        "#/$defs/enrichment"
+    );
+    // `unmapped` references the fieldless base object: any value.
+    assert_eq!(
+        properties["unmapped"],
+        serde_json::json!({"title": "Unmapped Data"})
+    );
+    assert!(properties.get("old_field").is_none());
+
+    let defs = doc["$defs"].as_object().unwrap();
+    assert_eq!(
+        defs.keys().collect::<Vec<_>>(),
+        ["enrichment", "network_endpoint"]
+    );
+    assert_eq!(
+        defs["network_endpoint"]["properties"]["port"]["type"],
+        "integer"
+    );
+    assert_eq!(
+        defs["network_endpoint"]["properties"]["ip"]["type"],
+        "string"
+    );
+
+    let err = codegen::generate_json_schema(&schema, &["nope".to_string()], &Default::default())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ocsf_proto_gen::error::Error::ClassNotFound { .. }
+    ));
+}
+
+#[cfg(all(feature = "jsonschema", feature = "validate-meta"))]
+#[test]
+fn json_schema_validates_ocsf_events() {
+    let files = codegen::generate_json_schema(
+        &test_schema(),
+        &["authentication".to_string()],
+        &Default::default(),
+    )
+    .unwrap();
+    let doc: serde_json::Value = serde_json::from_str(files.values().next().unwrap()).unwrap();
+    let validator = jsonschema::validator_for(&doc).unwrap();
+
+    assert!(validator.is_valid(&serde_json::json!({
+        "activity_id": 1,
+        "src_endpoint": {"ip": "10.0.0.1", "port": 443},
+        "enrichments": [{"name": "geo"}],
+        "unmapped": {"anything": [1, 2]}
+    })));
+    assert!(!validator.is_valid(&serde_json::json!({"src_endpoint": {}})));
+    assert!(!validator.is_valid(&serde_json::json!({"activity_id": 7})));
+    assert!(!validator.is_valid(&serde_json::json!({
+        "activity_id": 1,
+        "src_endpoint": {"port": "443"}
+    })));
+}
+
+#[cfg(feature = "rustgen")]
+#[test]
+fn rust_structs_parse_and_mirror_the_schema() {
+    let mut schema = test_schema();
+    schema
+        .objects
+        .get_mut("network_endpoint")
+        .unwrap()
+        .attributes
+        .extend([
+            (
+                "proxy_endpoint".to_string(),
+                OcsfAttribute {
+                    type_name: "object_t".to_string(),
+                    caption: "Proxy Endpoint".to_string(),
+                    object_type: Some("network_endpoint".to_string()),
+                    ..default_attr()
+                },
+            ),
+            (
+                "type".to_string(),
+                OcsfAttribute {
+                    type_name: "json_t".to_string(),
+                    caption: "Type".to_string(),
+                    ..default_attr()
+                },
+            ),
+        ]);
+    let source = codegen::generate_rust(
+        &schema,
+        &["authentication".to_string()],
+        &Default::default(),
+    )
+    .unwrap();
+
+    let file = syn::parse_file(&source).expect("generated Rust parses");
+    let items: Vec<String> = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Struct(s) => Some(format!("struct {}", s.ident)),
+            syn::Item::Enum(e) => Some(format!("enum {}", e.ident)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        items,
+        [
+            "struct Authentication",
+            "enum AuthenticationActivityId",
+            "enum AuthenticationSeverityId",
+            "struct Enrichment",
+            "struct NetworkEndpoint",
+            "enum NetworkEndpointTypeId",
+        ]
+    );
+
+    assert!(source.starts_with("// Generated by ocsf-proto-gen "));
+    assert!(source.contains("\n    pub activity_id: AuthenticationActivityId,\n"));
+    assert!(source.contains(
+        "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub time: Option<i64>,\n"
+    ));
+    assert!(source.contains(
+        "    #[serde(default, skip_serializing_if = \"Vec::is_empty\")]\n    pub enrichments: Vec<Enrichment>,\n"
+    ));
+    // Fieldless `object` and json_t hold any JSON; keywords are raw identifiers.
+    assert!(source.contains("    pub unmapped: Option<serde_json::Value>,\n"));
+    assert!(source.contains("    pub r#type: Option<serde_json::Value>,\n"));
+    // A self-reference is boxed so the struct has a finite size.
+    assert!(source.contains("    pub proxy_endpoint: Option<Box<NetworkEndpoint>>,\n"));
+    assert!(source.contains("    pub src_endpoint: Option<NetworkEndpoint>,\n"));
+    assert!(source.contains("#[serde(try_from = \"i32\", into = \"i32\")]\n"));
+    assert!(source.contains("            99 => Ok(Self::Other),\n"));
+    assert!(!source.contains("old_field"));
+}
+
+#[cfg(feature = "rustgen")]
+#[test]
+fn rust_structs_rename_fields_with_sanitized_names() {
+    let mut schema = test_schema();
+    schema
+        .objects
+        .get_mut("enrichment")
+        .unwrap()
+        .attributes
+        .insert(
+            "data-type".to_string(),
+            OcsfAttribute {
+                type_name: "string_t".to_string(),
+                caption: "Data Type".to_string(),
+                requirement: Some("required".to_string()),
+                ..default_attr()
+            },
+        );
+    let source = codegen::generate_rust(
+        &schema,
+        &["authentication".to_string()],
+        &Default::default(),
+    )
+    .unwrap();
+    syn::parse_file(&source).expect("generated Rust parses");
+    assert!(source.contains("    #[serde(rename = \"data-type\")]\n    pub data_type: String,\n"));
+
+    let err =
+        codegen::generate_rust(&schema, &["nope".to_string()], &Default::default()).unwrap_err();
+    assert!(matches!(
+        err,
+        ocsf_proto_gen::error::Error::ClassNotFound { .. }
+    ));
+}
+
+#[test]
+fn deterministic_output() {
+    let schema = test_schema();
+
+    let dir_a = tempdir();
+    let dir_b = tempdir();
+
+    codegen::generate(&schema, &["authentication".to_string()], &dir_a).unwrap();
+    codegen::generate(&schema, &["authentication".to_string()], &dir_b).unwrap();
+
+    // Compare all generated files byte-for-byte.
+    for entry in walkdir(&dir_a) {
+        let relative = entry.strip_prefix(&dir_a).unwrap();
+        let file_a = std::fs::read_to_string(&entry).unwrap();
+        let file_b = std::fs::read_to_string(dir_b.join(relative)).unwrap();
+        assert_eq!(file_a, file_b, "files differ: {}", relative.display());
+    }
+}
+
+#[test]
+fn provenance_header_opens_every_proto_and_stays_deterministic() {
+    let schema = test_schema();
+    let opts = codegen::GenerationOptions {
+        emit_uid_options: true,
+        ..Default::default()
+    };
+    let dir_a = tempdir();
+    let dir_b = tempdir();
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir_a, &opts)
+        .unwrap();
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir_b, &opts)
+        .unwrap();
+
+    let versioned = format!(
+        "// Generated by ocsf-proto-gen {} from OCSF 1.7.0. DO NOT EDIT.\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    let shared = format!(
+        "// Generated by ocsf-proto-gen {}. DO NOT EDIT.\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    let protos: Vec<_> = walkdir(&dir_a)
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "proto"))
+        .collect();
+    assert!(protos.len() >= 4);
+    for path in protos {
+        let relative = path.strip_prefix(&dir_a).unwrap();
+        let proto = std::fs::read_to_string(&path).unwrap();
+        let header = if relative == Path::new("ocsf/ocsf_options.proto") {
+            &shared
+        } else {
+            &versioned
+        };
+        assert!(proto.starts_with(header.as_str()), "{}", relative.display());
+        assert_eq!(
+            proto,
+            std::fs::read_to_string(dir_b.join(relative)).unwrap(),
+            "files differ: {}",
+            relative.display()
+        );
+    }
+}
+
+#[test]
+fn buf_config_covers_every_generated_proto() {
+    let classes = ["authentication".to_string()];
+    let opts = codegen::GenerationOptions {
+        emit_buf_config: true,
+        emit_uid_options: true,
+        package_prefix: "com.acme.ocsf".to_string(),
+        ..Default::default()
+    };
+    let files = codegen::generate_to_map(&test_schema(), &classes, &opts).unwrap();
+
+    assert_eq!(
+        files[Path::new("buf.yaml")],
+        format!(
+            "# Generated by ocsf-proto-gen {}. DO NOT EDIT.\n\
+             version: v2\n\
+             modules:\n  - path: .\n",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+    let buf_gen = &files[Path::new("buf.gen.yaml")];
+    assert!(buf_gen.contains(
+        "inputs:\n  - directory: .\n    paths:\n\
+         \x20     - com/acme/ocsf/v1_7_0\n\
+         \x20     - com/acme/ocsf/ocsf_options.proto\n\
+         plugins:\n"
+    ));
+    let paths: Vec<&str> = buf_gen
+        .lines()
+        .filter_map(|line| line.strip_prefix("      - "))
+        .collect();
+    for proto in files
+        .keys()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "proto"))
+    {
+        assert!(
+            paths.iter().any(|path| proto.starts_with(path)),
+            "{} is outside buf.gen.yaml inputs",
+            proto.display()
+        );
+    }
+
+    let planned = codegen::planned_outputs(&test_schema(), &classes, &opts).unwrap();
+    assert!(planned.contains(&"buf.yaml".into()));
+    assert!(planned.contains(&"buf.gen.yaml".into()));
+    let plain = codegen::generate_to_map(&test_schema(), &classes, &Default::default()).unwrap();
+    assert!(!plain.contains_key(Path::new("buf.yaml")));
+}
+
+#[test]
+fn invalid_class_name_returns_error() {
+    let schema = test_schema();
+    let dir = tempdir();
+
+    let result = codegen::generate(&schema, &["nonexistent_class".to_string()], &dir);
+    assert!(result.is_err());
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("nonexistent_class"));
+    assert!(err.contains("not found"));
+    // Should mention available classes.
+    assert!(err.contains("authentication"));
+}
+
+#[test]
+fn schema_load_from_file() {
+    let dir = tempdir();
+    let path = dir.join("schema.json");
+
+    // Write a minimal schema JSON directly.
+    std::fs::write(
+        &path,
+        r#"{"version":"1.7.0","classes":{},"objects":{},"types":{},"base_event":{}}"#,
+    )
+    .unwrap();
+
+    let loaded = ocsf_proto_gen::schema::load_schema(&path).unwrap();
+    assert_eq!(loaded.version, "1.7.0");
+    assert_eq!(loaded.classes.len(), 0);
+    assert_eq!(loaded.objects.len(), 0);
+}
+
+#[test]
+fn empty_object_type_emits_string() {
+    // An object_t referencing an object with zero attributes should emit
+    // `string` instead of an empty proto message reference.
+    let schema = test_schema();
+    let dir = tempdir();
+
+    codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+
+    let proto = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+
+    // unmapped is object_t → "object" (empty) → must become string.
+    assert!(
+        proto.contains("string unmapped"),
+        "expected `string unmapped`, got:\n{proto}"
+    );
+    assert!(
+        !proto.contains("Object unmapped"),
+        "empty object type should NOT produce message reference"
+    );
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn generate_check_fails_on_unknown_types() {
+    let dir = tempdir();
+    let check = |type_name: &str| {
+        let schema = format!(
+            r#"{{
+                "version": "1.7.0",
+                "classes": {{
+                    "authentication": {{
+                        "name": "authentication",
+                        "uid": 3002,
+                        "caption": "Authentication",
+                        "category": "iam",
+                        "attributes": {{"message": {{"type": "{type_name}"}}}}
+                    }}
+                }},
+                "objects": {{}}
+            }}"#
+        );
+        std::fs::create_dir_all(dir.join("schemas/1.7.0")).unwrap();
+        std::fs::write(dir.join("schemas/1.7.0/schema.json"), schema).unwrap();
+        std::process::Command::new(env!("CARGO_BIN_EXE_ocsf-proto-gen"))
+            .args([
+                "generate",
+                "--ocsf-version",
+                "1.7.0",
+                "--classes",
+                "authentication",
+            ])
+            .arg("--schema-dir")
+            .arg(dir.join("schemas"))
+            .arg("--output-dir")
+            .arg(dir.join("out"))
+            .arg("--check")
+            .output()
+            .unwrap()
+    };
+
+    let failed = check("mystery_t");
+    assert_eq!(failed.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&failed.stderr);
+    assert!(
+        stderr.contains(
+            "error: class 'authentication', attribute 'message': unknown type 'mystery_t'"
+        ),
+        "{stderr}"
+    );
+    assert!(stderr.contains("check failed"), "{stderr}");
+
+    let passed = check("string_t");
+    assert!(
+        passed.status.success(),
+        "{}",
+        String::from_utf8_lossy(&passed.stderr)
+    );
+    assert!(!dir.join("out").exists());
+}
+
+#[test]
+fn todo_markers_flag_defaulted_types() {
+    let mut schema = test_schema();
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert(
+            "mystery".to_string(),
+            OcsfAttribute {
+                type_name: "mystery_t".to_string(),
+                caption: "Mystery".to_string(),
+                ..default_attr()
+            },
+        );
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_todo_markers: true,
+        ..Default::default()
+    };
+
+    let stats =
+        codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts)
+            .unwrap();
+
+    assert_eq!(stats.unknown_types_defaulted, 1);
+    assert_eq!(
+        stats.warnings,
+        ["authentication.mystery: unknown type 'mystery_t' defaulted to string"]
+    );
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains(
+        "\t// TODO(ocsf-proto-gen): unknown type 'mystery_t' defaulted to string\n\tstring mystery = "
+    ));
+    assert_eq!(events.matches("TODO").count(), 1);
+
+    // Markers are off by default.
+    let plain = tempdir();
+    codegen::generate(&schema, &["authentication".to_string()], &plain).unwrap();
+    let events = std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(!events.contains("TODO"));
+}
+
+#[test]
+fn strict_rejects_unknown_types_and_missing_objects() {
+    let classes = ["authentication".to_string()];
+    let strict = codegen::GenerationOptions {
+        strict: true,
+        ..Default::default()
+    };
+    codegen::generate_to_map(&test_schema(), &classes, &strict).unwrap();
+
+    let mut unknown_type = test_schema();
+    unknown_type
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert(
+            "mystery".to_string(),
+            OcsfAttribute {
+                type_name: "mystery_t".to_string(),
+                caption: "Mystery".to_string(),
+                ..default_attr()
+            },
+        );
+    let err = codegen::generate_to_map(&unknown_type, &classes, &strict).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "codegen error: class 'authentication', attribute 'mystery': \
+         unknown type 'mystery_t' (strict mode)"
+    );
+
+    let mut missing_object = test_schema();
+    missing_object
+        .objects
+        .get_mut("network_endpoint")
+        .unwrap()
+        .attributes
+        .insert(
+            "location".to_string(),
+            OcsfAttribute {
+                type_name: "object_t".to_string(),
+                caption: "Location".to_string(),
+                object_type: Some("location".to_string()),
+                ..default_attr()
+            },
+        );
+    let err = codegen::generate_to_map(&missing_object, &classes, &strict).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "codegen error: object 'network_endpoint', attribute 'location': \
+         unknown object type 'location' (strict mode)"
+    );
+
+    // Permissive by default: both fall back to string.
+    for schema in [unknown_type, missing_object] {
+        codegen::generate_to_map(&schema, &classes, &Default::default()).unwrap();
+    }
+}
+
+#[test]
+fn field_numbers_lock_keeps_numbers_across_schema_versions() {
+    use ocsf_proto_gen::field_numbers::{FIELD_NUMBERS_LOCK, parse_field_numbers_lock};
+
+    let classes = ["authentication".to_string()];
+    let opts = codegen::GenerationOptions {
+        field_numbers_lock: Some(Default::default()),
+        ..Default::default()
+    };
+    let v1 = codegen::generate_to_map(&test_schema(), &classes, &opts).unwrap();
+    let lock = parse_field_numbers_lock(&v1[Path::new(FIELD_NUMBERS_LOCK)]).unwrap();
+    assert_eq!(lock["Authentication.auth_protocol"], 2);
+    assert_eq!(lock["Authentication.unmapped"], 8);
+    assert!(
+        codegen::planned_outputs(&test_schema(), &classes, &opts)
+            .unwrap()
+            .contains(&FIELD_NUMBERS_LOCK.into())
+    );
+
+    // The next OCSF version drops `auth_protocol` and adds `account_name`,
+    // which sorts first.
+    let mut schema = test_schema();
+    schema.version = "1.8.0".to_string();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    auth.attributes.remove("auth_protocol");
+    auth.attributes.insert(
+        "account_name".to_string(),
+        OcsfAttribute {
+            type_name: "string_t".to_string(),
+            caption: "Account Name".to_string(),
+            ..default_attr()
+        },
+    );
+    let opts = codegen::GenerationOptions {
+        field_numbers_lock: Some(lock),
+        ..Default::default()
+    };
+    let v2 = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+
+    let events = &v2[Path::new("ocsf/v1_8_0/events/iam/iam.proto")];
+    assert!(events.contains("\tstring account_name = 9;"));
+    assert!(events.contains(" activity_id = 1;"));
+    assert!(events.contains(" unmapped = 8;"));
+    assert!(events.contains("\treserved 2;\n\treserved \"auth_protocol\";\n}"));
+    assert!(!events.contains(" = 2;"));
+
+    // The freed number stays locked, so it is never handed out again.
+    let lock = parse_field_numbers_lock(&v2[Path::new(FIELD_NUMBERS_LOCK)]).unwrap();
+    assert_eq!(lock["Authentication.auth_protocol"], 2);
+    assert_eq!(lock["Authentication.account_name"], 9);
+    let opts = codegen::GenerationOptions {
+        field_numbers_lock: Some(lock),
+        ..Default::default()
+    };
+    let rerun = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    assert_eq!(rerun, v2);
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+
+    // Without a lock nothing changes.
+    let plain = codegen::generate_to_map(&schema, &classes, &Default::default()).unwrap();
+    assert!(!plain.contains_key(Path::new(FIELD_NUMBERS_LOCK)));
+    assert!(plain[Path::new("ocsf/v1_8_0/events/iam/iam.proto")].contains(" account_name = 1;"));
+}
+
+#[cfg(feature = "descriptors")]
+#[test]
+fn pinned_field_numbers_seeded_from_existing_proto() {
+    let schema = test_schema();
+    let legacy = tempdir();
+    std::fs::write(
+        legacy.join("legacy.proto"),
+        "syntax = \"proto3\";\n\
+         package legacy.v1;\n\
+         message Authentication {\n  int64 time = 40;\n  int32 activity_id = 5;\n}\n",
+    )
+    .unwrap();
+    let pinned = ocsf_proto_gen::field_numbers::field_numbers_from_proto(
+        &legacy.join("legacy.proto"),
+        &[&legacy],
+    )
+    .unwrap();
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        pinned_field_numbers: pinned,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    let message = events
+        .split("message Authentication {")
+        .nth(1)
+        .and_then(|rest| rest.split("\n}").next())
+        .unwrap();
+    assert!(message.contains(" time = 40;"));
+    assert!(message.contains(" activity_id = 5;"));
+    // Unpinned fields are numbered around the pins without reusing them.
+    let numbers: Vec<u32> = message
+        .lines()
+        .filter_map(|line| {
+            line.split(" = ")
+                .nth(1)?
+                .split([';', ' '])
+                .next()?
+                .parse()
+                .ok()
+        })
+        .collect();
+    let unique: std::collections::BTreeSet<_> = numbers.iter().collect();
+    assert_eq!(unique.len(), numbers.len());
+    assert_eq!(numbers.iter().filter(|&&n| n == 5 || n == 40).count(), 2);
+}
+
+#[test]
+fn event_envelope_covers_every_class() {
+    let mut schema = test_schema();
+    schema.classes.insert(
+        "network_activity".to_string(),
+        OcsfClass {
+            name: "network_activity".to_string(),
+            uid: 4001,
+            caption: "Network Activity".to_string(),
+            description: String::new(),
+            extends: "network".to_string(),
+            category: "network".to_string(),
+            category_uid: 4,
+            category_name: "Network Activity".to_string(),
+            profiles: vec![],
+            attributes: BTreeMap::from([(
+                "message".to_string(),
+                OcsfAttribute {
+                    type_name: "string_t".to_string(),
+                    caption: "Message".to_string(),
+                    ..default_attr()
+                },
+            )]),
+            deprecated: None,
+        },
+    );
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_event_envelope: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(
+        &schema,
+        &["network_activity".to_string(), "authentication".to_string()],
+        &dir,
+        &opts,
+    )
+    .unwrap();
+
+    let envelope = std::fs::read_to_string(dir.join("ocsf/v1_7_0/ocsf_event.proto")).unwrap();
+    assert!(envelope.contains("package ocsf.v1_7_0;"));
+    assert!(envelope.contains(
+        "\toneof event {\n\
+         \t\tocsf.v1_7_0.events.iam.Authentication authentication = 3002;\n\
+         \t\tocsf.v1_7_0.events.network.NetworkActivity network_activity = 4001;\n\
+         \t}"
+    ));
+    // Every import names a generated file.
+    for line in envelope.lines().filter(|l| l.starts_with("import ")) {
+        let path = line.trim_start_matches("import \"").trim_end_matches("\";");
+        assert!(dir.join(path).exists(), "unresolved import {path}");
+    }
+    assert!(codegen::check_import_graph(&dir).is_ok());
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(
+        &schema,
+        &["network_activity".to_string(), "authentication".to_string()],
+        &opts,
+    )
+    .unwrap();
+
+    // No envelope by default.
+    let plain = tempdir();
+    codegen::generate(&schema, &["authentication".to_string()], &plain).unwrap();
+    assert!(!plain.join("ocsf/v1_7_0/ocsf_event.proto").exists());
+}
+
+#[test]
+fn deprecated_class_marks_message_deprecated() {
+    let mut schema = test_schema();
+    schema.classes.get_mut("authentication").unwrap().deprecated = Some(OcsfDeprecated {
+        message: "Use the identity class instead.".to_string(),
+        since: "1.6.0".to_string(),
+    });
+    let dir = tempdir();
+
+    let stats = codegen::generate(&schema, &["authentication".to_string()], &dir).unwrap();
+
+    assert_eq!(stats.deprecated_messages, 1);
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains(
+        "// Deprecated since 1.6.0: Use the identity class instead.\n\
+         message Authentication {\n\
+         \toption deprecated = true;\n"
+    ));
+    // Objects are not deprecated with the class.
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(!objects.contains("deprecated"));
+}
+
+#[test]
+fn packed_repeated_annotates_numeric_fields() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    for (name, type_name) in [("ports", "integer_t"), ("hostnames", "string_t")] {
+        auth.attributes.insert(
+            name.to_string(),
+            OcsfAttribute {
+                type_name: type_name.to_string(),
+                caption: name.to_string(),
+                is_array: true,
+                ..default_attr()
+            },
+        );
+    }
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        packed_repeated: Some(false),
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    let field_line = |name: &str| {
+        events
+            .lines()
+            .find(|line| line.contains(&format!(" {name} = ")))
+            .unwrap()
+            .to_string()
+    };
+    assert!(field_line("ports").starts_with("\trepeated int32 ports = "));
+    assert!(field_line("ports").contains(" [packed = false];"));
+    // Non-numeric and singular fields are unaffected.
+    assert!(!field_line("hostnames").contains("packed"));
+    assert!(!field_line("enrichments").contains("packed"));
+    assert!(!field_line("activity_id").contains("packed"));
+
+    // No annotation unless set.
+    let plain = tempdir();
+    codegen::generate(&schema, &["authentication".to_string()], &plain).unwrap();
+    let events = std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(!events.contains("packed"));
+}
+
+#[test]
+fn category_enum_lists_generated_categories() {
+    let schema = test_schema();
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_category_enum: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let proto = std::fs::read_to_string(dir.join("ocsf/v1_7_0/category.proto")).unwrap();
+    assert!(proto.contains("package ocsf.v1_7_0;"));
+    assert!(proto.contains(
+        "enum OcsfCategory {\n\
+         \tOCSF_CATEGORY_UNSPECIFIED = 0;\n\
+         \tOCSF_CATEGORY_IAM = 3; // Identity & Access Management\n\
+         }"
+    ));
+}
+
+#[test]
+fn profile_options_set_on_classes_with_profiles() {
+    let mut schema = test_schema();
+    schema.classes.get_mut("authentication").unwrap().profiles =
+        vec!["cloud".to_string(), "host".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_profile_options: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let options = std::fs::read_to_string(dir.join("ocsf/ocsf_options.proto")).unwrap();
+    assert!(options.contains("package ocsf;"));
+    assert!(options.contains("extend google.protobuf.MessageOptions {"));
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("import \"ocsf/ocsf_options.proto\";"));
+    assert!(
+        events.contains("message Authentication {\n\toption (ocsf.profiles) = \"cloud,host\";\n")
+    );
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &["authentication".to_string()], &opts).unwrap();
+
+    // Off by default.
+    let plain = tempdir();
+    codegen::generate(&schema, &["authentication".to_string()], &plain).unwrap();
+    assert!(!plain.join("ocsf/ocsf_options.proto").exists());
+}
+
+#[test]
+fn planned_outputs_match_written_files() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    let opts = codegen::GenerationOptions {
+        extension_packages: true,
+        emit_presence_caveats: true,
+        emit_event_envelope: true,
+        ..Default::default()
+    };
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+
+    let planned = codegen::planned_outputs(&schema, &classes, &opts).unwrap();
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let mut written: Vec<std::path::PathBuf> = walkdir(&dir)
+        .into_iter()
+        .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+        .collect();
+    written.sort();
+    assert_eq!(planned, written);
+    assert!(planned.contains(&std::path::PathBuf::from(
+        "ocsf/v1_7_0/ext/win/objects/objects.proto"
+    )));
+}
+
+#[test]
+fn generate_to_map_matches_written_files() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    let opts = codegen::GenerationOptions {
+        extension_packages: true,
+        ..Default::default()
+    };
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    assert!(walkdir(&dir).is_empty());
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let written = walkdir(&dir);
+    assert_eq!(files.len(), written.len());
+    for path in written {
+        let relative = path.strip_prefix(&dir).unwrap();
+        assert_eq!(
+            files[relative],
+            std::fs::read_to_string(&path).unwrap(),
+            "{}",
+            relative.display()
+        );
+    }
+}
+
+#[test]
+fn synthetic_zero_uses_configured_caption() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    for attr in ["activity_id", "severity_id"] {
+        let values = auth.attributes.get_mut(attr).unwrap().enum_values.as_mut();
+        values.unwrap().remove("0");
+    }
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        synthetic_zero_captions: BTreeMap::from([
+            (
+                "AUTHENTICATION_ACTIVITY_ID".to_string(),
+                "Unknown".to_string(),
+            ),
+            ("*".to_string(), "Not Set".to_string()),
+        ]),
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let enums =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/enums/enums.proto")).unwrap();
+    assert!(
+        enums.contains(
+            "\tAUTHENTICATION_ACTIVITY_ID_UNKNOWN = 0; // Unknown (synthetic zero value)\n"
+        )
+    );
+    assert!(
+        enums.contains(
+            "\tAUTHENTICATION_SEVERITY_ID_NOT_SET = 0; // Not Set (synthetic zero value)\n"
+        )
+    );
+    assert!(!enums.contains("UNSPECIFIED"));
+
+    // Without a caption the zero stays UNSPECIFIED.
+    let plain = tempdir();
+    let stats = codegen::generate(&schema, &["authentication".to_string()], &plain).unwrap();
+    let enums =
+        std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/enums/enums.proto")).unwrap();
+    assert!(enums.contains(
+        "\tAUTHENTICATION_ACTIVITY_ID_UNSPECIFIED = 0; // synthetic: OCSF has no 0 value\n"
+    ));
+    assert_eq!(stats.synthetic_zero_values, 2);
+}
+
+#[test]
+fn deep_object_chain_generates_without_recursion() {
+    const DEPTH: usize = 500;
+    let mut schema = test_schema();
+    let link = |next: usize| OcsfAttribute {
+        type_name: "object_t".to_string(),
+        caption: "Next".to_string(),
+        object_type: Some(format!("link_{next}")),
+        ..default_attr()
+    };
+    for level in 0..DEPTH {
+        let mut attributes = BTreeMap::from([(
+            "name".to_string(),
+            OcsfAttribute {
+                type_name: "string_t".to_string(),
+                caption: "Name".to_string(),
+                ..default_attr()
+            },
+        )]);
+        if level + 1 < DEPTH {
+            attributes.insert("next".to_string(), link(level + 1));
+        }
+        schema.objects.insert(
+            format!("link_{level}"),
+            OcsfObject {
+                name: format!("link_{level}"),
+                caption: format!("Link {level}"),
+                description: String::new(),
+                extends: None,
+                attributes,
+                observable: None,
+                deprecated: None,
+            },
+        );
+    }
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert("chain".to_string(), link(0));
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+
+    // A small stack makes any per-level recursion overflow long before
+    // DEPTH levels.
+    let (schema, stats) = std::thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn({
+            let dir = dir.clone();
+            move || {
+                let stats = codegen::generate(&schema, &classes, &dir).unwrap();
+                (schema, stats)
+            }
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert!(stats.objects_generated >= DEPTH);
+    assert!(stats.dangling_references.is_empty());
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains(&format!("message Link{} {{", DEPTH - 1)));
+    codegen::check_import_graph(&dir).unwrap();
+    let standalone =
+        codegen::generate_standalone(&schema, "authentication", &Default::default()).unwrap();
+    assert!(standalone.contains(&format!("message Link{} {{", DEPTH - 1)));
+}
+
+#[test]
+fn pgv_rules_for_required_objects_and_ports() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    auth.attributes.get_mut("src_endpoint").unwrap().requirement = Some("required".into());
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_pgv_rules: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("import \"validate/validate.proto\";"));
+    let src_endpoint = events
+        .lines()
+        .find(|line| line.contains(" src_endpoint = "))
+        .unwrap();
+    assert!(src_endpoint.contains("[(validate.rules).message.required = true];"));
+    // activity_id is required but not a message.
+    let activity_id = events
+        .lines()
+        .find(|line| line.contains(" activity_id = "))
+        .unwrap();
+    assert!(!activity_id.contains("validate"));
+
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains("import \"validate/validate.proto\";"));
+    let port = objects
+        .lines()
+        .find(|line| line.contains(" port = "))
+        .unwrap();
+    assert!(port.contains("[(validate.rules).int32 = {gte: 0, lte: 65535}];"));
+
+    // No rules or import by default.
+    let plain = tempdir();
+    codegen::generate(&schema, &["authentication".to_string()], &plain).unwrap();
+    let objects = std::fs::read_to_string(plain.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(!objects.contains("validate"));
+}
+
+#[test]
+fn pgv_rules_follow_the_emitted_fields() {
+    let mut schema = test_schema();
+    let classes = ["authentication".to_string()];
+
+    // The optional `port` is the only field with a rule; filtered out, it
+    // takes the import with it.
+    let filtered = codegen::GenerationOptions::builder()
+        .emit_pgv_rules(true)
+        .min_requirement(Some(codegen::Requirement::Recommended))
+        .build();
+    let files = codegen::generate_to_map(&schema, &classes, &filtered).unwrap();
+    let proto = &files[Path::new("ocsf/v1_7_0/objects/objects.proto")];
+    assert!(!proto.contains(" port = "));
+    assert!(!proto.contains("validate"), "{proto}");
+
+    // A deprecated port kept as a field keeps its rule.
+    let port = schema
+        .objects
+        .get_mut("network_endpoint")
+        .unwrap()
+        .attributes
+        .get_mut("port")
+        .unwrap();
+    port.deprecated = Some(OcsfDeprecated {
+        message: "Use other.".to_string(),
+        since: String::new(),
+    });
+    let emitted = codegen::GenerationOptions::builder()
+        .emit_pgv_rules(true)
+        .deprecated_handling(codegen::DeprecatedHandling::Emit)
+        .build();
+    let files = codegen::generate_to_map(&schema, &classes, &emitted).unwrap();
+    let proto = &files[Path::new("ocsf/v1_7_0/objects/objects.proto")];
+    assert!(proto.contains("import \"validate/validate.proto\";"));
+    let port = proto
+        .lines()
+        .find(|line| line.contains(" port = "))
+        .unwrap();
+    assert!(
+        port.contains("(validate.rules).int32 = {gte: 0, lte: 65535}"),
+        "{port}"
+    );
+}
+
+#[test]
+fn per_enum_layout_writes_one_file_per_enum() {
+    let schema = test_schema();
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        enum_layout: codegen::EnumLayout::PerEnum,
+        ..Default::default()
+    };
+    let classes = ["authentication".to_string()];
+
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let enums_dir = dir.join("ocsf/v1_7_0/events/iam/enums");
+    assert!(!enums_dir.join("enums.proto").exists());
+    let activity =
+        std::fs::read_to_string(enums_dir.join("authentication_activity_id.proto")).unwrap();
+    assert!(activity.contains("package ocsf.v1_7_0.events.iam.enums;"));
+    assert!(activity.contains("enum AUTHENTICATION_ACTIVITY_ID {"));
+    assert!(!activity.contains("AUTHENTICATION_SEVERITY_ID"));
+    assert!(enums_dir.join("authentication_severity_id.proto").exists());
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    let imports: Vec<&str> = events
+        .lines()
+        .filter(|l| l.starts_with("import "))
+        .collect();
+    assert_eq!(
+        imports,
+        [
+            "import \"ocsf/v1_7_0/events/iam/enums/authentication_activity_id.proto\";",
+            "import \"ocsf/v1_7_0/events/iam/enums/authentication_severity_id.proto\";",
+            "import \"ocsf/v1_7_0/objects/objects.proto\";",
+        ]
+    );
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(
+        objects.contains("import \"ocsf/v1_7_0/objects/enums/network_endpoint_type_id.proto\";")
+    );
+
+    codegen::check_import_graph(&dir).unwrap();
+    let mut written: Vec<std::path::PathBuf> = walkdir(&dir)
+        .into_iter()
+        .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+        .collect();
+    written.sort();
+    assert_eq!(
+        codegen::planned_outputs(&schema, &classes, &opts).unwrap(),
+        written
+    );
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+}
+
+#[test]
+fn visitor_walks_classes_objects_attributes_and_enum_values() {
+    #[derive(Default)]
+    struct Counter {
+        classes: Vec<String>,
+        objects: Vec<String>,
+        attributes: usize,
+        enum_values: usize,
+    }
+    impl ocsf_proto_gen::schema::SchemaVisitor for Counter {
+        fn visit_class(&mut self, class: &OcsfClass) {
+            self.classes.push(class.name.clone());
+        }
+        fn visit_object(&mut self, name: &str, _object: &OcsfObject) {
+            self.objects.push(name.to_string());
+        }
+        fn visit_attribute(&mut self, _owner: &str, _name: &str, _attribute: &OcsfAttribute) {
+            self.attributes += 1;
+        }
+        fn visit_enum_value(
+            &mut self,
+            _owner: &str,
+            _attribute: &str,
+            _value: &str,
+            _enum_value: &OcsfEnumValue,
+        ) {
+            self.enum_values += 1;
+        }
+    }
+
+    let schema = test_schema();
+    let mut counter = Counter::default();
+    ocsf_proto_gen::schema::visit(&schema, &["authentication".to_string()], &mut counter).unwrap();
+
+    assert_eq!(counter.classes, ["authentication"]);
+    assert_eq!(
+        counter.objects,
+        ["enrichment", "network_endpoint", "object"]
+    );
+    let owners = [&schema.classes["authentication"].attributes]
+        .into_iter()
+        .chain(
+            counter
+                .objects
+                .iter()
+                .map(|name| &schema.objects[name].attributes),
+        );
+    let (attributes, enum_values) = owners.fold((0, 0), |(attrs, values), owner| {
+        let owner_values: usize = owner
+            .values()
+            .filter_map(|a| a.enum_values.as_ref())
+            .map(|e| e.len())
+            .sum();
+        (attrs + owner.len(), values + owner_values)
+    });
+    assert_eq!(
+        (counter.attributes, counter.enum_values),
+        (attributes, enum_values)
+    );
+    assert_eq!((attributes, enum_values), (15, 17));
+
+    let err = ocsf_proto_gen::schema::visit(&schema, &["nope".to_string()], &mut counter);
+    assert!(matches!(
+        err,
+        Err(ocsf_proto_gen::error::Error::ClassNotFound { .. })
+    ));
+}
+
+#[test]
+fn generate_matches_generate_with_default_options() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    let classes = ["authentication".to_string()];
+    let plain = tempdir();
+    let with_opts = tempdir();
+
+    let stats = codegen::generate(&schema, &classes, &plain).unwrap();
+    let opts_stats =
+        codegen::generate_with_options(&schema, &classes, &with_opts, &Default::default()).unwrap();
+    assert_eq!(stats.classes_generated, opts_stats.classes_generated);
+    assert_eq!(stats.objects_generated, opts_stats.objects_generated);
+
+    let plain_files = walkdir(&plain);
+    assert!(!plain_files.is_empty());
+    let with_opts_files = walkdir(&with_opts);
+    assert_eq!(plain_files.len(), with_opts_files.len());
+    for (a, b) in plain_files.iter().zip(&with_opts_files) {
+        assert_eq!(
+            a.strip_prefix(&plain).unwrap(),
+            b.strip_prefix(&with_opts).unwrap()
+        );
+        assert_eq!(
+            std::fs::read(a).unwrap(),
+            std::fs::read(b).unwrap(),
+            "{}",
+            a.display()
+        );
+    }
+}
+
+#[test]
+fn options_builder_sets_fields_over_defaults() {
+    let opts = codegen::GenerationOptions::builder()
+        .emit_event_envelope(true)
+        .packed_repeated(Some(false))
+        .enum_layout(codegen::EnumLayout::PerEnum)
+        .build();
+    assert!(opts.emit_event_envelope);
+    assert_eq!(opts.packed_repeated, Some(false));
+    assert_eq!(opts.enum_layout, codegen::EnumLayout::PerEnum);
+    assert!(opts.enum_map_include_objects);
+    assert!(!opts.emit_category_enum);
+}
+
+#[test]
+fn well_known_timestamps_import_only_where_used() {
+    let schema = test_schema();
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        timestamp_mapping: codegen::TimestampMapping::WellKnownTimestamp,
+        ..Default::default()
+    };
+    let classes = ["authentication".to_string()];
+
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("import \"google/protobuf/timestamp.proto\";"));
+    assert!(events.contains("\tgoogle.protobuf.Timestamp time = "));
+    assert!(!events.contains("int64 time = "));
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(!objects.contains("google/protobuf/timestamp.proto"));
+
+    #[cfg(feature = "descriptors")]
+    {
+        use protox::prost_reflect::{DescriptorPool, Kind};
+
+        let bytes = codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+        let pool = DescriptorPool::decode(bytes.as_slice()).unwrap();
+        let auth = pool
+            .get_message_by_name("ocsf.v1_7_0.events.iam.Authentication")
+            .unwrap();
+        match auth.get_field_by_name("time").unwrap().kind() {
+            Kind::Message(m) => assert_eq!(m.full_name(), "google.protobuf.Timestamp"),
+            other => panic!("expected Timestamp, got {other:?}"),
+        }
+    }
+
+    // The default mapping stays int64 with no import.
+    let plain = tempdir();
+    codegen::generate(&schema, &classes, &plain).unwrap();
+    let events = std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("\tint64 time = "));
+    assert!(!events.contains("google/protobuf/timestamp.proto"));
+}
+
+#[test]
+fn struct_json_maps_json_and_unmapped_in_events_and_objects() {
+    let mut schema = test_schema();
+    schema
+        .objects
+        .get_mut("enrichment")
+        .unwrap()
+        .attributes
+        .insert(
+            "data".to_string(),
+            OcsfAttribute {
+                type_name: "json_t".to_string(),
+                caption: "Data".to_string(),
+                ..default_attr()
+            },
+        );
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        json_mapping: codegen::JsonMapping::WellKnownStruct,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("import \"google/protobuf/struct.proto\";"));
+    assert!(events.contains("\tgoogle.protobuf.Struct unmapped = "));
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains("import \"google/protobuf/struct.proto\";"));
+    assert!(objects.contains("\tgoogle.protobuf.Struct data = "));
+
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+
+    // By default both stay string, with no import.
+    let plain = tempdir();
+    codegen::generate(&schema, &classes, &plain).unwrap();
+    let events = std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("\tstring unmapped = "));
+    let objects = std::fs::read_to_string(plain.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains("\tstring data = "));
+    assert!(!objects.contains("google/protobuf/struct.proto"));
+}
+
+#[test]
+fn json_field_overrides_type_single_json_attributes() {
+    let mut schema = test_schema();
+    let enrichment = &mut schema.objects.get_mut("enrichment").unwrap().attributes;
+    for (name, is_array) in [("data", false), ("labels", false), ("history", true)] {
+        enrichment.insert(
+            name.to_string(),
+            OcsfAttribute {
+                type_name: "json_t".to_string(),
+                caption: name.to_string(),
+                is_array,
+                ..default_attr()
+            },
+        );
+    }
+    let classes = vec!["authentication".to_string()];
+    let opts = codegen::GenerationOptions::builder()
+        .json_field_overrides(BTreeMap::from([
+            (
+                "Enrichment.labels".to_string(),
+                "map<string, string>".to_string(),
+            ),
+            ("Enrichment.history".to_string(), "acme.History".to_string()),
+        ]))
+        .type_overrides(BTreeMap::from([(
+            "json_t".to_string(),
+            "bytes".to_string(),
+        )]))
+        .extra_imports(vec!["acme/history.proto".to_string()])
+        .build();
+
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    let objects = &files[Path::new("ocsf/v1_7_0/objects/objects.proto")];
+    assert!(objects.contains("import \"acme/history.proto\";"));
+    assert!(objects.contains("\tmap<string, string> labels = "));
+    assert!(objects.contains("\trepeated acme.History history = "));
+    // Attributes without an entry keep the type-level mapping.
+    assert!(objects.contains("\tbytes data = "));
+
+    // Overrides also win over the well-known Struct mapping, which then
+    // needs no import in the objects file.
+    let opts = codegen::GenerationOptions {
+        json_field_overrides: BTreeMap::from([
+            (
+                "Enrichment.data".to_string(),
+                "map<string, string>".to_string(),
+            ),
+            ("Enrichment.labels".to_string(), "string".to_string()),
+            ("Enrichment.history".to_string(), "string".to_string()),
+        ]),
+        json_mapping: codegen::JsonMapping::WellKnownStruct,
+        ..Default::default()
+    };
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    let objects = &files[Path::new("ocsf/v1_7_0/objects/objects.proto")];
+    assert!(objects.contains("\tmap<string, string> data = "));
+    assert!(!objects.contains("google/protobuf/struct.proto"));
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+
+    // Map fields cannot be repeated.
+    let opts = codegen::GenerationOptions {
+        json_field_overrides: BTreeMap::from([(
+            "Enrichment.history".to_string(),
+            "map<string, string>".to_string(),
+        )]),
+        ..Default::default()
+    };
+    let err = codegen::generate_to_map(&schema, &classes, &opts).unwrap_err();
+    assert!(err.to_string().contains(
+        "object 'enrichment', attribute 'history': map type 'map<string, string>' cannot be \
+         repeated"
+    ));
+}
+
+#[test]
+fn infer_maps_emits_dictionary_attributes_as_maps() {
+    let mut schema = test_schema();
+    let enrichment = &mut schema.objects.get_mut("enrichment").unwrap().attributes;
+    for name in ["data", "labels", "value"] {
+        enrichment.insert(
+            name.to_string(),
+            OcsfAttribute {
+                type_name: "json_t".to_string(),
+                caption: name.to_string(),
+                ..default_attr()
+            },
+        );
+    }
+    let classes = vec!["authentication".to_string()];
+    let opts = codegen::GenerationOptions::builder()
+        .infer_maps(true)
+        .map_attribute_names(["data", "value", "unmapped"].map(String::from).into())
+        .json_field_overrides(BTreeMap::from([(
+            "Enrichment.value".to_string(),
+            "bytes".to_string(),
+        )]))
+        .build();
+
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    let objects = &files[Path::new("ocsf/v1_7_0/objects/objects.proto")];
+    assert!(objects.contains("\tmap<string, string> data = "));
+    // Not a configured name, and an explicit override wins.
+    assert!(objects.contains("\tstring labels = "));
+    assert!(objects.contains("\tbytes value = "));
+    // `unmapped` references the base object, which has no message.
+    let events = &files[Path::new("ocsf/v1_7_0/events/iam/iam.proto")];
+    assert!(events.contains("\tmap<string, string> unmapped = "));
+
+    let structured = codegen::GenerationOptions {
+        json_mapping: codegen::JsonMapping::WellKnownStruct,
+        ..opts.clone()
+    };
+    let files = codegen::generate_to_map(&schema, &classes, &structured).unwrap();
+    let objects = &files[Path::new("ocsf/v1_7_0/objects/objects.proto")];
+    assert!(objects.contains("import \"google/protobuf/struct.proto\";"));
+    assert!(objects.contains("\tmap<string, google.protobuf.Value> data = "));
+    assert!(objects.contains("\tgoogle.protobuf.Struct labels = "));
+    let events = &files[Path::new("ocsf/v1_7_0/events/iam/iam.proto")];
+    assert!(events.contains("\tmap<string, google.protobuf.Value> unmapped = "));
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &structured).unwrap();
+
+    // Off by default.
+    let files = codegen::generate_to_map(&schema, &classes, &Default::default()).unwrap();
+    assert!(!files.values().any(|proto| proto.contains("map<")));
+}
+
+#[test]
+fn infer_maps_rejects_array_dictionaries() {
+    let mut schema = test_schema();
+    schema
+        .objects
+        .get_mut("enrichment")
+        .unwrap()
+        .attributes
+        .insert(
+            "labels".to_string(),
+            OcsfAttribute {
+                type_name: "json_t".to_string(),
+                caption: "Labels".to_string(),
+                is_array: true,
+                ..default_attr()
+            },
+        );
+    let classes = vec!["authentication".to_string()];
+    let opts = codegen::GenerationOptions {
+        infer_maps: true,
+        ..Default::default()
+    };
+
+    let err = codegen::generate_to_map(&schema, &classes, &opts).unwrap_err();
+    assert!(matches!(err, ocsf_proto_gen::error::Error::Codegen(_)));
+    assert!(err.to_string().contains(
+        "object 'enrichment', attribute 'labels': map type 'map<string, string>' cannot be \
+         repeated"
+    ));
+}
+
+#[test]
+fn reserve_deprecated_keeps_later_field_numbers() {
+    fn field_number(proto: &str, field: &str) -> u32 {
+        let line = proto
+            .lines()
+            .find(|l| l.contains(&format!(" {field} = ")))
+            .unwrap();
+        let rest = &line[line.find(" = ").unwrap() + 3..];
+        rest[..rest.find(|c: char| !c.is_ascii_digit()).unwrap()]
+            .parse()
+            .unwrap()
+    }
+
+    let schema = test_schema();
+    let classes = ["authentication".to_string()];
+    let skip = tempdir();
+    codegen::generate(&schema, &classes, &skip).unwrap();
+    let reserve = tempdir();
+    let opts = codegen::GenerationOptions {
+        deprecated_handling: codegen::DeprecatedHandling::Reserve,
+        ..Default::default()
+    };
+    let stats = codegen::generate_with_options(&schema, &classes, &reserve, &opts).unwrap();
+    assert_eq!(stats.deprecated_fields_reserved, 1);
+    assert_eq!(stats.deprecated_fields_skipped, 0);
+
+    let events = "ocsf/v1_7_0/events/iam/iam.proto";
+    let skipped = std::fs::read_to_string(skip.join(events)).unwrap();
+    let reserved = std::fs::read_to_string(reserve.join(events)).unwrap();
+    // `old_field` sorts between `message` and `severity_id`.
+    let old = field_number(&skipped, "severity_id");
+    assert!(reserved.contains(&format!("\treserved {old};\n\treserved \"old_field\";\n")));
+    assert_eq!(field_number(&reserved, "severity_id"), old + 1);
+    assert_eq!(
+        field_number(&reserved, "message_"),
+        field_number(&skipped, "message_")
+    );
+
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+}
+
+#[test]
+fn emit_deprecated_keeps_field_with_option() {
+    let schema = test_schema();
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        deprecated_handling: codegen::DeprecatedHandling::Emit,
+        ..Default::default()
+    };
+    let stats = codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+    assert_eq!(stats.deprecated_fields_emitted, 1);
+    assert_eq!(stats.deprecated_fields_skipped, 0);
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    let line = events
+        .lines()
+        .find(|l| l.contains(" old_field = "))
+        .expect("deprecated field emitted");
+    assert!(line.starts_with("\tstring old_field = "));
+    assert!(line.contains(" [deprecated = true];"));
+    assert!(
+        line.ends_with("// Caption: Old Field; Deprecated since 1.4.0: Use new_field instead.;")
+    );
+
+    #[cfg(feature = "descriptors")]
+    {
+        let bytes = codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+        let pool = protox::prost_reflect::DescriptorPool::decode(bytes.as_slice()).unwrap();
+        let auth = pool
+            .get_message_by_name("ocsf.v1_7_0.events.iam.Authentication")
+            .unwrap();
+        let field = auth.get_field_by_name("old_field").unwrap();
+        assert!(
+            field
+                .field_descriptor_proto()
+                .options
+                .as_ref()
+                .unwrap()
+                .deprecated()
+        );
+    }
+}
+
+#[test]
+fn descriptions_become_wrapped_leading_comments() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    auth.attributes.get_mut("message").unwrap().description =
+        "The description of the event/finding, as defined by the source. ".repeat(2)
+            + "\nSecond paragraph.";
+    schema
+        .objects
+        .get_mut("enrichment")
+        .unwrap()
+        .attributes
+        .get_mut("name")
+        .unwrap()
+        .description = "The name of the attribute.".to_string();
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_descriptions: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    let lines: Vec<&str> = events.lines().collect();
+    let field = lines
+        .iter()
+        .position(|l| l.starts_with("\tstring message_ = "))
+        .unwrap();
+    let comment: Vec<&str> = lines[..field]
+        .iter()
+        .rev()
+        .take_while(|l| l.starts_with("\t//"))
+        .copied()
+        .collect();
+    assert_eq!(comment.len(), 3);
+    assert_eq!(comment[0], "\t// Second paragraph.");
+    assert!(comment.iter().all(|l| l.len() <= 101));
+    assert!(comment[2].starts_with("\t// The description of the event/finding"));
+
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains("\t// The name of the attribute.\n\tstring name = "));
+
+    // Off by default.
+    let plain = tempdir();
+    codegen::generate(&schema, &classes, &plain).unwrap();
+    let events = std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(!events.contains("// The description of the event"));
+}
+
+#[test]
+fn enum_descriptions_comment_described_variants_only() {
+    fn describe(attr: &mut OcsfAttribute, key: &str, description: &str) {
+        let values = attr.enum_values.as_mut().unwrap();
+        values.get_mut(key).unwrap().description = Some(description.to_string());
+    }
+
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    describe(
+        auth.attributes.get_mut("activity_id").unwrap(),
+        "1",
+        "A new logon session was requested.",
+    );
+    describe(auth.attributes.get_mut("activity_id").unwrap(), "2", "");
+    let endpoint = schema.objects.get_mut("network_endpoint").unwrap();
+    describe(
+        endpoint.attributes.get_mut("type_id").unwrap(),
+        "1",
+        "A server.",
+    );
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_enum_descriptions: true,
+        ..Default::default()
+    };
+
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let class_enums =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/enums/enums.proto")).unwrap();
+    assert!(class_enums.contains(
+        "\tAUTHENTICATION_ACTIVITY_ID_UNKNOWN = 0;\n\
+         \t// A new logon session was requested.\n\
+         \tAUTHENTICATION_ACTIVITY_ID_LOGON = 1;\n\
+         \tAUTHENTICATION_ACTIVITY_ID_LOGOFF = 2;\n"
+    ));
+    let object_enums =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/enums/enums.proto")).unwrap();
+    assert!(object_enums.contains("\t// A server.\n\tNETWORK_ENDPOINT_TYPE_ID_SERVER = 1;\n"));
+
+    let plain = tempdir();
+    codegen::generate(&schema, &classes, &plain).unwrap();
+    let class_enums =
+        std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/enums/enums.proto")).unwrap();
+    assert!(!class_enums.contains("// A new logon session"));
+}
+
+#[test]
+fn duplicate_enum_values_allow_alias() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    let values = auth
+        .attributes
+        .get_mut("activity_id")
+        .unwrap()
+        .enum_values
+        .as_mut()
+        .unwrap();
+    values.insert(
+        "01".to_string(),
+        OcsfEnumValue {
+            caption: "Log On".to_string(),
+            description: None,
+        },
+    );
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+
+    codegen::generate(&schema, &classes, &dir).unwrap();
+
+    let enums =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/enums/enums.proto")).unwrap();
+    assert_eq!(enums.matches("option allow_alias = true;").count(), 1);
+    assert!(enums.contains(
+        "enum AUTHENTICATION_ACTIVITY_ID {\n\toption allow_alias = true;\n\
+         \tAUTHENTICATION_ACTIVITY_ID_UNKNOWN = 0;\n"
+    ));
+    assert!(enums.contains("\tAUTHENTICATION_ACTIVITY_ID_LOG_ON = 1;\n"));
+    assert!(enums.contains("\tAUTHENTICATION_ACTIVITY_ID_LOGON = 1;\n"));
+
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &Default::default()).unwrap();
+}
+
+#[test]
+fn identical_enum_aliases_are_emitted_once() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    let values = auth
+        .attributes
+        .get_mut("activity_id")
+        .unwrap()
+        .enum_values
+        .as_mut()
+        .unwrap();
+    let caption = values["1"].caption.clone();
+    values.insert(
+        "01".to_string(),
+        OcsfEnumValue {
+            caption,
+            description: None,
+        },
+    );
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+
+    codegen::generate(&schema, &classes, &dir).unwrap();
+
+    let enums =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/enums/enums.proto")).unwrap();
+    assert_eq!(
+        enums
+            .matches("\tAUTHENTICATION_ACTIVITY_ID_LOGON = 1;\n")
+            .count(),
+        1
+    );
+    assert!(!enums.contains("option allow_alias = true;"));
+
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &Default::default()).unwrap();
+}
+
+#[test]
+fn reserve_enum_gaps_covers_unused_values_up_to_the_max() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    let values = auth
+        .attributes
+        .get_mut("activity_id")
+        .unwrap()
+        .enum_values
+        .as_mut()
+        .unwrap();
+    // Drop 0 so the synthetic zero value has to count as used, and add a
+    // negative value, an alias of 1, and single-value gaps.
+    values.remove("0");
+    for (key, caption) in [
+        ("-2", "Legacy"),
+        ("01", "Log On"),
+        ("5", "Renew"),
+        ("7", "Lock"),
+    ] {
+        values.insert(
+            key.to_string(),
+            OcsfEnumValue {
+                caption: caption.to_string(),
+                description: None,
+            },
+        );
+    }
+    let classes = vec!["authentication".to_string()];
+    let class_enums = |opts: &codegen::GenerationOptions| {
+        codegen::generate_to_map(&schema, &classes, opts).unwrap()
+            [Path::new("ocsf/v1_7_0/events/iam/enums/enums.proto")]
+        .clone()
+    };
+
+    let opts = codegen::GenerationOptions {
+        reserve_enum_gaps: true,
+        ..Default::default()
+    };
+    let enums = class_enums(&opts);
+    assert!(enums.contains(
+        "\tAUTHENTICATION_ACTIVITY_ID_OTHER = 99;\n\
+         \treserved -1;\n\
+         \treserved 3 to 4;\n\
+         \treserved 6;\n\
+         \treserved 8 to 98;\n\
+         }\n"
+    ));
+    // Contiguous enums get nothing reserved.
+    let objects = codegen::generate_to_map(&schema, &classes, &opts).unwrap()
+        [Path::new("ocsf/v1_7_0/objects/enums/enums.proto")]
+    .clone();
+    assert!(objects.contains("enum NETWORK_ENDPOINT_TYPE_ID {"));
+    assert!(!objects.contains("reserved"));
+
+    assert!(!class_enums(&Default::default()).contains("reserved"));
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+}
+
+#[test]
+fn punctuation_only_captions_get_value_names() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    let values = auth
+        .attributes
+        .get_mut("activity_id")
+        .unwrap()
+        .enum_values
+        .as_mut()
+        .unwrap();
+    values.insert(
+        "5".to_string(),
+        OcsfEnumValue {
+            caption: "***".to_string(),
+            description: None,
+        },
+    );
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+
+    codegen::generate(&schema, &classes, &dir).unwrap();
+
+    let enums =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/enums/enums.proto")).unwrap();
+    assert!(enums.contains("\tAUTHENTICATION_ACTIVITY_ID_VALUE_5 = 5;\n"));
+    assert!(!enums.contains("__ ="));
+    let map = std::fs::read_to_string(dir.join("ocsf/v1_7_0/enum-value-map.json")).unwrap();
+    assert!(map.contains("\"AUTHENTICATION_ACTIVITY_ID_VALUE_5\""));
+
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &Default::default()).unwrap();
+}
+
+#[test]
+fn colliding_object_message_names_fail() {
+    let mut schema = test_schema();
+    schema.objects.insert(
+        "win/network_endpoint".to_string(),
+        OcsfObject {
+            name: "win/network_endpoint".to_string(),
+            caption: "Windows Network Endpoint".to_string(),
+            description: String::new(),
+            extends: None,
+            attributes: BTreeMap::from([(
+                "hostname".to_string(),
+                OcsfAttribute {
+                    type_name: "hostname_t".to_string(),
+                    caption: "Hostname".to_string(),
+                    ..default_attr()
+                },
+            )]),
+            observable: None,
+            deprecated: None,
+        },
+    );
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert(
+            "win_endpoint".to_string(),
+            OcsfAttribute {
+                type_name: "object_t".to_string(),
+                caption: "Windows Endpoint".to_string(),
+                object_type: Some("win/network_endpoint".to_string()),
+                ..default_attr()
+            },
+        );
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+
+    let err = codegen::generate(&schema, &classes, &dir)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(
+            "objects 'network_endpoint' and 'win/network_endpoint' would generate the same \
+             message NetworkEndpoint"
+        ),
+        "{err}"
+    );
+    assert!(walkdir(&dir).is_empty());
+    let opts = codegen::GenerationOptions {
+        extension_packages: true,
+        ..Default::default()
+    };
+    assert!(codegen::generate_with_options(&schema, &classes, &dir, &opts).is_err());
+    assert!(codegen::generate_standalone(&schema, "authentication", &Default::default()).is_err());
+}
+
+#[test]
+fn min_requirement_leaves_out_lower_attributes_and_their_objects() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    auth.attributes.get_mut("message").unwrap().requirement = Some("recommended".to_string());
+    let optional = auth
+        .attributes
+        .values()
+        .filter(|attr| {
+            !matches!(
+                attr.requirement.as_deref(),
+                Some("required" | "recommended")
+            )
+        })
+        .count();
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        min_requirement: Some(codegen::Requirement::Recommended),
+        ..Default::default()
+    };
+
+    let stats = codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+    assert_eq!(stats.requirement_filtered, optional);
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains(" activity_id = 1;"));
+    assert!(events.contains("\tstring message_ = 2;"));
+    assert!(!events.contains(" time = "));
+    assert!(!events.contains(" src_endpoint = "));
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(!objects.contains("message NetworkEndpoint {"));
+
+    // Under Reserve, the left-out attributes keep their numbers.
+    let reserve = tempdir();
+    let opts = codegen::GenerationOptions {
+        deprecated_handling: codegen::DeprecatedHandling::Reserve,
+        ..opts
+    };
+    codegen::generate_with_options(&schema, &classes, &reserve, &opts).unwrap();
+    let events = std::fs::read_to_string(reserve.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("\treserved \"src_endpoint\";"));
+    assert!(events.contains("\treserved \"time\";"));
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+
+    assert!(codegen::Requirement::Optional < codegen::Requirement::Recommended);
+    assert!(codegen::Requirement::Recommended < codegen::Requirement::Required);
+}
+
+#[test]
+fn include_profiles_option_filters_fields_and_objects() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    for (name, profile) in [("cloud_region", "cloud"), ("host_name", "host")] {
+        auth.attributes.insert(
+            name.to_string(),
+            OcsfAttribute {
+                type_name: "string_t".to_string(),
+                caption: name.to_string(),
+                profile: Some(profile.to_string()),
+                ..default_attr()
+            },
+        );
+    }
+    // Only reachable through a left-out profile attribute.
+    auth.attributes.get_mut("enrichments").unwrap().profile = Some("data_classification".into());
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        include_profiles: Some(["cloud".to_string()].into()),
+        ..Default::default()
+    };
+
+    let stats = codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+    assert_eq!(stats.profile_filtered, 2);
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains(" cloud_region = "));
+    assert!(events.contains(" activity_id = "));
+    assert!(!events.contains(" host_name = "));
+    assert!(!events.contains(" enrichments = "));
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains("message NetworkEndpoint {"));
+    assert!(!objects.contains("message Enrichment {"));
+
+    // Every attribute is kept without the option.
+    let plain = tempdir();
+    let stats = codegen::generate(&schema, &classes, &plain).unwrap();
+    assert_eq!(stats.profile_filtered, 0);
+    let events = std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains(" host_name = "));
+}
+
+#[test]
+fn group_siblings_links_id_and_name_fields() {
+    let mut schema = test_schema();
+    let auth = schema.classes.get_mut("authentication").unwrap();
+    auth.attributes.insert(
+        "activity_name".to_string(),
+        OcsfAttribute {
+            type_name: "string_t".to_string(),
+            caption: "Activity".to_string(),
+            ..default_attr()
+        },
+    );
+    // A sibling the class does not define is ignored.
+    auth.attributes.get_mut("message").unwrap().sibling = Some("missing".to_string());
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions::builder()
+        .group_siblings(true)
+        .build();
+
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    let line = |field: &str| {
+        events
+            .lines()
+            .find(|l| l.contains(&format!(" {field} = ")))
+            .unwrap()
+            .to_string()
+    };
+    assert!(line("activity_id").ends_with("// Caption: Activity ID; Sibling: activity_name;"));
+    assert!(line("activity_name").ends_with("// Caption: Activity; Sibling: activity_id;"));
+    assert!(!line("message_").contains("Sibling:"));
+
+    let plain = tempdir();
+    codegen::generate(&schema, &classes, &plain).unwrap();
+    let events = std::fs::read_to_string(plain.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(!events.contains("Sibling:"));
+}
+
+#[test]
+fn object_graph_dot_lists_classes_objects_and_references() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    schema
+        .objects
+        .get_mut("network_endpoint")
+        .unwrap()
+        .attributes
+        .insert(
+            "service".to_string(),
+            OcsfAttribute {
+                type_name: "object_t".to_string(),
+                caption: "Service".to_string(),
+                object_type: Some("win/win_service".to_string()),
+                ..default_attr()
+            },
+        );
+
+    let dot = codegen::object_graph_dot(&schema, &["authentication".to_string()]).unwrap();
+    assert!(dot.starts_with("digraph ocsf {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("\t\"class/authentication\" [label=\"authentication\", shape=box];\n"));
+    assert!(dot.contains("\t\"object/win_service\" [label=\"win_service\"];\n"));
+    assert!(dot.contains(
+        "\t\"class/authentication\" -> \"object/enrichment\" [label=\"enrichments\", style=dashed];\n"
+    ));
+    assert!(dot.contains(
+        "\t\"class/authentication\" -> \"object/win_service\" [label=\"win_service\"];\n"
+    ));
+    assert!(dot.contains(
+        "\t\"object/network_endpoint\" -> \"object/win_service\" [label=\"service\"];\n"
+    ));
+    assert_eq!(dot.matches(" -> ").count(), 5);
+
+    let err = codegen::object_graph_dot(&schema, &["nope".to_string()]).unwrap_err();
+    assert!(matches!(
+        err,
+        ocsf_proto_gen::error::Error::ClassNotFound { .. }
+    ));
+}
+
+#[test]
+fn detect_cycles_reports_self_and_mutual_references() {
+    let mut schema = test_schema();
+    let needed = resolve_object_graph(&schema, &["authentication".to_string()]);
+    assert!(codegen::detect_cycles(&schema, &needed).is_empty());
+
+    let reference = |object_type: &str| OcsfAttribute {
+        type_name: "object_t".to_string(),
+        caption: object_type.to_string(),
+        object_type: Some(object_type.to_string()),
+        ..default_attr()
+    };
+    for (name, caption, attributes) in [
+        (
+            "process",
+            "Process",
+            BTreeMap::from([
+                ("parent_process".to_string(), reference("process")),
+                ("session".to_string(), reference("session")),
+            ]),
+        ),
+        (
+            "session",
+            "Session",
+            BTreeMap::from([("process".to_string(), reference("process"))]),
+        ),
+    ] {
+        schema.objects.insert(
+            name.to_string(),
+            OcsfObject {
+                name: name.to_string(),
+                caption: caption.to_string(),
+                description: String::new(),
+                extends: None,
+                attributes,
+                observable: None,
+                deprecated: None,
+            },
+        );
+    }
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert("process".to_string(), reference("process"));
+
+    let needed = resolve_object_graph(&schema, &["authentication".to_string()]);
+    assert_eq!(
+        codegen::detect_cycles(&schema, &needed),
+        [
+            vec!["process".to_string(), "process".to_string()],
+            vec![
+                "process".to_string(),
+                "session".to_string(),
+                "process".to_string()
+            ],
+        ]
+    );
+
+    // Cycles outside the requested objects are not reported.
+    let needed = std::collections::BTreeSet::from(["process".to_string()]);
+    assert_eq!(
+        codegen::detect_cycles(&schema, &needed),
+        [vec!["process".to_string(), "process".to_string()]]
+    );
+}
+
+#[test]
+fn manifest_lists_written_protos_and_matches_return_value() {
+    let schema = test_schema();
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        emit_category_enum: true,
+        ..Default::default()
+    };
+
+    let manifest = codegen::generate_with_manifest(&schema, &classes, &dir, &opts).unwrap();
+    assert_eq!(manifest.ocsf_version, "1.7.0");
+    assert_eq!(manifest.classes, classes);
+    assert_eq!(manifest.objects, ["enrichment", "network_endpoint"]);
+    let mut protos: Vec<String> = walkdir(&dir)
+        .into_iter()
+        .map(|path| {
+            path.strip_prefix(&dir)
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .filter(|path| path.ends_with(".proto"))
+        .collect();
+    protos.sort();
+    assert_eq!(manifest.protos, protos);
+    assert!(
+        manifest
+            .protos
+            .contains(&"ocsf/v1_7_0/category.proto".to_string())
+    );
+
+    let json_str = std::fs::read_to_string(dir.join("ocsf/v1_7_0/manifest.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json_str).unwrap();
+    assert_eq!(json["ocsf_version"], "1.7.0");
+    assert_eq!(json["protos"].as_array().unwrap().len(), protos.len());
+    assert_eq!(json["stats"]["classes_generated"], 1);
+    assert_eq!(
+        json["stats"]["objects_generated"],
+        manifest.stats.objects_generated
+    );
+}
+
+#[test]
+fn single_file_layout_writes_one_import_free_proto() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        layout: codegen::OutputLayout::SingleFile,
+        extension_packages: true,
+        emit_category_enum: true,
+        emit_event_envelope: true,
+        ..Default::default()
+    };
+
+    let manifest = codegen::generate_with_manifest(&schema, &classes, &dir, &opts).unwrap();
+    assert_eq!(manifest.protos, ["ocsf/v1_7_0/ocsf.proto"]);
+    let mut written: Vec<std::path::PathBuf> = walkdir(&dir)
+        .into_iter()
+        .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+        .collect();
+    written.sort();
+    assert_eq!(
+        codegen::planned_outputs(&schema, &classes, &opts).unwrap(),
+        written
+    );
+
+    let proto = std::fs::read_to_string(dir.join("ocsf/v1_7_0/ocsf.proto")).unwrap();
+    assert!(proto.contains("package ocsf.v1_7_0;"));
+    assert!(!proto.contains("import "), "single file has imports");
+    assert!(proto.contains("\tNetworkEndpoint src_endpoint = "));
+    assert!(proto.contains("\tAUTHENTICATION_ACTIVITY_ID activity_id = "));
+    assert!(proto.contains("\tWinService win_service = "));
+    assert!(proto.contains("message WinService {"));
+    assert!(proto.contains("enum OcsfCategory {"));
+    assert!(proto.contains("\t\tAuthentication authentication = 3002;"));
+    // Types are defined before the messages that use them.
+    assert!(
+        proto.find("enum AUTHENTICATION_ACTIVITY_ID {") < proto.find("message Authentication {")
+    );
+    assert!(proto.find("message NetworkEndpoint {") < proto.find("message Authentication {"));
+}
+
+#[cfg(feature = "descriptors")]
+#[test]
+fn single_file_layout_compiles() {
+    use protox::prost_reflect::DescriptorPool;
+
+    let opts = codegen::GenerationOptions {
+        layout: codegen::OutputLayout::SingleFile,
+        emit_event_envelope: true,
+        ..Default::default()
+    };
+    let bytes =
+        codegen::generate_descriptor_set(&test_schema(), &["authentication".to_string()], &opts)
+            .unwrap();
+    let pool = DescriptorPool::decode(bytes.as_slice()).unwrap();
+    assert!(
+        pool.get_message_by_name("ocsf.v1_7_0.Authentication")
+            .is_some()
+    );
+    assert!(
+        pool.get_message_by_name("ocsf.v1_7_0.NetworkEndpoint")
+            .is_some()
+    );
+    assert!(pool.get_message_by_name("ocsf.v1_7_0.OcsfEvent").is_some());
+}
+
+#[test]
+fn single_file_layout_rejects_class_and_object_name_clash() {
+    let mut schema = test_schema();
+    schema.classes.insert(
+        "network_endpoint".to_string(),
+        OcsfClass {
+            name: "network_endpoint".to_string(),
+            uid: 4999,
+            caption: "Network Endpoint".to_string(),
+            description: String::new(),
+            extends: "network".to_string(),
+            category: "network".to_string(),
+            category_uid: 4,
+            category_name: "Network Activity".to_string(),
+            profiles: vec![],
+            attributes: BTreeMap::from([(
+                "message".to_string(),
+                OcsfAttribute {
+                    type_name: "string_t".to_string(),
+                    caption: "Message".to_string(),
+                    ..default_attr()
+                },
+            )]),
+            deprecated: None,
+        },
+    );
+    let classes = ["authentication".to_string(), "network_endpoint".to_string()];
+    let opts = codegen::GenerationOptions {
+        layout: codegen::OutputLayout::SingleFile,
+        ..Default::default()
+    };
+
+    let err = codegen::generate_to_map(&schema, &classes, &opts)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("class 'network_endpoint' and object 'network_endpoint'"),
+        "{err}"
+    );
+    // The split layout keeps them in separate packages.
+    codegen::generate_to_map(&schema, &classes, &Default::default()).unwrap();
+}
+
+#[test]
+fn single_file_layout_rejects_options_that_need_imports() {
+    let schema = test_schema();
+    let classes = ["authentication".to_string()];
+    let single_file =
+        || codegen::GenerationOptions::builder().layout(codegen::OutputLayout::SingleFile);
+    let rejected = [
+        (
+            single_file()
+                .timestamp_mapping(codegen::TimestampMapping::WellKnownTimestamp)
+                .build(),
+            "the well-known timestamp mapping",
+        ),
+        (
+            single_file()
+                .json_mapping(codegen::JsonMapping::WellKnownStruct)
+                .build(),
+            "the well-known struct mapping",
+        ),
+        (
+            single_file().emit_pgv_rules(true).build(),
+            "protoc-gen-validate rules",
+        ),
+        (
+            single_file().emit_profile_options(true).build(),
+            "the profiles option",
+        ),
+        (
+            single_file().emit_uid_options(true).build(),
+            "the class and category UID options",
+        ),
+        (
+            single_file()
+                .type_overrides(BTreeMap::from([(
+                    "uuid_t".to_string(),
+                    "acme.Uuid".to_string(),
+                )]))
+                .build(),
+            "the override of 'uuid_t' must be a proto scalar or a map of them",
+        ),
+        (
+            single_file()
+                .json_field_overrides(BTreeMap::from([(
+                    "Metadata.labels".to_string(),
+                    "map<string, acme.Label>".to_string(),
+                )]))
+                .build(),
+            "the override of 'Metadata.labels'",
+        ),
+    ];
+    for (opts, message) in rejected {
+        let err = codegen::generate_to_map(&schema, &classes, &opts)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("codegen error: single-file output has no imports"),
+            "{err}"
+        );
+        assert!(err.contains(message), "{err}");
+        assert!(codegen::estimate(&schema, &classes, &opts).is_err());
+    }
+
+    // Import-free overrides and inferred maps still apply.
+    let opts = single_file()
+        .type_overrides(BTreeMap::from([("ip_t".to_string(), "bytes".to_string())]))
+        .infer_maps(true)
+        .map_attribute_names(["unmapped".to_string()].into())
+        .build();
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    let proto = &files[Path::new("ocsf/v1_7_0/ocsf.proto")];
+    assert!(proto.contains("\tbytes ip = "));
+    assert!(proto.contains("\tmap<string, string> unmapped = "));
+    assert!(!proto.contains("import "));
+}
+
+#[test]
+fn package_prefix_applies_to_packages_imports_and_references() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    schema.classes.get_mut("authentication").unwrap().profiles = vec!["host".to_string()];
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        package_prefix: "com.acme.ocsf".to_string(),
+        extension_packages: true,
+        emit_profile_options: true,
+        emit_category_enum: true,
+        emit_event_envelope: true,
+        ..Default::default()
+    };
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let protos: Vec<std::path::PathBuf> = walkdir(&dir)
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "proto"))
+        .collect();
+    assert!(
+        dir.join("com/acme/ocsf/v1_7_0/objects/objects.proto")
+            .exists()
+    );
+    assert!(
+        dir.join("com/acme/ocsf/v1_7_0/ext/win/objects/objects.proto")
+            .exists()
+    );
+    assert!(!dir.join("ocsf").exists());
+    for path in &protos {
+        let proto = std::fs::read_to_string(path).unwrap();
+        for line in proto.lines() {
+            let line = line.trim();
+            if let Some(package) = line.strip_prefix("package ") {
+                assert!(package.starts_with("com.acme.ocsf"), "{line}");
+            } else if let Some(import) = line.strip_prefix("import \"") {
+                assert!(
+                    import.starts_with("com/acme/ocsf/") || import.starts_with("google/"),
+                    "{line}"
+                );
+            }
+            // Every qualified reference carries the full prefix.
+            for (i, _) in line.match_indices("ocsf.v1_7_0") {
+                assert!(
+                    line[..i].ends_with("com.acme."),
+                    "{}: {line}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    let auth =
+        std::fs::read_to_string(dir.join("com/acme/ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(auth.contains("package com.acme.ocsf.v1_7_0.events.iam;"));
+    assert!(auth.contains("import \"com/acme/ocsf/v1_7_0/objects/objects.proto\";"));
+    assert!(auth.contains("\tcom.acme.ocsf.v1_7_0.objects.NetworkEndpoint src_endpoint = "));
+    assert!(auth.contains("\tcom.acme.ocsf.v1_7_0.ext.win.objects.WinService win_service = "));
+    assert!(auth.contains("import \"com/acme/ocsf/ocsf_options.proto\";"));
+    assert!(auth.contains("option (com.acme.ocsf.profiles) = "));
+    let options = std::fs::read_to_string(dir.join("com/acme/ocsf/ocsf_options.proto")).unwrap();
+    assert!(options.contains("package com.acme.ocsf;"));
+    codegen::check_import_graph(&dir).unwrap();
+    assert_eq!(
+        codegen::planned_outputs(&schema, &classes, &opts)
+            .unwrap()
+            .len(),
+        walkdir(&dir).len()
+    );
+}
+
+#[test]
+fn package_prefix_must_be_dotted_identifiers() {
+    for prefix in ["", "com..acme", "com.1acme", "com/acme", "com.acme."] {
+        let opts = codegen::GenerationOptions::builder()
+            .package_prefix(prefix)
+            .build();
+        let err = codegen::generate_to_map(&test_schema(), &["authentication".to_string()], &opts)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("invalid package prefix"),
+            "{prefix}: {err}"
+        );
+    }
+}
+
+#[test]
+fn slug_style_sets_version_segment_everywhere() {
+    let mut schema = test_schema();
+    schema.version = "1.8.0-dev".to_string();
+    let classes = ["authentication".to_string()];
+    let styles = [
+        (codegen::SlugStyle::Default, "v1_8_0_dev"),
+        (codegen::SlugStyle::NoPrefix, "1_8_0_dev"),
+        (
+            codegen::SlugStyle::Custom(Arc::new(|version| format!("v{version}"))),
+            "v1.8.0-dev",
+        ),
+    ];
+    for (style, slug) in styles {
+        let dir = tempdir();
+        let opts = codegen::GenerationOptions::builder()
+            .slug_style(style)
+            .emit_event_envelope(true)
+            .build();
+        codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+        let written = walkdir(&dir);
+        assert!(
+            written
+                .iter()
+                .all(|path| path.starts_with(dir.join("ocsf").join(slug))),
+            "{slug}: {written:?}"
+        );
+        assert_eq!(
+            codegen::planned_outputs(&schema, &classes, &opts)
+                .unwrap()
+                .len(),
+            written.len()
+        );
+        let events =
+            std::fs::read_to_string(dir.join(format!("ocsf/{slug}/events/iam/iam.proto"))).unwrap();
+        assert!(events.contains(&format!("package ocsf.{slug}.events.iam;")));
+        assert!(events.contains(&format!("import \"ocsf/{slug}/objects/objects.proto\";")));
+        assert!(events.contains(&format!(
+            "\tocsf.{slug}.objects.NetworkEndpoint src_endpoint = "
+        )));
+        let envelope =
+            std::fs::read_to_string(dir.join(format!("ocsf/{slug}/ocsf_event.proto"))).unwrap();
+        assert!(envelope.contains(&format!("package ocsf.{slug};")));
+        assert!(envelope.contains(&format!("import \"ocsf/{slug}/events/iam/iam.proto\";")));
+        assert!(envelope.contains(&format!("\t\tocsf.{slug}.events.iam.Authentication ")));
+    }
+}
+
+#[test]
+fn custom_slug_must_be_one_path_segment() {
+    let opts = codegen::GenerationOptions::builder()
+        .slug_style(codegen::SlugStyle::Custom(Arc::new(|version| {
+            format!("v/{version}")
+        })))
+        .build();
+    let err = codegen::generate_to_map(&test_schema(), &["authentication".to_string()], &opts)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("invalid version slug 'v/1.7.0'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn type_overrides_replace_builtin_mapping_except_objects_and_enums() {
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions::builder()
+        .type_overrides(BTreeMap::from([
+            ("ip_t".to_string(), "acme.IpAddress".to_string()),
+            ("port_t".to_string(), "uint32".to_string()),
+            ("timestamp_t".to_string(), "fixed64".to_string()),
+            ("integer_t".to_string(), "int64".to_string()),
+            ("object_t".to_string(), "bytes".to_string()),
+        ]))
+        .extra_imports(vec!["acme/types.proto".to_string()])
+        .timestamp_mapping(codegen::TimestampMapping::WellKnownTimestamp)
+        .emit_pgv_rules(true)
+        .build();
+    codegen::generate_with_options(&test_schema(), &classes, &dir, &opts).unwrap();
+
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains("import \"acme/types.proto\";"));
+    assert!(objects.contains("\tacme.IpAddress ip = "));
+    // The override replaces the type, so the int32 port range no longer applies.
+    assert!(objects.contains("\tuint32 port = "));
+    assert!(!objects.contains("lte: 65535"));
+    // Integer enums keep their generated enum type.
+    assert!(objects.contains("\tocsf.v1_7_0.objects.enums.NETWORK_ENDPOINT_TYPE_ID type_id = "));
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("import \"acme/types.proto\";"));
+    assert!(!events.contains("google/protobuf/timestamp.proto"));
+    assert!(events.contains("\tfixed64 time = "));
+    assert!(events.contains("\tocsf.v1_7_0.objects.NetworkEndpoint src_endpoint = "));
+    assert!(
+        events.contains("\tocsf.v1_7_0.events.iam.enums.AUTHENTICATION_ACTIVITY_ID activity_id = ")
+    );
+
+    // Files without an overridden field don't get the extra imports.
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions::builder()
+        .type_overrides(BTreeMap::from([(
+            "ip_t".to_string(),
+            "acme.IpAddress".to_string(),
+        )]))
+        .extra_imports(vec!["acme/types.proto".to_string()])
+        .build();
+    codegen::generate_with_options(&test_schema(), &classes, &dir, &opts).unwrap();
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(!events.contains("acme/types.proto"));
+}
+
+#[test]
+fn int32_enum_mode_lists_values_and_writes_no_enum_files() {
+    let schema = test_schema();
+    let classes = ["authentication".to_string()];
+    for layout in [codegen::EnumLayout::Shared, codegen::EnumLayout::PerEnum] {
+        let dir = tempdir();
+        let opts = codegen::GenerationOptions::builder()
+            .enum_mode(codegen::EnumMode::Int32WithComment)
+            .enum_layout(layout)
+            .build();
+        let stats = codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+        assert_eq!(stats.enums_generated, 0);
+
+        let written = walkdir(&dir);
+        assert!(
+            !written
+                .iter()
+                .any(|path| path.to_string_lossy().contains("/enums/")),
+            "{written:?}"
+        );
+        assert_eq!(
+            codegen::planned_outputs(&schema, &classes, &opts)
+                .unwrap()
+                .len(),
+            written.len()
+        );
+
+        let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+        assert!(!events.contains("enums"), "{events}");
+        assert!(!events.contains("enum "));
+        let activity = events
+            .lines()
+            .find(|line| line.contains(" activity_id = "))
+            .unwrap();
+        assert!(
+            activity.starts_with("\tint32 activity_id = 1;"),
+            "{activity}"
+        );
+        assert!(
+            activity.ends_with(" Values: 0=Unknown, 1=Logon, 2=Logoff, 99=Other;"),
+            "{activity}"
+        );
+        let objects =
+            std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+        assert!(!objects.contains("enums"));
+        assert!(objects.contains("\tint32 type_id = "));
+    }
+}
+
+#[cfg(feature = "descriptors")]
+#[test]
+fn int32_enum_mode_compiles() {
+    let opts = codegen::GenerationOptions::builder()
+        .enum_mode(codegen::EnumMode::Int32WithComment)
+        .build();
+    codegen::generate_descriptor_set(&test_schema(), &["authentication".to_string()], &opts)
+        .unwrap();
+}
+
+#[test]
+fn prost_build_inputs_lists_version_protos_and_include_root() {
+    let mut schema = test_schema();
+    add_extension_object(&mut schema);
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions {
+        extension_packages: true,
+        emit_profile_options: true,
+        ..Default::default()
+    };
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let root = codegen::version_root(&schema, &opts);
+    let inputs = codegen::prost_build_inputs(&dir, &root).unwrap();
+    assert_eq!(inputs.includes, std::slice::from_ref(&dir));
+    let mut expected: Vec<std::path::PathBuf> = walkdir(&dir.join("ocsf/v1_7_0"))
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "proto"))
+        .collect();
+    expected.sort();
+    assert_eq!(inputs.protos, expected);
+    assert!(
+        inputs
+            .protos
+            .contains(&dir.join("ocsf/v1_7_0/ext/win/objects/objects.proto"))
+    );
+
+    assert!(matches!(
+        codegen::prost_build_inputs(&dir, Path::new("ocsf/v9_9_9")),
+        Err(ocsf_proto_gen::error::Error::Read { .. })
+    ));
+
+    // A package prefix moves the version directory.
+    let prefixed_dir = tempdir();
+    let prefixed = codegen::GenerationOptions::builder()
+        .package_prefix("acme.ocsf")
+        .build();
+    codegen::generate_with_options(
+        &schema,
+        &["authentication".to_string()],
+        &prefixed_dir,
+        &prefixed,
+    )
+    .unwrap();
+    let root = codegen::version_root(&schema, &prefixed);
+    let inputs = codegen::prost_build_inputs(&prefixed_dir, &root).unwrap();
+    assert!(
+        inputs
+            .protos
+            .contains(&prefixed_dir.join("acme/ocsf/v1_7_0/objects/objects.proto"))
+    );
+    assert!(codegen::prost_build_inputs(&prefixed_dir, Path::new("ocsf/v1_7_0")).is_err());
+}
+
+#[test]
+fn descriptions_comment_class_and_object_messages() {
+    let mut schema = test_schema();
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .description =
+        "Authentication events report authentication session activities.".to_string();
+    schema
+        .objects
+        .get_mut("network_endpoint")
+        .unwrap()
+        .description =
+        "The network endpoint object describes characteristics of a network endpoint.".to_string();
+    schema.objects.get_mut("enrichment").unwrap().description = String::new();
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions::builder()
+        .emit_descriptions(true)
+        .build();
+
+    codegen::generate_with_options(&schema, &["authentication".to_string()], &dir, &opts).unwrap();
+
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains(
+        "// Class UID: 3002\n\
+         // Authentication events report authentication session activities.\n\
+         message Authentication {\n"
+    ));
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(objects.contains(
+        "\n\n// The network endpoint object describes characteristics of a network endpoint.\n\
+         message NetworkEndpoint {\n"
+    ));
+    // An empty description adds no comment lines.
+    let lines: Vec<&str> = objects.lines().collect();
+    let enrichment = lines
+        .iter()
+        .position(|l| *l == "message Enrichment {")
+        .unwrap();
+    assert_eq!(lines[enrichment - 1], "");
+}
+
+#[test]
+fn uid_options_set_on_every_event_class() {
+    let schema = test_schema();
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions::builder()
+        .emit_uid_options(true)
+        .build();
+
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let options = std::fs::read_to_string(dir.join("ocsf/ocsf_options.proto")).unwrap();
+    assert!(options.contains("\tint32 class_uid = 50001;\n"));
+    assert!(options.contains("\tint32 category_uid = 50002;\n"));
+    let events = std::fs::read_to_string(dir.join("ocsf/v1_7_0/events/iam/iam.proto")).unwrap();
+    assert!(events.contains("import \"ocsf/ocsf_options.proto\";"));
+    assert!(events.contains(
+        "message Authentication {\n\
+         \toption (ocsf.class_uid) = 3002;\n\
+         \toption (ocsf.category_uid) = 3;\n"
+    ));
+    let objects = std::fs::read_to_string(dir.join("ocsf/v1_7_0/objects/objects.proto")).unwrap();
+    assert!(!objects.contains("option (ocsf."));
+    assert!(
+        codegen::planned_outputs(&schema, &classes, &opts)
+            .unwrap()
+            .contains(&std::path::PathBuf::from("ocsf/ocsf_options.proto"))
+    );
+    #[cfg(feature = "descriptors")]
+    codegen::generate_descriptor_set(&schema, &classes, &opts).unwrap();
+
+    // Standalone output has no imports to define the options with.
+    assert!(codegen::generate_standalone(&schema, "authentication", &opts).is_err());
+}
+
+#[test]
+fn enum_map_by_number_nests_captions_under_enum_names() {
+    let schema = test_schema();
+    let classes = ["authentication".to_string()];
+    let dir = tempdir();
+    let opts = codegen::GenerationOptions::builder()
+        .emit_enum_map_by_number(true)
+        .enum_map_globs(vec!["AUTHENTICATION_*".to_string()])
+        .build();
+    codegen::generate_with_options(&schema, &classes, &dir, &opts).unwrap();
+
+    let content =
+        std::fs::read_to_string(dir.join("ocsf/v1_7_0/enum-value-map-by-number.json")).unwrap();
+    let map: BTreeMap<String, BTreeMap<String, String>> = serde_json::from_str(&content).unwrap();
+    assert!(map.keys().all(|name| name.starts_with("AUTHENTICATION_")));
+    assert_eq!(
+        map["AUTHENTICATION_ACTIVITY_ID"],
+        BTreeMap::from([
+            ("0".to_string(), "Unknown".to_string()),
+            ("1".to_string(), "Logon".to_string()),
+            ("2".to_string(), "Logoff".to_string()),
+            ("99".to_string(), "Other".to_string()),
+        ])
+    );
+    // Values are in numeric order, so 99 comes after 2.
+    assert!(content.find("\"2\"") < content.find("\"99\""));
+
+    // The flat map is unchanged and written either way.
+    let flat: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(dir.join("ocsf/v1_7_0/enum-value-map.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(flat["AUTHENTICATION_ACTIVITY_ID_LOGON"]["value"], 1);
+    assert!(
+        codegen::planned_outputs(&schema, &classes, &opts)
+            .unwrap()
+            .contains(&std::path::PathBuf::from(
+                "ocsf/v1_7_0/enum-value-map-by-number.json"
+            ))
+    );
+
+    let plain = tempdir();
+    codegen::generate(&schema, &classes, &plain).unwrap();
+    assert!(
+        !plain
+            .join("ocsf/v1_7_0/enum-value-map-by-number.json")
+            .exists()
+    );
+}
+
+#[test]
+fn verify_enum_map_accepts_generated_output() {
+    let mut schema = test_schema();
+    // The map still lists enums of deprecated attributes, which the protos
+    // leave out; they are not compared.
+    let status = OcsfAttribute {
+        type_name: "integer_t".to_string(),
+        caption: "Old Status ID".to_string(),
+        enum_values: Some(BTreeMap::from([(
+            "1".to_string(),
+            OcsfEnumValue {
+                caption: "Success".to_string(),
+                description: None,
+            },
+        )])),
+        deprecated: Some(OcsfDeprecated {
+            message: "Use status_id.".to_string(),
+            since: "1.1.0".to_string(),
+        }),
+        ..default_attr()
+    };
+    schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .insert("old_status_id".to_string(), status);
+    let classes = ["authentication".to_string()];
+
+    for layout in [
+        codegen::OutputLayout::Split,
+        codegen::OutputLayout::SingleFile,
+    ] {
+        let opts = codegen::GenerationOptions::builder()
+            .verify_enum_map(true)
+            .synthetic_zero_captions(BTreeMap::from([("*".to_string(), "Unset".to_string())]))
+            .layout(layout)
+            .build();
+        codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    }
+    let opts = codegen::GenerationOptions::builder()
+        .verify_enum_map(true)
+        .enum_layout(codegen::EnumLayout::PerEnum)
+        .enum_map_globs(vec!["AUTHENTICATION_*".to_string()])
+        .build();
+    codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+}
+
+#[test]
+fn verify_enum_map_reports_drift() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use ocsf_proto_gen::type_map::{NamingStrategy, to_enum_variant_name};
+
+    /// Names variants differently on every call, so the map and the protos
+    /// disagree.
+    struct Drifting(AtomicUsize);
+
+    impl NamingStrategy for Drifting {
+        fn enum_variant_name(&self, caption: &str) -> String {
+            let n = self.0.fetch_add(1, Ordering::Relaxed);
+            format!("{}_{n}", to_enum_variant_name(caption))
+        }
+    }
+
+    let naming: Arc<dyn NamingStrategy> = Arc::new(Drifting(AtomicUsize::new(0)));
+    let opts = codegen::GenerationOptions::builder()
+        .naming(naming)
+        .verify_enum_map(true)
+        .build();
+    let err = codegen::generate_to_map(&test_schema(), &["authentication".to_string()], &opts)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("enum-value-map.json disagrees with the generated enum AUTHENTICATION_"),
+        "{err}"
+    );
+    assert!(codegen::estimate(&test_schema(), &["authentication".to_string()], &opts).is_err());
+
+    // Without the option the drift goes unnoticed.
+    let opts = codegen::GenerationOptions {
+        verify_enum_map: false,
+        ..opts
+    };
+    codegen::generate_to_map(&test_schema(), &["authentication".to_string()], &opts).unwrap();
+}
+
+#[test]
+fn schema_field_order_follows_attribute_positions() {
+    let mut schema = test_schema();
+    for (position, attr) in schema
+        .classes
+        .get_mut("authentication")
+        .unwrap()
+        .attributes
+        .values_mut()
+        .rev()
+        .enumerate()
+    {
+        attr.position = position;
+    }
+    let classes = vec!["authentication".to_string()];
+    let events = Path::new("ocsf/v1_7_0/events/iam/iam.proto");
+    let fields = |files: &BTreeMap<std::path::PathBuf, String>| -> Vec<String> {
+        let proto = &files[events];
+        let start = proto.find("message Authentication {").unwrap();
+        proto[start..]
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.starts_with('}'))
+            .filter_map(|line| line.split(" = ").next()?.split(' ').next_back())
+            .map(str::to_string)
+            .collect()
+    };
+
+    let alphabetical =
+        fields(&codegen::generate_to_map(&schema, &classes, &Default::default()).unwrap());
+    let opts = codegen::GenerationOptions {
+        field_order: codegen::FieldOrder::SchemaOrder,
+        ..Default::default()
+    };
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    let schema_order = fields(&files);
+
+    let mut sorted = alphabetical.clone();
+    sorted.sort();
+    assert_eq!(alphabetical, sorted);
+    let mut reversed = alphabetical.clone();
+    reversed.reverse();
+    assert_eq!(schema_order, reversed);
+    // Numbers are assigned in emission order too.
+    assert!(files[events].contains("string unmapped = 1;"));
+    assert_eq!(
+        files,
+        codegen::generate_to_map(&schema, &classes, &opts).unwrap()
+    );
+}
+
+#[test]
+fn excluded_attributes_leave_no_fields_objects_or_enums() {
+    let schema = test_schema();
+    let classes = vec!["authentication".to_string()];
+    let opts = codegen::GenerationOptions {
+        exclude_attributes: BTreeMap::from([
+            (
+                "authentication".to_string(),
+                ["enrichments", "message"].map(String::from).into(),
+            ),
+            (
+                "network_endpoint".to_string(),
+                ["type_id".to_string()].into(),
+            ),
+        ]),
+        ..Default::default()
+    };
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    let file = |path: &str| files[Path::new(path)].as_str();
+
+    let events = file("ocsf/v1_7_0/events/iam/iam.proto");
+    assert!(!events.contains(" enrichments = "));
+    assert!(!events.contains(" message_ = "));
+    assert!(events.contains("NetworkEndpoint src_endpoint = "));
+
+    // Only `enrichments` referenced the enrichment object.
+    let objects = file("ocsf/v1_7_0/objects/objects.proto");
+    assert!(!objects.contains("message Enrichment {"));
+    assert!(objects.contains("message NetworkEndpoint {"));
+    assert!(!objects.contains(" type_id = "));
+    assert!(!file("ocsf/v1_7_0/objects/enums/enums.proto").contains("NETWORK_ENDPOINT_TYPE_ID"));
+    assert!(!file("ocsf/v1_7_0/enum-value-map.json").contains("NETWORK_ENDPOINT_TYPE_ID"));
+    assert!(!file("ocsf/v1_7_0/field-metadata.json").contains("\"Enrichment\""));
+
+    let stats = codegen::check(&schema, &classes, &opts).unwrap();
+    assert_eq!(stats.attributes_excluded, 3);
+    assert_eq!(
+        stats.objects_generated,
+        codegen::check(&schema, &classes, &Default::default())
+            .unwrap()
+            .objects_generated
+            - 1
+    );
+}
+
+#[test]
+fn resolved_objects_match_the_generated_manifest() {
+    let schema = test_schema();
+    let classes = vec!["authentication".to_string()];
+    let excluding = codegen::GenerationOptions {
+        exclude_attributes: BTreeMap::from([(
+            "authentication".to_string(),
+            ["enrichments".to_string()].into(),
+        )]),
+        ..Default::default()
+    };
+
+    for opts in [codegen::GenerationOptions::default(), excluding] {
+        let dir = tempdir();
+        let manifest = codegen::generate_with_manifest(&schema, &classes, &dir, &opts).unwrap();
+        let resolved = codegen::resolved_objects(&schema, &classes, &opts).unwrap();
+        assert_eq!(resolved.into_iter().collect::<Vec<_>>(), manifest.objects);
+    }
+
+    // The raw closure also has the field-less `object`, which is emitted as
+    // `string` rather than getting a message.
+    let raw = resolve_object_graph(&schema, &classes);
+    let resolved = codegen::resolved_objects(&schema, &classes, &Default::default()).unwrap();
+    assert_eq!(raw.difference(&resolved).collect::<Vec<_>>(), ["object"]);
+
+    let err = codegen::resolved_objects(&schema, &["nope".to_string()], &Default::default());
+    assert!(matches!(
+        err,
+        Err(ocsf_proto_gen::error::Error::ClassNotFound { .. })
+    ));
+}
+
+#[test]
+fn version_root_holds_every_versioned_output() {
+    let schema = test_schema();
+    let classes = vec!["authentication".to_string()];
+    let with_options = codegen::GenerationOptions {
+        emit_uid_options: true,
+        ..Default::default()
+    };
+    let prefixed = codegen::GenerationOptions {
+        package_prefix: "com.acme.ocsf".to_string(),
+        ..with_options.clone()
+    };
+    assert_eq!(
+        codegen::version_root(&schema, &Default::default()),
+        Path::new("ocsf/v1_7_0")
+    );
+
+    for opts in [with_options, prefixed] {
+        let root = codegen::version_root(&schema, &opts);
+        let outside: Vec<_> = codegen::planned_outputs(&schema, &classes, &opts)
+            .unwrap()
+            .into_iter()
+            .filter(|path| !path.starts_with(&root))
+            .collect();
+        assert_eq!(outside, [root.parent().unwrap().join("ocsf_options.proto")]);
+    }
+}
+
+#[test]
+fn dry_run_option_renders_everything_and_creates_nothing() {
+    let schema = test_schema();
+    let classes = vec!["authentication".to_string()];
+    let output = tempdir().join("not-created");
+    let opts = codegen::GenerationOptions {
+        dry_run: true,
+        emit_buf_config: true,
+        ..Default::default()
+    };
+
+    let manifest = codegen::generate_with_manifest(&schema, &classes, &output, &opts).unwrap();
+    assert!(!output.exists());
+
+    let written = tempdir();
+    let expected = codegen::generate_with_manifest(
+        &schema,
+        &classes,
+        &written,
+        &codegen::GenerationOptions {
+            dry_run: false,
+            ..opts
+        },
+    )
+    .unwrap();
+    assert_eq!(manifest.protos, expected.protos);
+    assert_eq!(
+        serde_json::to_value(&manifest.stats).unwrap(),
+        serde_json::to_value(&expected.stats).unwrap()
+    );
+}
+
+#[test]
+fn estimate_counts_what_generation_reports() {
+    let schema = test_schema();
+    let classes: Vec<String> = schema.classes.keys().cloned().collect();
+    let option_sets = [
+        codegen::GenerationOptions::default(),
+        codegen::GenerationOptions {
+            deprecated_handling: codegen::DeprecatedHandling::Reserve,
+            min_requirement: Some(codegen::Requirement::Recommended),
+            ..Default::default()
+        },
+        codegen::GenerationOptions {
+            deprecated_handling: codegen::DeprecatedHandling::Emit,
+            exclude_attributes: BTreeMap::from([(
+                "authentication".to_string(),
+                ["user".to_string()].into(),
+            )]),
+            enum_mode: codegen::EnumMode::Int32WithComment,
+            ..Default::default()
+        },
+        codegen::GenerationOptions {
+            layout: codegen::OutputLayout::SingleFile,
+            field_name_source: codegen::FieldNameSource::Caption,
+            ..Default::default()
+        },
+    ];
+
+    for opts in option_sets {
+        let estimated = codegen::estimate(&schema, &classes, &opts).unwrap();
+        let generated = codegen::check(&schema, &classes, &opts).unwrap();
+        assert!(estimated.fields_generated > 0);
+        // Only the order of warnings may differ, in the single-file layout.
+        let sorted = |mut stats: codegen::GenerationStats| {
+            stats.warnings.sort();
+            serde_json::to_value(stats).unwrap()
+        };
+        assert_eq!(sorted(estimated), sorted(generated));
+    }
+
+    let err = codegen::estimate(&schema, &["nope".to_string()], &Default::default());
+    assert!(matches!(
+        err,
+        Err(ocsf_proto_gen::error::Error::ClassNotFound { .. })
+    ));
+
+    // Both check the envelope field numbers.
+    let mut schema = schema;
+    schema.classes.get_mut("authentication").unwrap().uid = 0;
+    let opts = codegen::GenerationOptions::builder()
+        .emit_event_envelope(true)
+        .build();
+    let err = codegen::estimate(&schema, &classes, &opts)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not a valid OcsfEvent field number"), "{err}");
+    assert!(codegen::check(&schema, &classes, &opts).is_err());
+}
+
+#[test]
+fn observable_comments_precede_observable_object_messages() {
+    // `network_endpoint` is observable type 20; `enrichment` is not observable.
+    let schema = test_schema();
+    let classes = vec!["authentication".to_string()];
+    let objects_proto = |opts: &codegen::GenerationOptions| {
+        codegen::generate_to_map(&schema, &classes, opts).unwrap()
+            [Path::new("ocsf/v1_7_0/objects/objects.proto")]
+        .clone()
+    };
+
+    let opts = codegen::GenerationOptions {
+        emit_observable_comments: true,
+        ..Default::default()
+    };
+    let proto = objects_proto(&opts);
+    assert!(proto.contains("\n// Observable type: 20\nmessage NetworkEndpoint {\n"));
+    assert_eq!(proto.matches("// Observable type:").count(), 1);
+
+    assert!(!objects_proto(&Default::default()).contains("Observable type"));
+}
+
+#[test]
+fn base_object_is_string_even_with_fields() {
+    let mut schema = test_schema();
+    let base = schema.objects.get_mut("object").unwrap();
+    base.attributes.insert(
+        "note".to_string(),
+        OcsfAttribute {
+            type_name: "string_t".to_string(),
+            caption: "Note".to_string(),
+            ..default_attr()
+        },
+    );
+    base.attributes.insert(
+        "detail".to_string(),
+        OcsfAttribute {
+            type_name: "object_t".to_string(),
+            caption: "Detail".to_string(),
+            object_type: Some("detail".to_string()),
+            ..default_attr()
+        },
+    );
+    schema.objects.insert(
+        "detail".to_string(),
+        OcsfObject {
+            name: "detail".to_string(),
+            caption: "Detail".to_string(),
+            attributes: BTreeMap::from([(
+                "name".to_string(),
+                OcsfAttribute {
+                    type_name: "string_t".to_string(),
+                    caption: "Name".to_string(),
+                    ..default_attr()
+                },
+            )]),
+            description: String::new(),
+            extends: None,
+            observable: None,
+            deprecated: None,
+        },
+    );
+    let classes = vec!["authentication".to_string()];
+
+    let files = codegen::generate_to_map(&schema, &classes, &Default::default()).unwrap();
+    let events = &files[Path::new("ocsf/v1_7_0/events/iam/iam.proto")];
+    assert!(events.contains("string unmapped = "));
+    let objects = &files[Path::new("ocsf/v1_7_0/objects/objects.proto")];
+    assert!(!objects.contains("message Object {"));
+    // Only the base object references `detail`, so it is not generated.
+    assert!(!objects.contains("message Detail {"));
+
+    let manifest =
+        codegen::generate_with_manifest(&schema, &classes, &tempdir(), &Default::default())
+            .unwrap();
+    assert_eq!(manifest.stats.empty_objects_skipped, 1);
+    assert!(!manifest.objects.contains(&"object".to_string()));
+}
+
+#[test]
+fn missing_base_object_is_still_string() {
+    let mut schema = test_schema();
+    let classes = vec!["authentication".to_string()];
+    let with_base = codegen::check(&schema, &classes, &Default::default()).unwrap();
+    schema.objects.remove("object");
+
+    let opts = codegen::GenerationOptions::builder().strict(true).build();
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+    let events = &files[Path::new("ocsf/v1_7_0/events/iam/iam.proto")];
+    assert!(events.contains("\tstring unmapped = "));
+    let stats = codegen::check(&schema, &classes, &opts).unwrap();
+    assert!(stats.dangling_references.is_empty());
+    assert!(stats.warnings.is_empty(), "{:?}", stats.warnings);
+    assert_eq!(stats.objects_generated, with_base.objects_generated);
+    assert!(
+        ocsf_proto_gen::lint::lint_schema(&schema)
+            .iter()
+            .all(|issue| issue.attribute != "unmapped")
+    );
+}
+
+#[test]
+fn normalize_keeps_referenced_empty_objects() {
+    let mut schema = test_schema();
+    let classes = vec!["authentication".to_string()];
+    let before = codegen::check(&schema, &classes, &Default::default()).unwrap();
+
+    ocsf_proto_gen::schema::normalize(&mut schema);
+
+    assert!(schema.objects.contains_key("object"));
+    let opts = codegen::GenerationOptions::builder().strict(true).build();
+    let stats = codegen::check(&schema, &classes, &opts).unwrap();
+    assert!(stats.dangling_references.is_empty());
+    assert_eq!(stats.objects_generated, before.objects_generated);
+}
+
+#[cfg(feature = "archive")]
+#[test]
+fn archive_holds_the_generated_tree_deterministically() {
+    use std::io::Read;
+
+    let schema = test_schema();
+    let classes = vec!["authentication".to_string()];
+    let opts = codegen::GenerationOptions::default();
+    let files = codegen::generate_to_map(&schema, &classes, &opts).unwrap();
+
+    let dir = tempdir();
+    let path = dir.join("dist").join("ocsf.zip");
+    let stats = codegen::generate_archive(&schema, &classes, &path, &opts).unwrap();
+    assert_eq!(stats.classes_generated, 1);
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(bytes, codegen::zip_archive(&files).unwrap());
+    // Nothing but the archive is written.
+    assert_eq!(walkdir(&dir), std::slice::from_ref(&path));
+
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+    let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+    let expected: Vec<String> = files
+        .keys()
+        .map(|path| path.to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(names, expected);
+    for (index, (path, content)) in files.iter().enumerate() {
+        let mut entry = archive.by_index(index).unwrap();
+        assert_eq!(entry.name(), path.to_str().unwrap());
+        assert_eq!(entry.last_modified(), Some(zip::DateTime::default()));
+        let mut read = String::new();
+        entry.read_to_string(&mut read).unwrap();
+        assert_eq!(&read, content);
+    }
+}
+
 // ── Helpers ────────────────────────────────────────────────────────────
 
 fn tempdir() -> std::path::PathBuf {